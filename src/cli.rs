@@ -16,16 +16,19 @@ use dunce::canonicalize;
 
 use clap::parser::ValuesRef;
 use clap::{Arg, ArgAction, Command};
+use is_terminal::IsTerminal;
 use serde_yaml;
 
 use crate::cmd;
 use crate::config::{
-    Config, Locked, LockedPackage, LockedSource, Manifest, Merge, PartialConfig, PrefixPaths,
-    Validate,
+    Config, Dependency, Locked, LockedPackage, LockedSource, Manifest, Merge, PartialConfig,
+    PrefixPaths, Validate,
 };
 use crate::error::*;
+use crate::git::Git;
 use crate::resolver::DependencyResolver;
 use crate::sess::{Session, SessionArenas, SessionIo};
+use crate::target::TargetSet;
 use tokio::runtime::Runtime;
 
 /// Inner main function which can return an error.
@@ -48,6 +51,23 @@ pub fn main() -> Result<()> {
                 .global(true)
                 .help("Sets a custom root working directory"),
         )
+        .arg(
+            Arg::new("manifest")
+                .long("manifest")
+                .num_args(1)
+                .global(true)
+                .conflicts_with("dir")
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("Use the given manifest instead of searching for `Bender.yml`, with its directory as root"),
+        )
+        .arg(
+            Arg::new("lock")
+                .long("lock")
+                .num_args(1)
+                .global(true)
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("Use the given lockfile instead of the one next to the manifest"),
+        )
         .arg(
             Arg::new("local")
                 .long("local")
@@ -56,6 +76,47 @@ pub fn main() -> Result<()> {
                 .action(ArgAction::SetTrue)
                 .help("Disables fetching of remotes (e.g. for air-gapped computers)"),
         )
+        .arg(
+            Arg::new("git-timeout")
+                .long("git-timeout")
+                .num_args(1)
+                .global(true)
+                .value_parser(clap::value_parser!(u64))
+                .help("Sets a timeout in seconds for git network operations"),
+        )
+        .arg(
+            Arg::new("strict-names")
+                .long("strict-names")
+                .global(true)
+                .num_args(0)
+                .action(ArgAction::SetTrue)
+                .help("Treats a mismatch of dependency and package names as an error"),
+        )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .global(true)
+                .num_args(0)
+                .action(ArgAction::SetTrue)
+                .help("Treats likely mistakes in the manifests as errors, implies `--strict-names`"),
+        )
+        .arg(
+            Arg::new("color")
+                .long("color")
+                .global(true)
+                .num_args(1)
+                .default_value("auto")
+                .value_parser(["auto", "always", "never"])
+                .help("Controls the use of color in diagnostics, `auto` colors a terminal unless `NO_COLOR` is set"),
+        )
+        .arg(
+            Arg::new("progress-json")
+                .long("progress-json")
+                .global(true)
+                .num_args(0)
+                .action(ArgAction::SetTrue)
+                .help("Emit progress as JSON lines on stderr instead of a progress bar, e.g. for IDE integration"),
+        )
         .subcommand(
             Command::new("update")
                 .about("Update the dependencies")
@@ -73,6 +134,70 @@ pub fn main() -> Result<()> {
                         .num_args(0)
                         .action(ArgAction::SetTrue)
                         .help("Disables checkout of dependencies"),
+                )
+                .arg(
+                    Arg::new("target")
+                        .short('t')
+                        .long("target")
+                        .num_args(1)
+                        .action(ArgAction::Append)
+                        .help("Applies the overrides restricted to the given targets"),
+                )
+                .arg(
+                    Arg::new("locked-only")
+                        .long("locked-only")
+                        .num_args(0)
+                        .action(ArgAction::SetTrue)
+                        .help("Only resolve to the revisions already present in the lockfile"),
+                )
+                .arg(
+                    Arg::new("allow-downgrade")
+                        .long("allow-downgrade")
+                        .num_args(0)
+                        .action(ArgAction::SetTrue)
+                        .help("Accept resolving dependencies to older versions than locked without confirmation"),
+                )
+                .arg(
+                    Arg::new("repro")
+                        .long("repro")
+                        .num_args(1)
+                        .value_name("FILE")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .help("If resolution fails, write an archive to reproduce it offline to the given file"),
+                )
+                .arg(
+                    Arg::new("save-versions")
+                        .long("save-versions")
+                        .num_args(1)
+                        .value_name("FILE")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .help("Write the resolved version of every dependency on a version tag to the given file"),
+                )
+                .arg(
+                    Arg::new("dry-run")
+                        .long("dry-run")
+                        .num_args(0)
+                        .action(ArgAction::SetTrue)
+                        .help("Print the changes to the lockfile without writing it or checking out the dependencies"),
+                )
+                .arg(
+                    Arg::new("no-prune-tmp")
+                        .long("no-prune-tmp")
+                        .num_args(0)
+                        .action(ArgAction::SetTrue)
+                        .help("Keep the manifests of path dependencies left in `.bender/tmp` by earlier runs"),
+                ),
+        )
+        .subcommand(
+            Command::new("lock")
+                .about("Resolve the dependencies and write the lockfile without checking them out")
+                .arg(
+                    Arg::new("target")
+                        .short('t')
+                        .long("target")
+                        .num_args(1)
+                        .action(ArgAction::Append)
+                        .help("Applies the overrides restricted to the given targets"),
                 ),
         )
         .subcommand(cmd::path::new())
@@ -80,13 +205,23 @@ pub fn main() -> Result<()> {
         .subcommand(cmd::clone::new())
         .subcommand(cmd::packages::new())
         .subcommand(cmd::sources::new())
+        .subcommand(cmd::file_info::new())
         .subcommand(cmd::completion::new())
         .subcommand(cmd::config::new())
         .subcommand(cmd::script::new())
         .subcommand(cmd::checkout::new())
         .subcommand(cmd::vendor::new())
         .subcommand(cmd::fusesoc::new())
-        .subcommand(cmd::init::new());
+        .subcommand(cmd::init::new())
+        .subcommand(cmd::bump::new())
+        .subcommand(cmd::resolve::new())
+        .subcommand(cmd::clean::new())
+        .subcommand(cmd::diff_lock::new())
+        .subcommand(cmd::fmt::new())
+        .subcommand(cmd::export_deps::new())
+        .subcommand(cmd::doctor::new())
+        .subcommand(cmd::license::new())
+        .subcommand(cmd::plugins::new());
 
     // Add the `--debug` option in debug builds.
     let app = if cfg!(debug_assertions) {
@@ -103,13 +238,34 @@ pub fn main() -> Result<()> {
     };
 
     // Parse the arguments.
-    let matches = app.clone().get_matches();
+    // The helper for dynamic completion is not part of the generated
+    // completion scripts.
+    let matches = app
+        .clone()
+        .subcommand(cmd::completion::new_complete())
+        .get_matches();
 
     // Enable debug outputs if needed.
     if matches.contains_id("debug") && matches.get_flag("debug") {
         ENABLE_DEBUG.store(true, std::sync::atomic::Ordering::Relaxed);
     }
 
+    // Enable colored outputs if needed.
+    let color = match matches.get_one::<String>("color").map(String::as_str) {
+        Some("always") => true,
+        Some("never") => false,
+        _ => {
+            !matches!(std::env::var_os("NO_COLOR"), Some(v) if !v.is_empty())
+                && std::io::stderr().is_terminal()
+        }
+    };
+    ENABLE_COLOR.store(color, std::sync::atomic::Ordering::Relaxed);
+
+    // Emit machine-readable progress if needed.
+    if matches.get_flag("progress-json") {
+        crate::progress::enable_json();
+    }
+
     if let Some(("init", matches)) = matches.subcommand() {
         return cmd::init::run(matches);
     }
@@ -129,49 +285,161 @@ pub fn main() -> Result<()> {
         }
     }
 
+    // Relocate the checkouts for this invocation only.
+    let checkout_prefix = match matches.subcommand() {
+        Some(("checkout", intern_matches)) => match intern_matches.get_one::<String>("prefix") {
+            Some(prefix) => Some(
+                std::env::current_dir()
+                    .map_err(|cause| Error::chain("Failed to determine current directory.", cause))?
+                    .join(prefix),
+            ),
+            None => None,
+        },
+        _ => None,
+    };
+
     // Determine the root working directory, which has either been provided via
     // the -d/--dir switch, or by searching upwards in the file system
     // hierarchy.
-    let root_dir: PathBuf = match matches.get_one::<String>("dir") {
-        Some(d) => canonicalize(d).map_err(|cause| {
-            Error::chain(format!("Failed to canonicalize path {:?}.", d), cause)
-        })?,
-        None => find_package_root(Path::new("."))
-            .map_err(|cause| Error::chain("Cannot find root directory of package.", cause))?,
+    // An explicitly given manifest determines the root directory instead.
+    let (root_dir, manifest_path) = match matches.get_one::<PathBuf>("manifest") {
+        Some(m) => {
+            let manifest_path = canonicalize(m).map_err(|cause| {
+                Error::chain(format!("Failed to canonicalize path {:?}.", m), cause)
+            })?;
+            (manifest_path.parent().unwrap().to_path_buf(), manifest_path)
+        }
+        None => {
+            let root_dir: PathBuf = match matches.get_one::<String>("dir") {
+                Some(d) => canonicalize(d).map_err(|cause| {
+                    Error::chain(format!("Failed to canonicalize path {:?}.", d), cause)
+                })?,
+                None => find_package_root(Path::new(".")).map_err(|cause| {
+                    Error::chain("Cannot find root directory of package.", cause)
+                })?,
+            };
+            let manifest_path = root_dir.join("Bender.yml");
+            (root_dir, manifest_path)
+        }
     };
     debugln!("main: root dir {:?}", root_dir);
 
-    // Parse the manifest file of the package.
-    let manifest_path = root_dir.join("Bender.yml");
-    let manifest = read_manifest(&manifest_path)?;
-    debugln!("main: {:#?}", manifest);
+    // The lockfile is named after the manifest, e.g. `Bender.lock` for
+    // `Bender.yml`, unless given explicitly.
+    let lock_path = match matches.get_one::<PathBuf>("lock") {
+        Some(l) => {
+            let lock_path = std::env::current_dir()
+                .map_err(|cause| Error::chain("Failed to determine current directory.", cause))?
+                .join(l);
+            // The lockfile may not exist yet, but its directory should.
+            match (lock_path.parent(), lock_path.file_name()) {
+                (Some(dir), Some(name)) => canonicalize(dir)
+                    .map(|dir| dir.join(name))
+                    .unwrap_or(lock_path),
+                _ => lock_path,
+            }
+        }
+        None => manifest_path.with_extension("lock"),
+    };
+    debugln!("main: lockfile {:?}", lock_path);
+
+    // Compare lockfiles, which does not require a valid manifest.
+    if let Some(("diff-lock", matches)) = matches.subcommand() {
+        return cmd::diff_lock::run(&root_dir, &lock_path, matches);
+    }
+
+    // Format the manifest, which only requires it to be valid on its own.
+    if let Some(("fmt", matches)) = matches.subcommand() {
+        return cmd::fmt::run(&manifest_path, matches);
+    }
 
     // Gather and parse the tool configuration.
-    let config = load_config(
+    let mut config = load_config(
         &root_dir,
-        matches!(matches.subcommand(), Some(("update", _))),
+        matches!(matches.subcommand(), Some(("update" | "lock", _))),
     )?;
+    if let Some(timeout) = matches.get_one::<u64>("git-timeout") {
+        config.git_timeout_secs = Some(*timeout);
+    }
+    if matches.get_flag("strict-names") {
+        config.strict_names = true;
+    }
+    if matches.get_flag("strict") {
+        config.strict = true;
+        config.strict_names = true;
+    }
     debugln!("main: {:#?}", config);
 
+    // Read-only commands may inspect the package at another git revision
+    // without checking it out.
+    let rev = match matches.subcommand() {
+        Some(("packages" | "parents", matches)) => matches.get_one::<String>("rev"),
+        _ => None,
+    };
+
+    // Parse the manifest file of the package.
+    let manifest = match rev {
+        Some(rev) => {
+            let data = read_file_at_rev(&root_dir, rev, &manifest_path, &config.git)?;
+            parse_manifest(&data, &manifest_path).map_err(|cause| {
+                Error::chain(format!("In manifest at revision `{}`:", rev), cause)
+            })?
+        }
+        None => read_manifest(&manifest_path)?,
+    };
+    let manifest = add_workspace_members(manifest)?;
+    debugln!("main: {:#?}", manifest);
+
+    // Unknown fields are kept for external tools, but may also be typos.
+    if !config.allow_extra_fields {
+        for field in manifest.unknown_fields() {
+            warnln!(
+                "Unknown field `{}` in manifest {:?}. Set `allow_extra_fields` in the configuration to silence this warning.",
+                field,
+                manifest_path
+            );
+        }
+    }
+
     // Assemble the session.
     let sess_arenas = SessionArenas::new();
     let sess = Session::new(
         &root_dir,
+        &manifest_path,
+        &lock_path,
         &manifest,
         &config,
         &sess_arenas,
         matches.get_flag("local"),
         force_fetch,
+        checkout_prefix,
     );
 
     // Read the existing lockfile.
-    let lock_path = root_dir.join("Bender.lock");
-    let locked_existing = if lock_path.exists() {
+    let locked_existing = if let Some(rev) = rev {
+        // Never resolve and write a lockfile for another revision.
+        let data = read_file_at_rev(&root_dir, rev, &lock_path, &config.git)?;
+        Some(
+            parse_lockfile(&data, &lock_path, &root_dir).map_err(|cause| {
+                Error::chain(format!("In lockfile at revision `{}`:", rev), cause)
+            })?,
+        )
+    } else if lock_path.exists() {
         Some(read_lockfile(&lock_path, &root_dir)?)
     } else {
         None
     };
 
+    // Diagnose the manifest and lockfile as they are, without resolving.
+    if let Some(("doctor", matches)) = matches.subcommand() {
+        return cmd::doctor::run(&sess, locked_existing.as_ref(), matches);
+    }
+
+    // Complete values for the shell without resolving the dependencies.
+    if let Some(("__complete", matches)) = matches.subcommand() {
+        return cmd::completion::run_complete(&sess, locked_existing.as_ref(), matches);
+    }
+
     // Resolve the dependencies if the lockfile does not exist or is outdated.
     let locked = match matches.subcommand() {
         Some((command, matches)) => {
@@ -179,7 +447,7 @@ pub fn main() -> Result<()> {
             // execute pre-dependency-fetch commands
             if command == "fusesoc" && matches.get_flag("single") {
                 return cmd::fusesoc::run_single(&sess, matches);
-            } else if command == "update" || locked_existing.is_none() {
+            } else if command == "update" || command == "lock" || locked_existing.is_none() {
                 if manifest.frozen {
                     return Err(Error::new(format!(
                         "Refusing to update dependencies because the package is frozen.
@@ -188,9 +456,70 @@ pub fn main() -> Result<()> {
                     )));
                 }
                 debugln!("main: lockfile {:?} outdated", lock_path);
-                let res = DependencyResolver::new(&sess);
+                let dry_run = command == "update" && matches.get_flag("dry-run");
+                if !(dry_run || command == "update" && matches.get_flag("no-prune-tmp")) {
+                    sess.prune_tmp_manifests()?;
+                }
+                // Apply the overrides restricted to the targets of the command,
+                // if it takes any.
+                let targets = match matches.try_get_many::<String>("target") {
+                    Ok(targets) => Some(TargetSet::new(targets.into_iter().flatten())),
+                    Err(_) => {
+                        if !sess.config.target_overrides.is_empty() {
+                            warnln!(
+                                "Resolving the dependencies without targets, so the overrides \
+                                 restricted to targets do not apply. Run `bender update -t \
+                                 <target>` to apply them."
+                            );
+                        }
+                        None
+                    }
+                };
+                let mut res = DependencyResolver::new(&sess, targets);
+                if command == "update" {
+                    if let Some(ref locked) = locked_existing {
+                        // A dry run only reports the downgrades.
+                        res.guard_downgrades(
+                            locked,
+                            matches.get_flag("allow-downgrade") || dry_run,
+                        );
+                    }
+                    if let Some(path) = matches.get_one::<PathBuf>("repro") {
+                        res.repro_on_failure(path);
+                    }
+                }
+                if command == "update" && matches.get_flag("locked-only") {
+                    match locked_existing {
+                        Some(ref locked) => res.restrict_to_locked(locked),
+                        None => {
+                            return Err(Error::new(
+                                "Cannot restrict resolution to the lockfile since there is no lockfile.",
+                            ))
+                        }
+                    }
+                }
                 let locked_new = res.resolve()?;
-                write_lockfile(&locked_new, &root_dir.join("Bender.lock"), &root_dir)?;
+                if dry_run {
+                    let empty = Locked {
+                        packages: Default::default(),
+                    };
+                    cmd::diff_lock::print_changes(
+                        locked_existing.as_ref().unwrap_or(&empty),
+                        &locked_new,
+                    );
+                    return Ok(());
+                }
+                write_lockfile(&locked_new, &lock_path, &root_dir)?;
+                if command == "update" {
+                    if let Some(path) = matches.get_one::<PathBuf>("save-versions") {
+                        write_versions(&locked_new, path)?;
+                    }
+                }
+                // Only write the lockfile, without checking out the dependencies
+                // or maintaining the package links.
+                if command == "lock" {
+                    return Ok(());
+                }
                 locked_new
             } else {
                 debugln!("main: lockfile {:?} up-to-date", lock_path);
@@ -203,8 +532,10 @@ pub fn main() -> Result<()> {
     };
     sess.load_locked(&locked)?;
 
-    // Ensure the locally linked packages are up-to-date.
-    {
+    // Ensure the locally linked packages are up-to-date. Relocated checkouts
+    // are only staged, the links keep pointing to the regular checkouts, and
+    // another revision leaves the working tree alone.
+    if sess.checkout_prefix.is_none() && rev.is_none() {
         let io = SessionIo::new(&sess);
         for (path, pkg_name) in &sess.manifest.workspace.package_links {
             debugln!("main: maintaining link to {} at {:?}", pkg_name, path);
@@ -291,6 +622,7 @@ pub fn main() -> Result<()> {
         Some(("clone", matches)) => cmd::clone::run(&sess, &root_dir, matches),
         Some(("packages", matches)) => cmd::packages::run(&sess, matches),
         Some(("sources", matches)) => cmd::sources::run(&sess, matches),
+        Some(("file-info", matches)) => cmd::file_info::run(&sess, matches),
         Some(("config", matches)) => cmd::config::run(&sess, matches),
         Some(("script", matches)) => cmd::script::run(&sess, matches),
         Some(("checkout", matches)) => cmd::checkout::run(&sess, matches),
@@ -302,6 +634,12 @@ pub fn main() -> Result<()> {
             }
         }
         Some(("vendor", matches)) => cmd::vendor::run(&sess, matches),
+        Some(("bump", matches)) => cmd::bump::run(&sess, matches),
+        Some(("resolve", matches)) => cmd::resolve::run(&sess, matches),
+        Some(("clean", matches)) => cmd::clean::run(&sess, matches),
+        Some(("license", matches)) => cmd::license::run(&sess, matches),
+        Some(("plugins", matches)) => cmd::plugins::run(&sess, matches),
+        Some(("export-deps", matches)) => cmd::export_deps::run(&root_dir, &locked, matches),
         Some(("fusesoc", matches)) => cmd::fusesoc::run(&sess, matches),
         Some((plugin, matches)) => execute_plugin(&sess, plugin, matches.get_many::<OsString>("")),
         _ => Ok(()),
@@ -376,12 +714,16 @@ fn find_package_root(from: &Path) -> Result<PathBuf> {
 
 /// Read a package manifest from a file.
 pub fn read_manifest(path: &Path) -> Result<Manifest> {
-    use crate::config::PartialManifest;
-    use std::fs::File;
     debugln!("read_manifest: {:?}", path);
-    let file = File::open(path)
+    let data = std::fs::read_to_string(path)
         .map_err(|cause| Error::chain(format!("Cannot open manifest {:?}.", path), cause))?;
-    let partial: PartialManifest = serde_yaml::from_reader(file)
+    parse_manifest(&data, path)
+}
+
+/// Parse a package manifest as if it was read from the given path.
+fn parse_manifest(data: &str, path: &Path) -> Result<Manifest> {
+    use crate::config::PartialManifest;
+    let partial: PartialManifest = serde_yaml::from_str(data)
         .map_err(|cause| Error::chain(format!("Syntax error in manifest {:?}.", path), cause))?;
     let manifest = partial
         .validate()
@@ -389,6 +731,50 @@ pub fn read_manifest(path: &Path) -> Result<Manifest> {
     manifest.prefix_paths(path.parent().unwrap())
 }
 
+/// Read a file of the package at a git revision, without checking it out.
+///
+/// The file is looked up by its name in the root directory of the package.
+fn read_file_at_rev(root_dir: &Path, rev: &str, path: &Path, git: &String) -> Result<String> {
+    let file = path.file_name().unwrap().to_string_lossy();
+    debugln!("read_file_at_rev: {} at {}", file, rev);
+    let rt = Runtime::new()?;
+    rt.block_on(Git::new(root_dir, git).cat_file(format!("{}:./{}", rev, file)))
+        .map_err(|cause| {
+            Error::chain(
+                format!("Cannot read `{}` at revision `{}`.", file, rev),
+                cause,
+            )
+        })
+}
+
+/// Aggregate the members of a workspace into its manifest.
+///
+/// Every member becomes a path dependency of the workspace package. Their
+/// dependencies are thus resolved together, such that conflicting requirements
+/// are reported as for any other dependencies, and their sources are included
+/// in dependency order.
+fn add_workspace_members(mut manifest: Manifest) -> Result<Manifest> {
+    for member in std::mem::take(&mut manifest.workspace.members) {
+        let member_manifest = read_manifest(&member.join("Bender.yml")).map_err(|cause| {
+            Error::chain(format!("Cannot load workspace member {:?}.", member), cause)
+        })?;
+        let name = member_manifest.package.name;
+        match manifest.dependencies.get(&name) {
+            None => {
+                manifest.dependencies.insert(name, Dependency::Path(member));
+            }
+            Some(Dependency::Path(path)) if path == &member => (),
+            Some(_) => {
+                return Err(Error::new(format!(
+                    "Workspace member {:?} conflicts with dependency `{}` of the workspace.",
+                    member, name
+                )))
+            }
+        }
+    }
+    Ok(manifest)
+}
+
 /// Load a configuration by traversing a directory hierarchy upwards.
 fn load_config(from: &Path, warn_config_loaded: bool) -> Result<Config> {
     #[cfg(unix)]
@@ -457,6 +843,14 @@ fn load_config(from: &Path, warn_config_loaded: bool) -> Result<Config> {
         git: Some("git".into()),
         overrides: None,
         plugins: None,
+        git_timeout_secs: None,
+        git_config: None,
+        respect_nested_lock: None,
+        check_include_collisions: None,
+        registry: None,
+        strict_names: None,
+        strict: None,
+        allow_extra_fields: None,
     };
     out = out.merge(default_cfg);
 
@@ -470,6 +864,11 @@ fn load_config(from: &Path, warn_config_loaded: bool) -> Result<Config> {
         .into_iter()
         .map(|(k, v)| (k.to_lowercase(), v))
         .collect();
+    out.target_overrides = out
+        .target_overrides
+        .into_iter()
+        .map(|(k, v)| (k.to_lowercase(), v))
+        .collect();
 
     Ok(out)
 }
@@ -492,12 +891,16 @@ fn maybe_load_config(path: &Path, warn_config_loaded: bool) -> Result<Option<Par
 }
 
 /// Read a lock file.
-fn read_lockfile(path: &Path, root_dir: &Path) -> Result<Locked> {
+pub fn read_lockfile(path: &Path, root_dir: &Path) -> Result<Locked> {
     debugln!("read_lockfile: {:?}", path);
-    use std::fs::File;
-    let file = File::open(path)
+    let data = std::fs::read_to_string(path)
         .map_err(|cause| Error::chain(format!("Cannot open lockfile {:?}.", path), cause))?;
-    let locked_loaded: Result<Locked> = serde_yaml::from_reader(file)
+    parse_lockfile(&data, path, root_dir)
+}
+
+/// Parse a lock file as if it was read from the given path.
+fn parse_lockfile(data: &str, path: &Path, root_dir: &Path) -> Result<Locked> {
+    let locked_loaded: Result<Locked> = serde_yaml::from_str(data)
         .map_err(|cause| Error::chain(format!("Syntax error in lockfile {:?}.", path), cause));
     // Make relative paths absolute
     Ok(Locked {
@@ -517,6 +920,7 @@ fn read_lockfile(path: &Path, root_dir: &Path) -> Result<Locked> {
                                 path.clone()
                             }),
                             dependencies: pack.1.dependencies.clone(),
+                            sparse: pack.1.sparse.clone(),
                         },
                     )
                 } else {
@@ -527,6 +931,21 @@ fn read_lockfile(path: &Path, root_dir: &Path) -> Result<Locked> {
     })
 }
 
+/// Write the resolved version of every package that has one as `name: version`
+/// lines, e.g. for release pinning outside of bender.
+///
+/// Git dependencies only have a version if their revision carries a version
+/// tag.
+fn write_versions(locked: &Locked, path: &Path) -> Result<()> {
+    let versions: String = locked
+        .packages
+        .iter()
+        .filter_map(|(name, pkg)| Some(format!("{}: {}\n", name, pkg.version.as_ref()?)))
+        .collect();
+    std::fs::write(path, versions)
+        .map_err(|cause| Error::chain(format!("Cannot write versions to {:?}.", path), cause))
+}
+
 /// Write a lock file.
 fn write_lockfile(locked: &Locked, path: &Path, root_dir: &Path) -> Result<()> {
     debugln!("write_lockfile: {:?}", path);
@@ -546,6 +965,7 @@ fn write_lockfile(locked: &Locked, path: &Path, root_dir: &Path) -> Result<()> {
                                 path.strip_prefix(root_dir).unwrap_or(path).to_path_buf(),
                             ),
                             dependencies: pack.1.dependencies.clone(),
+                            sparse: pack.1.sparse.clone(),
                         },
                     )
                 } else {
@@ -555,6 +975,14 @@ fn write_lockfile(locked: &Locked, path: &Path, root_dir: &Path) -> Result<()> {
             .collect(),
     };
 
+    // A lockfile given with `--lock` may point to a directory that does not
+    // exist, which is not created implicitly.
+    if let Some(parent) = path.parent().filter(|p| !p.is_dir()) {
+        return Err(Error::new(format!(
+            "Cannot write lockfile {:?}, since directory {:?} does not exist.",
+            path, parent
+        )));
+    }
     use std::fs::File;
     let file = File::create(path)
         .map_err(|cause| Error::chain(format!("Cannot create lockfile {:?}.", path), cause))?;
@@ -583,9 +1011,28 @@ fn execute_plugin(
     };
     debugln!("main: found plugin {:#?}", plugin);
 
+    // Provide the resolved sources to the plugin, such that it need not call
+    // `bender sources` itself. The file is removed once the plugin is done.
+    let srcs = runtime.block_on(io.sources())?;
+    let mut sources_file = tempfile::Builder::new()
+        .prefix("bender-sources-")
+        .suffix(".json")
+        .tempfile()
+        .map_err(|cause| Error::chain("Failed to create temporary sources file.", cause))?;
+    serde_json::to_writer_pretty(&mut sources_file, &srcs.flatten())
+        .map_err(|cause| Error::chain("Failed to serialize source file manifest.", cause))?;
+
     // Assemble a command that executes the plugin with the appropriate
     // environment and forwards command line arguments.
-    let mut cmd = SysCommand::new(&plugin.path);
+    let mut cmd = match plugin.interpreter {
+        Some(ref interpreter) => {
+            let mut cmd = SysCommand::new(interpreter);
+            cmd.arg(&plugin.path);
+            cmd
+        }
+        None => SysCommand::new(&plugin.path),
+    };
+    cmd.args(&plugin.args);
     cmd.env(
         "BENDER",
         std::env::current_exe()
@@ -597,6 +1044,9 @@ fn execute_plugin(
             .map_err(|cause| Error::chain("Failed to determine current directory.", cause))?,
     );
     cmd.env("BENDER_MANIFEST_DIR", sess.root);
+    cmd.env("BENDER_ROOT", sess.root);
+    cmd.env("BENDER_MANIFEST", sess.manifest_path);
+    cmd.env("BENDER_SOURCES_JSON", sources_file.path());
     cmd.current_dir(sess.root);
     if let Some(args) = matches {
         cmd.args(args);
@@ -613,5 +1063,6 @@ fn execute_plugin(
     })?;
 
     // Don't bother to do anything after the plugin was run.
+    drop(sources_file);
     std::process::exit(stat.code().unwrap_or(1));
 }
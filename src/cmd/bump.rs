@@ -0,0 +1,207 @@
+// Copyright (c) 2024 ETH Zurich
+
+//! The `bump` subcommand.
+
+use std::path::Path;
+use std::process::Command as SysCommand;
+
+use clap::builder::PossibleValue;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use tokio::runtime::Runtime;
+
+use crate::config;
+use crate::error::*;
+use crate::sess::{DependencyVersions, Session, SessionIo};
+
+/// Assemble the `bump` subcommand.
+pub fn new() -> Command {
+    Command::new("bump")
+        .about("Bump the version requirement of a dependency to the latest available version")
+        .arg(
+            Arg::new("name")
+                .required(true)
+                .num_args(1)
+                .help("Name of the dependency to bump"),
+        )
+        .arg(
+            Arg::new("level")
+                .long("level")
+                .num_args(1)
+                .default_value("minor")
+                .value_parser([
+                    PossibleValue::new("patch"),
+                    PossibleValue::new("minor"),
+                    PossibleValue::new("major"),
+                ])
+                .help("Highest version component allowed to change"),
+        )
+        .arg(
+            Arg::new("update")
+                .long("update")
+                .num_args(0)
+                .action(ArgAction::SetTrue)
+                .help("Re-resolve the dependencies after bumping"),
+        )
+}
+
+/// Execute the `bump` subcommand.
+pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
+    let name = matches.get_one::<String>("name").unwrap().to_lowercase();
+    let level = matches.get_one::<String>("level").unwrap();
+
+    // Only version requirements in the root manifest can be bumped.
+    match sess.manifest.dependencies.get(&name) {
+        Some(config::Dependency::GitVersion(..)) => (),
+        Some(_) => {
+            return Err(Error::new(format!(
+                "Dependency `{}` is not a git dependency with a version requirement.",
+                name
+            )))
+        }
+        None => {
+            return Err(Error::new(format!(
+                "Dependency `{}` is not a direct dependency of this package.",
+                name
+            )))
+        }
+    }
+    let id = sess.dependency_with_name(&name)?;
+    let current = match sess.dependency(id).version {
+        Some(ref v) => v.clone(),
+        None => {
+            return Err(Error::new(format!(
+                "Dependency `{}` is not locked to a version. Please run `bender update` first.",
+                name
+            )))
+        }
+    };
+
+    // Pick the highest available version within the bump level.
+    let rt = Runtime::new()?;
+    let io = SessionIo::new(sess);
+    let versions = match rt.block_on(io.dependency_versions(id, false))? {
+        DependencyVersions::Git(gv) => gv.versions,
+        _ => unreachable!(),
+    };
+    let latest = versions
+        .into_iter()
+        .map(|(v, _)| v)
+        .filter(|v| v.pre.is_empty())
+        .filter(|v| match level.as_str() {
+            "patch" => v.major == current.major && v.minor == current.minor,
+            "minor" => v.major == current.major,
+            _ => true,
+        })
+        .max()
+        .unwrap_or_else(|| current.clone());
+    if latest <= current {
+        stageln!(
+            "Bumping",
+            "{} already at latest {} version {}",
+            name,
+            level,
+            current
+        );
+        return Ok(());
+    }
+
+    // Rewrite the requirement in the manifest.
+    let manifest_path = sess.manifest_path;
+    let content = std::fs::read_to_string(manifest_path).map_err(|cause| {
+        Error::chain(format!("Cannot read manifest {:?}.", manifest_path), cause)
+    })?;
+    let content = replace_version(&content, &name, &latest.to_string()).ok_or_else(|| {
+        Error::new(format!(
+            "Unable to locate the version requirement of `{}` in {:?}. Please set it to `{}` manually.",
+            name, manifest_path, latest
+        ))
+    })?;
+    std::fs::write(manifest_path, content).map_err(|cause| {
+        Error::chain(format!("Cannot write manifest {:?}.", manifest_path), cause)
+    })?;
+    stageln!("Bumping", "{} from {} to {}", name, current, latest);
+
+    if matches.get_flag("update") {
+        update(sess.root)?;
+    }
+    Ok(())
+}
+
+/// Replace the version requirement of a dependency in the manifest text.
+///
+/// Comparison operators of the existing requirement are kept. Returns `None`
+/// if the requirement cannot be located or consists of multiple comparators.
+fn replace_version(manifest: &str, name: &str, version: &str) -> Option<String> {
+    let lines: Vec<&str> = manifest.lines().collect();
+    let indent = |line: &str| line.len() - line.trim_start().len();
+
+    // Locate the dependency entry within the `dependencies` section.
+    let deps = lines.iter().position(|l| l.starts_with("dependencies:"))?;
+    let entry = (deps + 1..lines.len())
+        .take_while(|&i| lines[i].trim().is_empty() || indent(lines[i]) > 0)
+        .find(|&i| lines[i].trim_start().starts_with(&format!("{}:", name)))?;
+
+    // The requirement is either on the same line or on a nested one.
+    let line = (entry..lines.len())
+        .take_while(|&i| {
+            i == entry || lines[i].trim().is_empty() || indent(lines[i]) > indent(lines[entry])
+        })
+        .find(|&i| lines[i].contains("version:"))?;
+
+    let text = lines[line];
+    let start = text.find("version:")? + "version:".len();
+    let rest = &text[start..];
+    let value_start = start + (rest.len() - rest.trim_start().len());
+    let value_end = text[value_start..]
+        .find([',', '}', '#'])
+        .map(|i| value_start + i)
+        .unwrap_or(text.len());
+    let value = text[value_start..value_end].trim_end();
+    let quote = if value.starts_with('"') || value.starts_with('\'') {
+        &value[..1]
+    } else {
+        ""
+    };
+    let req = value.trim_matches(['"', '\'']);
+    if req.contains(',') {
+        return None;
+    }
+    let op: String = req
+        .chars()
+        .take_while(|c| matches!(c, '=' | '<' | '>' | '~' | '^' | ' '))
+        .collect();
+
+    let new_line = format!(
+        "{}{}{}{}{}{}",
+        &text[..value_start],
+        quote,
+        op,
+        version,
+        quote,
+        &text[value_start + value.len()..]
+    );
+    let mut out: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+    out[line] = new_line;
+    let mut out = out.join("\n");
+    if manifest.ends_with('\n') {
+        out.push('\n');
+    }
+    Some(out)
+}
+
+/// Re-resolve the dependencies of the package at `root`.
+fn update(root: &Path) -> Result<()> {
+    let stat = SysCommand::new(
+        std::env::current_exe()
+            .map_err(|cause| Error::chain("Failed to determine current executable.", cause))?,
+    )
+    .arg("-d")
+    .arg(root)
+    .arg("update")
+    .status()
+    .map_err(|cause| Error::chain("Failed to run `bender update`.", cause))?;
+    if !stat.success() {
+        return Err(Error::new("Failed to re-resolve the dependencies."));
+    }
+    Ok(())
+}
@@ -3,7 +3,7 @@
 
 //! The `checkout` subcommand.
 
-use clap::{ArgMatches, Command};
+use clap::{Arg, ArgMatches, Command};
 use tokio::runtime::Runtime;
 
 use crate::error::*;
@@ -11,7 +11,15 @@ use crate::sess::{Session, SessionIo};
 
 /// Assemble the `checkout` subcommand.
 pub fn new() -> Command {
-    Command::new("checkout").about("Checkout all dependencies referenced in the Lock file")
+    Command::new("checkout")
+        .about("Checkout all dependencies referenced in the Lock file")
+        .arg(
+            Arg::new("prefix")
+                .long("prefix")
+                .num_args(1)
+                .value_name("DIR")
+                .help("Check out the git dependencies below this directory instead of the configured one"),
+        )
 }
 
 /// Execute the `checkout` subcommand.
@@ -0,0 +1,137 @@
+// Copyright (c) 2024 ETH Zurich
+
+//! The `clean` subcommand.
+
+use std::path::Path;
+
+use clap::{Arg, ArgAction, ArgGroup, ArgMatches, Command};
+use indexmap::IndexSet;
+use walkdir::WalkDir;
+
+use crate::error::*;
+use crate::sess::{git_db_name, DependencySource, Session, SessionIo};
+
+/// Assemble the `clean` subcommand.
+pub fn new() -> Command {
+    Command::new("clean")
+        .about("Remove checkouts and git databases not referenced by the lockfile")
+        .arg(
+            Arg::new("checkouts")
+                .long("checkouts")
+                .num_args(0)
+                .action(ArgAction::SetTrue)
+                .help("Remove checkouts of dependencies that are not locked"),
+        )
+        .arg(
+            Arg::new("database")
+                .long("database")
+                .num_args(0)
+                .action(ArgAction::SetTrue)
+                .help("Remove git databases of dependencies that are not locked"),
+        )
+        .arg(
+            Arg::new("all")
+                .long("all")
+                .num_args(0)
+                .action(ArgAction::SetTrue)
+                .help("Remove both stale checkouts and git databases"),
+        )
+        .group(
+            ArgGroup::new("what")
+                .args(["checkouts", "database", "all"])
+                .multiple(true)
+                .required(true),
+        )
+}
+
+/// Execute the `clean` subcommand.
+pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
+    let all = matches.get_flag("all");
+    let io = SessionIo::new(sess);
+
+    // Determine the checkouts and databases of the locked git dependencies.
+    let mut live_checkouts = IndexSet::new();
+    let mut live_dbs = IndexSet::new();
+    for &id in sess.packages().iter().flatten() {
+        let dep = sess.dependency(id);
+        if let DependencySource::Git(ref url) = dep.source {
+            if let Some(name) = io.get_package_path(id).file_name() {
+                live_checkouts.insert(name.to_string_lossy().into_owned());
+            }
+            live_dbs.insert(git_db_name(&dep.name, url));
+        }
+    }
+
+    // Only the directories managed by bender are pruned. Checkouts in the
+    // workspace's `checkout_dir` are left untouched.
+    let git_dir = sess.config.database.join("git");
+    let mut num_paths = 0;
+    let mut num_bytes = 0;
+    if all || matches.get_flag("checkouts") {
+        prune(
+            &git_dir.join("checkouts"),
+            &live_checkouts,
+            &mut num_paths,
+            &mut num_bytes,
+        )?;
+    }
+    if all || matches.get_flag("database") {
+        prune(
+            &git_dir.join("db"),
+            &live_dbs,
+            &mut num_paths,
+            &mut num_bytes,
+        )?;
+    }
+    stageln!(
+        "Cleaned",
+        "{} path{}, reclaiming {} bytes",
+        num_paths,
+        if num_paths == 1 { "" } else { "s" },
+        num_bytes
+    );
+    Ok(())
+}
+
+/// Remove the entries of a directory whose name is not in the live set.
+fn prune(
+    dir: &Path,
+    live: &IndexSet<String>,
+    num_paths: &mut usize,
+    num_bytes: &mut u64,
+) -> Result<()> {
+    if !dir.exists() {
+        return Ok(());
+    }
+    let entries = std::fs::read_dir(dir)
+        .map_err(|cause| Error::chain(format!("Failed to read directory {:?}.", dir), cause))?;
+    let mut stale: Vec<_> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .map(|name| !live.contains(name.to_string_lossy().as_ref()))
+                .unwrap_or(false)
+        })
+        .collect();
+    stale.sort();
+    for path in stale {
+        let size: u64 = WalkDir::new(&path)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| entry.metadata().ok())
+            .filter(|meta| meta.is_file())
+            .map(|meta| meta.len())
+            .sum();
+        stageln!("Removing", "{:?}", path);
+        if path.is_dir() {
+            std::fs::remove_dir_all(&path)
+        } else {
+            std::fs::remove_file(&path)
+        }
+        .map_err(|cause| Error::chain(format!("Failed to remove {:?}.", path), cause))?;
+        *num_paths += 1;
+        *num_bytes += size;
+    }
+    Ok(())
+}
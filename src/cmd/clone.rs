@@ -3,8 +3,8 @@
 
 //! The `clone` subcommand.
 
-use clap::{Arg, ArgMatches, Command};
-use futures::future::join_all;
+use clap::parser::ValueSource;
+use clap::{Arg, ArgAction, ArgMatches, Command};
 use std::path::Path;
 use std::process::Command as SysCommand;
 use tokio::runtime::Runtime;
@@ -12,7 +12,7 @@ use tokio::runtime::Runtime;
 use crate::config;
 use crate::config::{Locked, LockedSource};
 use crate::error::*;
-use crate::sess::{Session, SessionIo};
+use crate::sess::{DependencySource, Session, SessionIo};
 
 /// Assemble the `clone` subcommand.
 pub fn new() -> Command {
@@ -20,10 +20,18 @@ pub fn new() -> Command {
         .about("Clone dependency to a working directory")
         .arg(
             Arg::new("name")
-                .required(true)
+                .required_unless_present("all")
                 .num_args(1)
                 .help("Package name to clone to a working directory"),
         )
+        .arg(
+            Arg::new("all")
+                .long("all")
+                .num_args(0)
+                .action(ArgAction::SetTrue)
+                .conflicts_with("name")
+                .help("Clone all git dependencies (default directory: the workspace's checkout_dir, if set)"),
+        )
         .arg(
             Arg::new("path")
                 .short('p')
@@ -36,11 +44,69 @@ pub fn new() -> Command {
 
 /// Execute the `clone` subcommand.
 pub fn run(sess: &Session, path: &Path, matches: &ArgMatches) -> Result<()> {
-    let dep = &matches.get_one::<String>("name").unwrap().to_lowercase();
-    sess.dependency_with_name(dep)?;
-
+    let rt = Runtime::new()?;
+    let io = SessionIo::new(sess);
     let path_mod = matches.get_one::<String>("path").unwrap(); // TODO make this option for config in the Bender.yml file?
 
+    if !matches.get_flag("all") {
+        let dep = &matches.get_one::<String>("name").unwrap().to_lowercase();
+        sess.dependency_with_name(dep)?;
+        return clone_dependency(sess, &rt, &io, path, path_mod, dep);
+    }
+
+    // Clone into the checkout directory of the workspace, unless a path is
+    // given explicitly.
+    let path_mod = match (
+        matches.value_source("path"),
+        &sess.manifest.workspace.checkout_dir,
+    ) {
+        (Some(ValueSource::DefaultValue), Some(checkout_dir)) => checkout_dir
+            .strip_prefix(path)
+            .unwrap_or(checkout_dir)
+            .to_string_lossy()
+            .into_owned(),
+        _ => path_mod.clone(),
+    };
+
+    let mut num_cloned = 0;
+    let mut num_skipped = 0;
+    for &id in sess.packages().iter().flatten() {
+        let dep = sess.dependency(id);
+        if let Some(config::Dependency::Path(_)) = sess.config.overrides.get(&dep.name) {
+            noteln!(
+                "Skipping `{}`, which already has a path override.",
+                dep.name
+            );
+            num_skipped += 1;
+            continue;
+        }
+        if !matches!(dep.source, DependencySource::Git(..)) {
+            debugln!("clone: skipping non-git dependency {}", dep.name);
+            continue;
+        }
+        clone_dependency(sess, &rt, &io, path, &path_mod, &dep.name)?;
+        num_cloned += 1;
+    }
+    stageln!(
+        "Cloned",
+        "{} dependenc{} into {}, skipped {}",
+        num_cloned,
+        if num_cloned == 1 { "y" } else { "ies" },
+        path_mod,
+        num_skipped
+    );
+    Ok(())
+}
+
+/// Clone a single dependency and turn it into a path dependency.
+fn clone_dependency(
+    sess: &Session,
+    rt: &Runtime,
+    io: &SessionIo,
+    path: &Path,
+    path_mod: &str,
+    dep: &str,
+) -> Result<()> {
     // Check current config for matches
     if sess.config.overrides.contains_key(dep) {
         match &sess.config.overrides[dep] {
@@ -74,36 +140,20 @@ pub fn run(sess: &Session, path: &Path, matches: &ArgMatches) -> Result<()> {
         println!("{} already has a directory in {}.", dep, path_mod);
         println!("Please manually ensure the correct checkout.");
     } else {
-        let rt = Runtime::new()?;
-        let io = SessionIo::new(sess);
-
-        let ids = matches
-            .get_many::<String>("name")
-            .unwrap()
-            .map(|n| Ok((n, sess.dependency_with_name(n)?)))
-            .collect::<Result<Vec<_>>>()?;
-        debugln!("main: obtain checkouts {:?}", ids);
-        let checkouts = rt
-            .block_on(join_all(
-                ids.iter()
-                    .map(|&(_, id)| io.checkout(id))
-                    .collect::<Vec<_>>(),
-            ))
-            .into_iter()
-            .collect::<Result<Vec<_>>>()?;
-        debugln!("main: checkouts {:#?}", checkouts);
-        for c in checkouts {
-            if let Some(s) = c.to_str() {
-                let command = SysCommand::new("cp")
-                    .arg("-rf")
-                    .arg(s)
-                    .arg(path.join(path_mod).join(dep).to_str().unwrap())
-                    .status();
-                if !command.unwrap().success() {
-                    Err(Error::new(format!("Copying {} failed", dep,)))?;
-                }
-                // println!("{:?}", command);
+        let id = sess.dependency_with_name(dep)?;
+        debugln!("main: obtain checkout {:?}", id);
+        let checkout = rt.block_on(io.checkout(id))?;
+        debugln!("main: checkout {:#?}", checkout);
+        if let Some(s) = checkout.to_str() {
+            let command = SysCommand::new("cp")
+                .arg("-rf")
+                .arg(s)
+                .arg(path.join(path_mod).join(dep).to_str().unwrap())
+                .status();
+            if !command.unwrap().success() {
+                Err(Error::new(format!("Copying {} failed", dep,)))?;
             }
+            // println!("{:?}", command);
         }
 
         // rename and update git remotes for easier handling
@@ -138,7 +188,13 @@ pub fn run(sess: &Session, path: &Path, matches: &ArgMatches) -> Result<()> {
         }
 
         if !sess.local_only {
+            let url = sess
+                .dependency(sess.dependency_with_name(dep)?)
+                .source
+                .to_str();
+            let config = sess.config.git_config_for(&url);
             if !SysCommand::new(&sess.config.git)
+                .args(config.iter().flat_map(|c| ["-c", c.as_str()]))
                 .arg("fetch")
                 .arg("--all")
                 .current_dir(path.join(path_mod).join(dep))
@@ -176,7 +232,7 @@ pub fn run(sess: &Session, path: &Path, matches: &ArgMatches) -> Result<()> {
         let mut new_str = String::new();
         if local_file_str.contains("overrides:") {
             let split = local_file_str.split('\n');
-            let test = split.clone().last().unwrap().is_empty();
+            let test = split.clone().next_back().unwrap().is_empty();
             for i in split {
                 if i.contains(dep) {
                     new_str.push('#');
@@ -213,10 +269,15 @@ pub fn run(sess: &Session, path: &Path, matches: &ArgMatches) -> Result<()> {
 
     // Update Bender.lock to enforce usage
     use std::fs::File;
-    let file = File::open(path.join("Bender.lock"))
-        .map_err(|cause| Error::chain(format!("Cannot open lockfile {:?}.", path), cause))?;
-    let mut locked: Locked = serde_yaml::from_reader(&file)
-        .map_err(|cause| Error::chain(format!("Syntax error in lockfile {:?}.", path), cause))?;
+    let file = File::open(sess.lock_path).map_err(|cause| {
+        Error::chain(format!("Cannot open lockfile {:?}.", sess.lock_path), cause)
+    })?;
+    let mut locked: Locked = serde_yaml::from_reader(&file).map_err(|cause| {
+        Error::chain(
+            format!("Syntax error in lockfile {:?}.", sess.lock_path),
+            cause,
+        )
+    })?;
 
     let mut mod_package = locked.packages[dep].clone();
     mod_package.revision = None;
@@ -230,10 +291,18 @@ pub fn run(sess: &Session, path: &Path, matches: &ArgMatches) -> Result<()> {
     );
     locked.packages.insert(dep.to_string(), mod_package);
 
-    let file = File::create(path.join("Bender.lock"))
-        .map_err(|cause| Error::chain(format!("Cannot create lockfile {:?}.", path), cause))?;
-    serde_yaml::to_writer(&file, &locked)
-        .map_err(|cause| Error::chain(format!("Cannot write lockfile {:?}.", path), cause))?;
+    let file = File::create(sess.lock_path).map_err(|cause| {
+        Error::chain(
+            format!("Cannot create lockfile {:?}.", sess.lock_path),
+            cause,
+        )
+    })?;
+    serde_yaml::to_writer(&file, &locked).map_err(|cause| {
+        Error::chain(
+            format!("Cannot write lockfile {:?}.", sess.lock_path),
+            cause,
+        )
+    })?;
 
     println!("Lockfile updated");
 
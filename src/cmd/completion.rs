@@ -3,10 +3,14 @@
 
 //! The `completion` subcommand.
 
+use std::collections::BTreeSet;
 use std::io;
 
+use crate::cli::read_manifest;
+use crate::config::{Locked, SourceFile, Sources};
 use crate::error::*;
-use clap::{builder::PossibleValue, Arg, ArgMatches, Command};
+use crate::sess::{Session, SessionIo};
+use clap::{builder::PossibleValue, Arg, ArgAction, ArgMatches, Command};
 
 /// Assemble the `completion` subcommand.
 pub fn new() -> Command {
@@ -26,6 +30,29 @@ pub fn new() -> Command {
                     PossibleValue::new("zsh"),
                 ]),
         )
+        .arg(
+            Arg::new("dynamic")
+                .long("dynamic")
+                .num_args(0)
+                .action(ArgAction::SetTrue)
+                .help("Complete package names and targets of the current package (bash, fish, and zsh only)"),
+        )
+}
+
+/// Assemble the hidden `__complete` subcommand called by dynamic completions.
+pub fn new_complete() -> Command {
+    Command::new("__complete")
+        .hide(true)
+        .about("Print the candidate values for dynamic shell completion")
+        .arg(
+            Arg::new("context")
+                .required(true)
+                .num_args(1)
+                .value_parser([
+                    PossibleValue::new("packages"),
+                    PossibleValue::new("targets"),
+                ]),
+        )
 }
 
 /// Execute the `completion` subcommand.
@@ -39,6 +66,122 @@ pub fn run(matches: &ArgMatches, app: &mut Command) -> Result<()> {
         "zsh" => clap_complete::Shell::Zsh,
         _ => unreachable!(),
     };
+    let dynamic = match (matches.get_flag("dynamic"), shell) {
+        (false, _) => None,
+        (true, clap_complete::Shell::Bash) => Some(DYNAMIC_BASH),
+        (true, clap_complete::Shell::Fish) => Some(DYNAMIC_FISH),
+        (true, clap_complete::Shell::Zsh) => Some(DYNAMIC_ZSH),
+        (true, _) => {
+            return Err(Error::new(
+                "Dynamic completion is only supported for bash, fish, and zsh.",
+            ))
+        }
+    };
     clap_complete::generate(shell, app, "bender", &mut io::stdout());
+    if let Some(dynamic) = dynamic {
+        print!("{}", dynamic);
+    }
+    Ok(())
+}
+
+/// Execute the hidden `__complete` subcommand.
+///
+/// Only the lockfile and the manifests already checked out are consulted, such
+/// that completion never resolves or fetches dependencies.
+pub fn run_complete(sess: &Session, locked: Option<&Locked>, matches: &ArgMatches) -> Result<()> {
+    let mut candidates = BTreeSet::new();
+    let mut manifests = vec![];
+    if let Some(locked) = locked {
+        sess.load_locked(locked)?;
+        let io = SessionIo::new(sess);
+        for &id in sess.packages().iter().flatten() {
+            let manifest_path = io.get_package_path(id).join("Bender.yml");
+            if manifest_path.exists() {
+                manifests.extend(read_manifest(&manifest_path).ok());
+            }
+        }
+    }
+    match matches.get_one::<String>("context").unwrap().as_str() {
+        "packages" => {
+            candidates.insert(sess.manifest.package.name.clone());
+            candidates.extend(locked.iter().flat_map(|l| l.packages.keys().cloned()));
+        }
+        "targets" => {
+            for sources in std::iter::once(sess.manifest)
+                .chain(&manifests)
+                .filter_map(|m| m.sources.as_ref())
+            {
+                collect_targets(sources, &mut candidates);
+            }
+        }
+        _ => unreachable!(),
+    }
+    for candidate in candidates {
+        println!("{}", candidate);
+    }
     Ok(())
 }
+
+/// Collect the names of the targets referenced in a source group.
+fn collect_targets(sources: &Sources, targets: &mut BTreeSet<String>) {
+    targets.extend(sources.target.names().into_iter().map(String::from));
+    for spec in sources.target_defines.keys() {
+        targets.extend(spec.names().into_iter().map(String::from));
+    }
+    for file in &sources.files {
+        if let SourceFile::Group(group) = file {
+            collect_targets(group, targets);
+        }
+    }
+}
+
+/// Complete `--package`, `--exclude`, and `--target` dynamically in bash.
+const DYNAMIC_BASH: &str = r#"
+_bender_dynamic() {
+    local cur="${COMP_WORDS[COMP_CWORD]}"
+    local prev="${COMP_WORDS[COMP_CWORD-1]}"
+    local cmd
+    for cmd in "${COMP_WORDS[@]:1:COMP_CWORD-1}"; do
+        case "${cmd}" in
+            script|sources|update) break ;;
+        esac
+    done
+    case "${cmd}:${prev}" in
+        script:-p|script:--package|script:-e|script:--exclude|sources:-p|sources:--package|sources:-e|sources:--exclude)
+            COMPREPLY=($(compgen -W "$(bender __complete packages 2>/dev/null)" -- "${cur}"))
+            return 0
+            ;;
+        script:-t|script:--target|sources:-t|sources:--target|update:-t|update:--target)
+            COMPREPLY=($(compgen -W "$(bender __complete targets 2>/dev/null)" -- "${cur}"))
+            return 0
+            ;;
+    esac
+    _bender "$@"
+}
+
+complete -F _bender_dynamic -o bashdefault -o default bender
+"#;
+
+/// Complete `--package`, `--exclude`, and `--target` dynamically in fish.
+const DYNAMIC_FISH: &str = r#"
+complete -c bender -n "__fish_bender_using_subcommand script sources" -s p -l package -x -a "(bender __complete packages 2>/dev/null)"
+complete -c bender -n "__fish_bender_using_subcommand script sources" -s e -l exclude -x -a "(bender __complete packages 2>/dev/null)"
+complete -c bender -n "__fish_bender_using_subcommand script sources update" -s t -l target -x -a "(bender __complete targets 2>/dev/null)"
+"#;
+
+/// Complete `--package`, `--exclude`, and `--target` dynamically in zsh.
+const DYNAMIC_ZSH: &str = r#"
+_bender_dynamic() {
+    local cmd=${words[(r)(script|sources|update)]}
+    local prev=${words[CURRENT-1]}
+    if [[ $cmd == (script|sources) && $prev == (-p|--package|-e|--exclude) ]]; then
+        compadd -- ${(f)"$(bender __complete packages 2>/dev/null)"}
+    elif [[ -n $cmd && $prev == (-t|--target) ]]; then
+        compadd -- ${(f)"$(bender __complete targets 2>/dev/null)"}
+    else
+        _bender "$@"
+    fi
+}
+
+compdef _bender_dynamic bender
+"#;
@@ -5,19 +5,49 @@
 
 use std;
 
-use clap::{ArgMatches, Command};
-use serde_json;
+use clap::{Arg, ArgMatches, Command};
+use serde_json::{self, Value};
 
 use crate::error::*;
 use crate::sess::Session;
 
 /// Assemble the `config` subcommand.
 pub fn new() -> Command {
-    Command::new("config").about("Emit the configuration")
+    Command::new("config").about("Emit the configuration").arg(
+        Arg::new("get")
+            .long("get")
+            .num_args(1)
+            .value_name("KEY")
+            .help(
+            "Only print the value of the given dotted key, e.g. `database` or `overrides.<name>`",
+        ),
+    )
 }
 
 /// Execute the `config` subcommand.
-pub fn run(sess: &Session, _matches: &ArgMatches) -> Result<()> {
+pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
+    if let Some(key) = matches.get_one::<String>("get") {
+        let config = serde_json::to_value(sess.config)
+            .map_err(|cause| Error::chain("Failed to serialize configuration.", cause))?;
+        let value = key
+            .split('.')
+            .try_fold(&config, |value, part| match value {
+                Value::Object(map) => map.get(part),
+                Value::Array(list) => list.get(part.parse::<usize>().ok()?),
+                _ => None,
+            })
+            .ok_or_else(|| Error::new(format!("Unknown configuration key `{}`.", key)))?;
+        // Print scalars without quotes, such that they can be captured by a
+        // shell as they are.
+        match value {
+            Value::Null => println!(),
+            Value::String(s) => println!("{}", s),
+            Value::Bool(_) | Value::Number(_) => println!("{}", value),
+            _ => println!("{:#}", value),
+        }
+        return Ok(());
+    }
+
     let result = {
         let stdout = std::io::stdout();
         let handle = stdout.lock();
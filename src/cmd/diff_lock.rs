@@ -0,0 +1,123 @@
+// Copyright (c) 2024 ETH Zurich
+
+//! The `diff-lock` subcommand.
+
+use std::path::{Path, PathBuf};
+
+use clap::{value_parser, Arg, ArgMatches, Command};
+
+use crate::cli::read_lockfile;
+use crate::config::{Locked, LockedPackage, LockedSource};
+use crate::error::*;
+
+/// Assemble the `diff-lock` subcommand.
+pub fn new() -> Command {
+    Command::new("diff-lock")
+        .about("Compare two lockfiles package by package")
+        .arg(
+            Arg::new("old")
+                .required(true)
+                .num_args(1)
+                .value_parser(value_parser!(PathBuf))
+                .help("The lockfile to compare against"),
+        )
+        .arg(
+            Arg::new("new")
+                .num_args(1)
+                .value_parser(value_parser!(PathBuf))
+                .help("The lockfile to compare, defaults to the package's lockfile"),
+        )
+}
+
+/// Execute the `diff-lock` subcommand.
+pub fn run(root_dir: &Path, lock_path: &Path, matches: &ArgMatches) -> Result<()> {
+    let old_path = matches.get_one::<PathBuf>("old").unwrap();
+    let new_path = matches
+        .get_one::<PathBuf>("new")
+        .cloned()
+        .unwrap_or_else(|| lock_path.to_path_buf());
+    // Interpret relative paths in both lockfiles relative to the package, such
+    // that an unchanged path dependency compares equal.
+    let old = read_lockfile(old_path, root_dir)?;
+    let new = read_lockfile(&new_path, root_dir)?;
+    print_changes(&old, &new);
+    Ok(())
+}
+
+/// Print the packages added, removed, and changed between two lockfiles.
+pub fn print_changes(old: &Locked, new: &Locked) {
+    let mut num_changes = 0;
+    for (name, old_pkg) in &old.packages {
+        match new.packages.get(name) {
+            Some(new_pkg) => {
+                for change in changes(old_pkg, new_pkg) {
+                    println!("~ {} {}", name, change);
+                    num_changes += 1;
+                }
+            }
+            None => {
+                println!("- {}{}", name, describe(old_pkg));
+                num_changes += 1;
+            }
+        }
+    }
+    for (name, new_pkg) in &new.packages {
+        if !old.packages.contains_key(name) {
+            println!("+ {}{}", name, describe(new_pkg));
+            num_changes += 1;
+        }
+    }
+    if num_changes == 0 {
+        println!("No differences.");
+    }
+}
+
+/// Shorten a revision hash for display.
+fn short(revision: &str) -> &str {
+    revision.get(..7).unwrap_or(revision)
+}
+
+/// Describe the version and revision of a locked package.
+fn describe(pkg: &LockedPackage) -> String {
+    let mut desc = String::new();
+    if let Some(ref version) = pkg.version {
+        desc.push_str(&format!(" {}", version));
+    }
+    if let Some(ref revision) = pkg.revision {
+        desc.push_str(&format!(" ({})", short(revision)));
+    }
+    desc
+}
+
+/// Describe a locked source for display.
+fn source(source: &LockedSource) -> String {
+    match source {
+        LockedSource::Path(path) => format!("path {:?}", path),
+        LockedSource::Git(url) => format!("git {}", url),
+        LockedSource::Registry(url) => format!("registry {}", url),
+    }
+}
+
+/// List the changes between two locked versions of a package.
+fn changes(old: &LockedPackage, new: &LockedPackage) -> Vec<String> {
+    let mut changes = vec![];
+    let (old_source, new_source) = (source(&old.source), source(&new.source));
+    if old_source != new_source {
+        changes.push(format!("source {} -> {}", old_source, new_source));
+    }
+    if old.version != new.version {
+        changes.push(format!(
+            "version {} -> {}",
+            old.version.as_deref().unwrap_or("none"),
+            new.version.as_deref().unwrap_or("none")
+        ));
+    }
+    if old.revision != new.revision {
+        changes.push(format!(
+            "revision {} -> {}",
+            old.revision.as_deref().map(short).unwrap_or("none"),
+            new.revision.as_deref().map(short).unwrap_or("none")
+        ));
+    }
+    changes
+}
@@ -0,0 +1,308 @@
+// Copyright (c) 2024 ETH Zurich
+
+//! The `doctor` subcommand.
+
+use std::path::Path;
+
+use clap::{ArgMatches, Command};
+use indexmap::{IndexMap, IndexSet};
+use tokio::runtime::Runtime;
+
+use crate::cli::read_manifest;
+use crate::config::{self, Locked, LockedSource};
+use crate::error::*;
+use crate::git::Git;
+use crate::sess::{DependencySource, Session, SessionIo};
+
+/// Assemble the `doctor` subcommand.
+pub fn new() -> Command {
+    Command::new("doctor").about("Diagnose common problems with the manifest and lockfile")
+}
+
+/// An issue found by one of the checks.
+struct Issue {
+    severity: Severity,
+    message: String,
+    hint: String,
+}
+
+/// The issues found, grouped by category.
+#[derive(Default)]
+struct Report(IndexMap<&'static str, Vec<Issue>>);
+
+impl Report {
+    fn add(&mut self, category: &'static str, severity: Severity, message: String, hint: &str) {
+        self.0.entry(category).or_default().push(Issue {
+            severity,
+            message,
+            hint: hint.to_string(),
+        });
+    }
+
+    fn count(&self, severity: Severity) -> usize {
+        self.0
+            .values()
+            .flatten()
+            .filter(|issue| issue.severity == severity)
+            .count()
+    }
+}
+
+/// Execute the `doctor` subcommand.
+///
+/// This runs before the dependencies are resolved, such that a broken
+/// lockfile can be diagnosed rather than aborting the command.
+pub fn run(sess: &Session, locked: Option<&Locked>, _matches: &ArgMatches) -> Result<()> {
+    let mut report = Report::default();
+
+    check_path_dependencies(sess, locked, &mut report);
+    match locked {
+        Some(locked) => {
+            check_lockfile(sess, locked, &mut report);
+            if report.count(Severity::Error) == 0 {
+                // Reuse the session's graph construction, which detects cycles.
+                match sess.load_locked(locked) {
+                    Ok(()) => {
+                        check_package_names(sess, &mut report);
+                        check_checkouts(sess, locked, &mut report)?;
+                    }
+                    Err(e) => report.add(
+                        "Dependency graph",
+                        Severity::Error,
+                        e.to_string(),
+                        "Remove the dependency loop from the manifests and run `bender update`.",
+                    ),
+                }
+            }
+        }
+        None if sess.manifest.frozen => report.add(
+            "Lockfile",
+            Severity::Error,
+            "The package is frozen, but there is no `Bender.lock`.".to_string(),
+            "Temporarily remove `frozen: true` from `Bender.yml` and run `bender update`.",
+        ),
+        None => report.add(
+            "Lockfile",
+            Severity::Warning,
+            "There is no `Bender.lock`; dependencies are resolved on the next command.".to_string(),
+            "Run `bender update` and commit the lockfile for reproducible builds.",
+        ),
+    }
+
+    // Print the report.
+    for (category, issues) in &report.0 {
+        println!("{}:", category);
+        for issue in issues {
+            println!("  {} {}", issue.severity, issue.message);
+            println!("    hint: {}", issue.hint);
+        }
+    }
+    let num_errors = report.count(Severity::Error);
+    let num_warnings = report.count(Severity::Warning);
+    if num_errors + num_warnings == 0 {
+        println!("No issues found.");
+    } else {
+        println!("{} error(s), {} warning(s).", num_errors, num_warnings);
+    }
+    if num_errors > 0 {
+        return Err(Error::new(format!(
+            "Found {} error-level issue(s).",
+            num_errors
+        )));
+    }
+    Ok(())
+}
+
+/// Check that path dependencies point to packages.
+fn check_path_dependencies(sess: &Session, locked: Option<&Locked>, report: &mut Report) {
+    let mut paths: IndexMap<&str, &Path> = sess
+        .manifest
+        .dependencies
+        .iter()
+        .filter_map(|(name, dep)| match dep {
+            config::Dependency::Path(path) => Some((name.as_str(), path.as_path())),
+            _ => None,
+        })
+        .collect();
+    for (name, pkg) in locked.iter().flat_map(|locked| &locked.packages) {
+        if let LockedSource::Path(ref path) = pkg.source {
+            paths.entry(name.as_str()).or_insert(path.as_path());
+        }
+    }
+    for (name, path) in paths {
+        let path = sess.root.join(path);
+        if !path.exists() {
+            report.add(
+                "Path dependencies",
+                Severity::Error,
+                format!(
+                    "Path dependency `{}` points to {:?}, which does not exist.",
+                    name, path
+                ),
+                "Fix the `path` of the dependency, or check out the package there.",
+            );
+        } else if !path.join("Bender.yml").exists() {
+            report.add(
+                "Path dependencies",
+                Severity::Warning,
+                format!("Path dependency `{}` at {:?} has no `Bender.yml`.", name, path),
+                "Add a manifest to the package, otherwise its sources and dependencies are ignored.",
+            );
+        } else if let Err(e) = read_manifest(&path.join("Bender.yml")) {
+            report.add(
+                "Path dependencies",
+                Severity::Error,
+                format!("Manifest of path dependency `{}` is invalid: {}", name, e),
+                "Fix the manifest of the dependency.",
+            );
+        }
+    }
+}
+
+/// Check that the lockfile matches the dependency graph of the manifests.
+fn check_lockfile(sess: &Session, locked: &Locked, report: &mut Report) {
+    for name in sess.manifest.dependencies.keys() {
+        if !locked.packages.contains_key(name) {
+            report.add(
+                "Lockfile",
+                Severity::Error,
+                format!(
+                    "`Bender.yml` contains dependency `{}` but `Bender.lock` does not.",
+                    name
+                ),
+                if sess.manifest.frozen {
+                    "Temporarily remove `frozen: true` from `Bender.yml` and run `bender update`."
+                } else {
+                    "Run `bender update`."
+                },
+            );
+        }
+    }
+    for (name, pkg) in &locked.packages {
+        for dep in &pkg.dependencies {
+            if !locked.packages.contains_key(dep) {
+                report.add(
+                    "Lockfile",
+                    Severity::Error,
+                    format!(
+                        "Locked package `{}` depends on `{}`, which is not locked.",
+                        name, dep
+                    ),
+                    "Run `bender update`.",
+                );
+            }
+        }
+    }
+
+    // Determine the packages reachable from the root package and plugins.
+    let mut reachable = IndexSet::new();
+    let mut pending: Vec<&str> = sess
+        .manifest
+        .dependencies
+        .keys()
+        .chain(sess.config.plugins.keys())
+        .map(String::as_str)
+        .collect();
+    while let Some(name) = pending.pop() {
+        if !reachable.insert(name) {
+            continue;
+        }
+        if let Some(pkg) = locked.packages.get(name) {
+            pending.extend(pkg.dependencies.iter().map(String::as_str));
+        }
+    }
+    for name in locked.packages.keys() {
+        if !reachable.contains(name.as_str()) {
+            report.add(
+                "Lockfile",
+                Severity::Warning,
+                format!(
+                    "`Bender.lock` contains `{}`, which no package depends on.",
+                    name
+                ),
+                "Run `bender update` to remove stale entries.",
+            );
+        }
+    }
+}
+
+/// Check that the dependency names match the package names in the manifests.
+fn check_package_names(sess: &Session, report: &mut Report) {
+    let io = SessionIo::new(sess);
+    for &id in sess.packages().iter().flatten() {
+        let dep = sess.dependency(id);
+        // Only inspect manifests that are available locally.
+        let manifest_path = match dep.source {
+            DependencySource::Path(..) | DependencySource::Git(..) => {
+                io.get_package_path(id).join("Bender.yml")
+            }
+            DependencySource::Registry => continue,
+        };
+        if !manifest_path.exists() {
+            continue;
+        }
+        if let Ok(manifest) = read_manifest(&manifest_path) {
+            if manifest.package.name != dep.name {
+                report.add(
+                    "Package names",
+                    Severity::Warning,
+                    format!(
+                        "Dependency `{}` refers to package `{}`.",
+                        dep.name, manifest.package.name
+                    ),
+                    "Rename the dependency to match the package name, otherwise its `export_include_dirs` are not propagated.",
+                );
+            }
+        }
+    }
+}
+
+/// Check that the git checkouts are at their locked revisions.
+///
+/// A checkout can diverge after manual git operations in it, such that the
+/// build silently uses different code than the lockfile says.
+fn check_checkouts(sess: &Session, locked: &Locked, report: &mut Report) -> Result<()> {
+    let rt = Runtime::new()?;
+    let io = SessionIo::new(sess);
+    for &id in sess.packages().iter().flatten() {
+        let dep = sess.dependency(id);
+        if !matches!(dep.source, DependencySource::Git(..)) {
+            continue;
+        }
+        let revision = match locked.packages.get(&dep.name) {
+            Some(config::LockedPackage {
+                revision: Some(revision),
+                ..
+            }) => revision,
+            _ => continue,
+        };
+        let path = io.get_package_path(id);
+        if !path.exists() {
+            continue;
+        }
+        let current = rt
+            .block_on(Git::new(&path, &sess.config.git).current_checkout())
+            .ok()
+            .flatten();
+        if current.as_deref() == Some(revision.as_str()) {
+            continue;
+        }
+        report.add(
+            "Checkouts",
+            Severity::Warning,
+            format!(
+                "Checkout of `{}` at {:?} is at revision {}, but `Bender.lock` has {}.",
+                dep.name,
+                path,
+                current.as_deref().unwrap_or("unknown"),
+                revision
+            ),
+            if sess.manifest.workspace.checkout_dir.is_some() {
+                "Check out the locked revision in the workspace checkout."
+            } else {
+                "Run `bender checkout` to restore the locked revision, which discards changes in the checkout."
+            },
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,115 @@
+// Copyright (c) 2024 ETH Zurich
+
+//! The `export-deps` subcommand.
+
+use std::io::Write;
+use std::path::Path;
+
+use clap::builder::PossibleValue;
+use clap::{Arg, ArgMatches, Command};
+use tabwriter::TabWriter;
+
+use crate::config::{Locked, LockedSource};
+use crate::error::*;
+
+/// Assemble the `export-deps` subcommand.
+pub fn new() -> Command {
+    Command::new("export-deps")
+        .about("Export the resolved dependencies for other tools")
+        .long_about("Export the resolved dependencies for other tools. Emits one row per package with its name, source type, URL or path, revision, and version, independent of the format of `Bender.lock`.")
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .help("Output format")
+                .num_args(1)
+                .default_value("table")
+                .value_parser([
+                    PossibleValue::new("table"),
+                    PossibleValue::new("csv"),
+                    PossibleValue::new("json"),
+                ]),
+        )
+}
+
+/// A resolved dependency as exported.
+#[derive(Serialize)]
+struct ExportedDep<'a> {
+    name: &'a str,
+    source: &'static str,
+    location: String,
+    revision: Option<&'a str>,
+    version: Option<&'a str>,
+}
+
+/// Execute the `export-deps` subcommand.
+pub fn run(root_dir: &Path, locked: &Locked, matches: &ArgMatches) -> Result<()> {
+    let deps: Vec<_> = locked
+        .packages
+        .iter()
+        .map(|(name, pkg)| ExportedDep {
+            name,
+            source: pkg.source.kind(),
+            // Keep paths within the package relative, as in the lockfile.
+            location: match pkg.source {
+                LockedSource::Path(ref path) => path
+                    .strip_prefix(root_dir)
+                    .unwrap_or(path)
+                    .to_string_lossy()
+                    .into_owned(),
+                ref source => source.location(),
+            },
+            revision: pkg.revision.as_deref(),
+            version: pkg.version.as_deref(),
+        })
+        .collect();
+
+    match matches.get_one::<String>("format").unwrap().as_str() {
+        "json" => {
+            let stdout = std::io::stdout();
+            serde_json::to_writer_pretty(stdout.lock(), &deps)
+                .map_err(|cause| Error::chain("Failed to serialize dependencies.", cause))?;
+            println!();
+        }
+        "csv" => {
+            println!("name,source,location,revision,version");
+            for dep in &deps {
+                let fields = [
+                    dep.name,
+                    dep.source,
+                    &dep.location,
+                    dep.revision.unwrap_or(""),
+                    dep.version.unwrap_or(""),
+                ];
+                let fields: Vec<_> = fields.iter().map(|f| csv_field(f)).collect();
+                println!("{}", fields.join(","));
+            }
+        }
+        _ => {
+            let mut tw = TabWriter::new(vec![]);
+            for dep in &deps {
+                writeln!(
+                    &mut tw,
+                    "{}\t{}\t{}\t{}\t{}",
+                    dep.name,
+                    dep.source,
+                    dep.location,
+                    dep.revision.unwrap_or("-"),
+                    dep.version.unwrap_or("-")
+                )
+                .unwrap();
+            }
+            tw.flush().unwrap();
+            print!("{}", String::from_utf8(tw.into_inner().unwrap()).unwrap());
+        }
+    }
+    Ok(())
+}
+
+/// Quote a CSV field if it contains a separator, quote, or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,68 @@
+// Copyright (c) 2024 ETH Zurich
+
+//! The `file-info` subcommand.
+
+use std;
+use std::path::{Path, PathBuf};
+
+use clap::{Arg, ArgMatches, Command};
+use indexmap::IndexMap;
+use serde_json;
+use tokio::runtime::Runtime;
+
+use crate::error::*;
+use crate::sess::{Session, SessionIo};
+use crate::target::TargetSpec;
+
+/// Assemble the `file-info` subcommand.
+pub fn new() -> Command {
+    Command::new("file-info")
+        .about("Emit the include directories and defines in effect for a source file")
+        .arg(
+            Arg::new("path")
+                .required(true)
+                .num_args(1)
+                .help("Path to the source file"),
+        )
+}
+
+/// The effective configuration of a single source file.
+#[derive(Serialize)]
+struct FileInfo<'ctx> {
+    package: Option<&'ctx str>,
+    target: TargetSpec,
+    include_dirs: Vec<&'ctx Path>,
+    defines: IndexMap<&'ctx str, Option<&'ctx str>>,
+}
+
+/// Execute the `file-info` subcommand.
+pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
+    let rt = Runtime::new()?;
+    let io = SessionIo::new(sess);
+    let srcs = rt.block_on(io.sources())?;
+
+    let path = PathBuf::from(matches.get_one::<String>("path").unwrap());
+    let group = match srcs.find_file(&path) {
+        Some(group) => group,
+        None => {
+            return Err(Error::new(format!(
+                "File {:?} is not part of the sources of this package or its dependencies.",
+                path
+            )))
+        }
+    };
+
+    let info = FileInfo {
+        package: group.package,
+        target: group.target.clone(),
+        defines: group.defines.clone(),
+        include_dirs: group.get_incdirs(),
+    };
+    let result = {
+        let stdout = std::io::stdout();
+        let handle = stdout.lock();
+        serde_json::to_writer_pretty(handle, &info)
+    };
+    println!();
+    result.map_err(|cause| Error::chain("Failed to serialize file information.", cause))
+}
@@ -0,0 +1,92 @@
+// Copyright (c) 2024 ETH Zurich
+
+//! The `fmt` subcommand.
+
+use std::fs;
+use std::path::Path;
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use serde_yaml::{Mapping, Value};
+
+use crate::config::{PartialManifest, Validate};
+use crate::error::*;
+
+/// The canonical order of the top-level manifest keys.
+///
+/// Keys not listed here follow in their original order.
+const KEY_ORDER: &[&str] = &[
+    "package",
+    "dependencies",
+    "sources",
+    "export_include_dirs",
+    "plugins",
+    "workspace",
+    "vendor_package",
+];
+
+/// Assemble the `fmt` subcommand.
+pub fn new() -> Command {
+    Command::new("fmt")
+        .about("Format the package's `Bender.yml` in a canonical key order and style")
+        .arg(
+            Arg::new("check")
+                .long("check")
+                .num_args(0)
+                .action(ArgAction::SetTrue)
+                .help("Only check whether the manifest is formatted, without modifying it"),
+        )
+}
+
+/// Execute the `fmt` subcommand.
+pub fn run(path: &Path, matches: &ArgMatches) -> Result<()> {
+    let original = fs::read_to_string(path)
+        .map_err(|cause| Error::chain(format!("Cannot read manifest {:?}.", path), cause))?;
+
+    // Refuse to format a manifest that would not load.
+    let partial: PartialManifest = serde_yaml::from_str(&original)
+        .map_err(|cause| Error::chain(format!("Syntax error in manifest {:?}.", path), cause))?;
+    partial
+        .validate()
+        .map_err(|cause| Error::chain(format!("Error in manifest {:?}.", path), cause))?;
+
+    // Reorder the document itself rather than the parsed manifest, such that
+    // the dependency shorthands and unknown keys are kept as they are written.
+    let value: Value = serde_yaml::from_str(&original)
+        .map_err(|cause| Error::chain(format!("Syntax error in manifest {:?}.", path), cause))?;
+    let formatted = serde_yaml::to_string(&canonicalize(value))
+        .map_err(|cause| Error::chain("Failed to serialize manifest.", cause))?;
+
+    if formatted == original {
+        return Ok(());
+    }
+    if matches.get_flag("check") {
+        return Err(Error::new(format!(
+            "Manifest {:?} is not formatted. Run `bender fmt` to format it.",
+            path
+        )));
+    }
+    fs::write(path, formatted)
+        .map_err(|cause| Error::chain(format!("Cannot write manifest {:?}.", path), cause))?;
+    stageln!("Formatted", "{}", path.display());
+    Ok(())
+}
+
+/// Bring the top-level keys into canonical order and sort the dependencies.
+fn canonicalize(value: Value) -> Value {
+    let Value::Mapping(mut map) = value else {
+        return value;
+    };
+    let mut result = Mapping::new();
+    for key in KEY_ORDER {
+        if let Some(value) = map.remove(*key) {
+            result.insert(Value::from(*key), value);
+        }
+    }
+    result.extend(map);
+    if let Some(Value::Mapping(deps)) = result.get_mut("dependencies") {
+        let mut sorted: Vec<_> = std::mem::take(deps).into_iter().collect();
+        sorted.sort_by_key(|(name, _)| name.as_str().map(str::to_lowercase));
+        deps.extend(sorted);
+    }
+    Value::Mapping(result)
+}
@@ -90,7 +90,7 @@ pub fn run_single(sess: &Session, matches: &ArgMatches) -> Result<()> {
                 sess.manifest.dependencies.keys().cloned().collect(),
                 IndexMap::new(),
                 version_string.clone(),
-            )
+            )?
             .flatten()),
         None => Err(Error::new("Error in loading sources")),
     }?;
@@ -353,6 +353,9 @@ pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
                 include_dirs: Default::default(),
                 export_incdirs: Default::default(),
                 defines: Default::default(),
+                target_defines: Default::default(),
+                headers: Default::default(),
+                library: None,
                 files: Default::default(),
                 dependencies: Default::default(),
                 version: None,
@@ -555,6 +558,9 @@ fn get_fuse_depend_string(
             include_dirs: Default::default(),
             export_incdirs: Default::default(),
             defines: Default::default(),
+            target_defines: Default::default(),
+            headers: Default::default(),
+            library: None,
             files: Default::default(),
             dependencies: Default::default(),
             version: None,
@@ -571,6 +577,9 @@ fn get_fuse_depend_string(
                 include_dirs: group.include_dirs.clone(),
                 export_incdirs: group.export_incdirs.clone(),
                 defines: group.defines.clone(),
+                target_defines: group.target_defines.clone(),
+                headers: group.headers.clone(),
+                library: group.library,
                 files: group.files.clone(),
                 dependencies: group.dependencies.clone(),
                 version: version_string.clone(),
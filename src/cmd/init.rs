@@ -3,32 +3,90 @@
 
 //! The `init` subcommand.
 
+use std::collections::BTreeSet;
 use std::env::current_dir;
 use std::ffi::OsStr;
 use std::fs::File;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command as SysCommand;
 
-use clap::{ArgMatches, Command};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use indexmap::IndexMap;
+use walkdir::WalkDir;
 
+use crate::config::{PartialManifest, Validate};
 use crate::error::*;
 
 /// Assemble the `init` subcommand.
 pub fn new() -> Command {
-    Command::new("init").about("Initialize a Bender package")
+    Command::new("init")
+        .about("Initialize a Bender package")
+        .arg(
+            Arg::new("name")
+                .long("name")
+                .num_args(1)
+                .help("Name of the package, defaults to the name of the current directory"),
+        )
+        .arg(
+            Arg::new("authors")
+                .long("authors")
+                .num_args(1..)
+                .action(ArgAction::Append)
+                .help("Authors of the package, defaults to the user in the git config"),
+        )
+        .arg(
+            Arg::new("git")
+                .long("git")
+                .num_args(1)
+                .value_name("URL")
+                .help("Declare a dependency on the git repository at the given URL"),
+        )
+        .arg(
+            Arg::new("rev")
+                .long("rev")
+                .num_args(1)
+                .requires("git")
+                .conflicts_with("version")
+                .help("Revision of the `--git` dependency"),
+        )
+        .arg(
+            Arg::new("version")
+                .long("version")
+                .num_args(1)
+                .requires("git")
+                .help("Version requirement of the `--git` dependency"),
+        )
+        .arg(
+            Arg::new("from-sources")
+                .long("from-sources")
+                .num_args(1)
+                .value_name("DIR")
+                .value_parser(value_parser!(PathBuf))
+                .help("Generate the `sources` from the HDL files found in the given directory"),
+        )
+        .arg(
+            Arg::new("force")
+                .long("force")
+                .num_args(0)
+                .action(ArgAction::SetTrue)
+                .help("Overwrite an existing Bender.yml"),
+        )
 }
 
 /// Execute the `init` subcommand.
-pub fn run(_matches: &ArgMatches) -> Result<()> {
+pub fn run(matches: &ArgMatches) -> Result<()> {
     // Get working directory name
     let binding = current_dir()?;
-    let cwd = binding
-        .as_path()
-        .file_name()
-        .unwrap_or(OsStr::new("new_package"))
-        .to_str()
-        .unwrap_or("new_package");
+    let cwd = match matches.get_one::<String>("name") {
+        Some(name) => name.as_str(),
+        None => binding
+            .as_path()
+            .file_name()
+            .unwrap_or(OsStr::new("new_package"))
+            .to_str()
+            .unwrap_or("new_package"),
+    };
 
     // Get author from git config
     let name = String::from_utf8(
@@ -52,33 +110,186 @@ pub fn run(_matches: &ArgMatches) -> Result<()> {
         .strip_suffix("\r\n")
         .unwrap_or(email.strip_suffix('\n').unwrap_or(&email));
 
+    let authors = match matches.get_many::<String>("authors") {
+        Some(authors) => authors.cloned().collect(),
+        None => vec![format!("{} <{}>", name, email)],
+    };
+
+    // Declare the dependency on the given git repository.
+    let dependency = match matches.get_one::<String>("git") {
+        Some(url) => {
+            let spec = match (
+                matches.get_one::<String>("rev"),
+                matches.get_one::<String>("version"),
+            ) {
+                (Some(rev), _) => format!("rev: {}", quote(rev)),
+                (None, Some(version)) => format!("version: {}", quote(version)),
+                (None, None) => {
+                    return Err(Error::new(
+                        "A `--git` dependency requires either `--rev` or `--version`.",
+                    ))
+                }
+            };
+            format!(
+                "\n  {}: {{ git: {}, {} }}",
+                dependency_name(url),
+                quote(url),
+                spec
+            )
+        }
+        None => String::new(),
+    };
+
     // Create Bender.yml
-    if Path::new("Bender.yml").exists() {
-        return Err(Error::new("Bender.yml already exists"));
+    if Path::new("Bender.yml").exists() && !matches.get_flag("force") {
+        return Err(Error::new(
+            "Bender.yml already exists, use `--force` to overwrite it",
+        ));
     }
 
-    let mut file = File::create("Bender.yml")?;
+    // List the existing source files, or leave a placeholder for them.
+    let sources = match matches.get_one::<PathBuf>("from-sources") {
+        Some(dir) => scan_sources(dir)?,
+        None => "
+  # Source files grouped in levels. Files in level 0 have no dependencies on files in this
+  # package. Files in level 1 only depend on files in level 0, files in level 2 on files in
+  # levels 1 and 0, etc. Files within a level are ordered alphabetically.
+  # Level 0"
+            .to_string(),
+    };
 
-    writeln!(
-        file,
+    let manifest = format!(
         "\
 # A more detailed description of the manifest format `Bender.yml` can be found here:
 # https://github.com/pulp-platform/bender#manifest-format-benderyml
 
 package:
   name: {}
-  authors:
-    - \"{} <{}>\"
+  authors:{}
 
-dependencies:
+dependencies:{}
 
-sources:
-  # Source files grouped in levels. Files in level 0 have no dependencies on files in this
-  # package. Files in level 1 only depend on files in level 0, files in level 2 on files in
-  # levels 1 and 0, etc. Files within a level are ordered alphabetically.
-  # Level 0",
-        cwd, name, email
-    )?;
+sources:{}",
+        quote(cwd),
+        authors
+            .iter()
+            .map(|author| format!("\n    - {}", quote(author)))
+            .collect::<String>(),
+        dependency,
+        sources
+    );
 
+    // Make sure the scaffolded manifest is valid before writing it.
+    let partial: PartialManifest = serde_yaml::from_str(&manifest)
+        .map_err(|cause| Error::chain("Failed to generate a valid manifest.", cause))?;
+    partial
+        .validate()
+        .map_err(|cause| Error::chain("Failed to generate a valid manifest.", cause))?;
+
+    let mut file = File::create("Bender.yml")?;
+    writeln!(file, "{}", manifest)?;
+
+    if let Some(dir) = matches.get_one::<PathBuf>("from-sources") {
+        warnln!(
+            "The `sources` were generated from the files in {:?}. Review them, in particular \
+            the order of the files, which must respect their dependencies.",
+            dir
+        );
+    }
     Ok(())
 }
+
+/// The extensions of the files listed by `--from-sources`.
+const SOURCE_EXTENSIONS: &[&str] = &["sv", "v", "vp", "vhd", "vhdl"];
+
+/// The extensions of the headers whose directories become include directories.
+const HEADER_EXTENSIONS: &[&str] = &["svh", "vh"];
+
+/// Render the `sources` of a manifest listing the HDL files below `dir`, with
+/// one group per directory. Hidden directories are skipped.
+///
+/// Subdirectories often contain the building blocks of their parent, so their
+/// groups are listed first.
+fn scan_sources(dir: &Path) -> Result<String> {
+    let cwd = current_dir()?;
+    let mut groups: IndexMap<PathBuf, Vec<PathBuf>> = IndexMap::new();
+    let mut include_dirs = BTreeSet::new();
+    for entry in WalkDir::new(dir).sort_by_file_name().contents_first(true) {
+        let entry = entry
+            .map_err(|cause| Error::chain(format!("Failed to scan directory {:?}.", dir), cause))?;
+        let path = entry.path();
+        let hidden = path
+            .strip_prefix(dir)
+            .unwrap_or(path)
+            .components()
+            .any(|c| c.as_os_str().to_string_lossy().starts_with('.'));
+        if hidden || !entry.file_type().is_file() {
+            continue;
+        }
+        let parent = path.parent().unwrap_or(dir).to_path_buf();
+        match path.extension().and_then(OsStr::to_str) {
+            Some(ext) if SOURCE_EXTENSIONS.contains(&ext) => {
+                groups.entry(parent).or_default().push(path.to_path_buf())
+            }
+            Some(ext) if HEADER_EXTENSIONS.contains(&ext) => {
+                include_dirs.insert(parent);
+            }
+            _ => (),
+        }
+    }
+    if groups.is_empty() {
+        return Err(Error::new(format!(
+            "No HDL source files found in {:?}.",
+            dir
+        )));
+    }
+
+    // Put the include directories on a group around all files.
+    let mut sources = String::new();
+    let indent = if include_dirs.is_empty() {
+        "  "
+    } else {
+        sources.push_str("\n  include_dirs:");
+        for dir in &include_dirs {
+            sources.push_str(&format!("\n    - {}", manifest_path(dir, &cwd)));
+        }
+        sources.push_str("\n  files:");
+        "    "
+    };
+    for files in groups.values() {
+        sources.push_str(&format!("\n{}- files:", indent));
+        for file in files {
+            sources.push_str(&format!("\n{}    - {}", indent, manifest_path(file, &cwd)));
+        }
+    }
+    Ok(sources)
+}
+
+/// Quote a path found by `--from-sources` relative to the manifest.
+fn manifest_path(path: &Path, cwd: &Path) -> String {
+    let path = if path.is_absolute() {
+        pathdiff::diff_paths(path, cwd).unwrap_or_else(|| path.to_path_buf())
+    } else {
+        path.strip_prefix(".").unwrap_or(path).to_path_buf()
+    };
+    match path.to_string_lossy() {
+        p if p.is_empty() => quote("."),
+        p => quote(&p),
+    }
+}
+
+/// Quote a string for use as a YAML scalar.
+fn quote(s: &str) -> String {
+    serde_json::to_string(s).unwrap()
+}
+
+/// Derive the name of a dependency from its git URL, e.g. `common_cells` for
+/// `git@github.com:pulp-platform/common_cells.git`.
+fn dependency_name(url: &str) -> String {
+    let name = url
+        .trim_end_matches('/')
+        .rsplit(['/', ':'])
+        .next()
+        .unwrap_or(url);
+    name.strip_suffix(".git").unwrap_or(name).to_lowercase()
+}
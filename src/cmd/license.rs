@@ -0,0 +1,225 @@
+// Copyright (c) 2024 ETH Zurich
+
+//! The `license` subcommand.
+
+use std::io::Write;
+
+use clap::builder::PossibleValue;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use futures::future::join_all;
+use indexmap::IndexMap;
+use tabwriter::TabWriter;
+use tokio::runtime::Runtime;
+
+use crate::error::*;
+use crate::sess::{Session, SessionIo};
+
+/// Assemble the `license` subcommand.
+pub fn new() -> Command {
+    Command::new("license")
+        .about("List the licenses of the package and its dependencies")
+        .arg(
+            Arg::new("check-compatibility")
+                .long("check-compatibility")
+                .num_args(0)
+                .action(ArgAction::SetTrue)
+                .help("Flag licenses that are potentially incompatible with the root package's license"),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .help("Output format, `json` emits an object mapping each license to its packages")
+                .num_args(1)
+                .default_value("text")
+                .value_parser([PossibleValue::new("text"), PossibleValue::new("json")]),
+        )
+}
+
+/// Execute the `license` subcommand.
+pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
+    let rt = Runtime::new()?;
+    let io = SessionIo::new(sess);
+
+    // Gather the licenses of the root package and all dependencies.
+    let ids: Vec<_> = sess.packages().iter().flatten().copied().collect();
+    let manifests = rt
+        .block_on(join_all(
+            ids.iter()
+                .map(|&id| io.dependency_manifest(id))
+                .collect::<Vec<_>>(),
+        ))
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+    let root_license = sess.manifest.package.license.as_deref();
+    let mut licenses = vec![(sess.manifest.package.name.as_str(), root_license)];
+    licenses.extend(ids.iter().zip(manifests).map(|(&id, manifest)| {
+        (
+            sess.dependency_name(id),
+            manifest.and_then(|m| m.package.license.as_deref()),
+        )
+    }));
+
+    // Check the licenses of the dependencies against the root license.
+    let conflicts: IndexMap<&str, &str> = match root_license {
+        Some(root) if matches.get_flag("check-compatibility") => licenses
+            .iter()
+            .skip(1)
+            .filter_map(|&(name, license)| Some((name, conflict(root, license?)?)))
+            .collect(),
+        None if matches.get_flag("check-compatibility") => {
+            return Err(Error::new(format!(
+                "Package `{}` does not declare a `license`, cannot check compatibility.",
+                sess.manifest.package.name
+            )))
+        }
+        _ => IndexMap::new(),
+    };
+
+    if matches.get_one::<String>("format").unwrap() == "json" {
+        let mut map: IndexMap<&str, Vec<&str>> = IndexMap::new();
+        for &(name, license) in &licenses {
+            map.entry(license.unwrap_or("unknown"))
+                .or_default()
+                .push(name);
+        }
+        let stdout = std::io::stdout();
+        serde_json::to_writer_pretty(stdout.lock(), &map)
+            .map_err(|cause| Error::chain("Failed to serialize licenses.", cause))?;
+        println!();
+    } else {
+        let mut tw = TabWriter::new(vec![]);
+        for &(name, license) in &licenses {
+            write!(&mut tw, "{}\t{}", name, license.unwrap_or("unknown")).unwrap();
+            if let Some(reason) = conflicts.get(name) {
+                write!(&mut tw, "\tincompatible: {}", reason).unwrap();
+            }
+            writeln!(&mut tw).unwrap();
+        }
+        tw.flush().unwrap();
+        print!("{}", String::from_utf8(tw.into_inner().unwrap()).unwrap());
+    }
+
+    for &(name, license) in &licenses {
+        if license.is_none() {
+            warnln!("Package `{}` does not declare a `license`.", name);
+        }
+    }
+    if !conflicts.is_empty() {
+        return Err(Error::new(format!(
+            "Found {} license(s) potentially incompatible with `{}` of package `{}`.",
+            conflicts.len(),
+            root_license.unwrap(),
+            sess.manifest.package.name
+        )));
+    }
+    Ok(())
+}
+
+/// The kind of a license, as far as compatibility is concerned.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Kind {
+    /// Permissive licenses, e.g. `MIT` or `Apache-2.0`.
+    Permissive,
+    /// File- or library-level copyleft, e.g. `LGPL-2.1-only` or `MPL-2.0`.
+    WeakCopyleft,
+    /// Copyleft that extends to the combined work, e.g. `GPL-3.0-only`.
+    StrongCopyleft,
+    /// Licenses that do not permit redistribution, i.e. `LicenseRef-*`.
+    Proprietary,
+}
+
+/// Classify an SPDX license identifier.
+///
+/// Returns `None` for unknown identifiers, which are never flagged.
+fn kind(id: &str) -> Option<Kind> {
+    if id.starts_with("LicenseRef-") {
+        return Some(Kind::Proprietary);
+    }
+    if id.starts_with("LGPL-") || id.starts_with("MPL-") || id.starts_with("EPL-") {
+        return Some(Kind::WeakCopyleft);
+    }
+    if id.starts_with("GPL-") || id.starts_with("AGPL-") {
+        return Some(Kind::StrongCopyleft);
+    }
+    match id {
+        "CERN-OHL-W-2.0" => Some(Kind::WeakCopyleft),
+        "CERN-OHL-S-2.0" => Some(Kind::StrongCopyleft),
+        "0BSD" | "Apache-2.0" | "BSD-2-Clause" | "BSD-3-Clause" | "CERN-OHL-P-2.0" | "ISC"
+        | "MIT" | "SHL-0.5" | "SHL-0.51" | "Unlicense" | "Zlib" => Some(Kind::Permissive),
+        _ => None,
+    }
+}
+
+/// Pairs of (root, dependency) licenses that cannot be combined, in addition
+/// to the rules based on the license kinds.
+const INCOMPATIBLE: &[(&str, &str)] = &[
+    ("GPL-2.0-only", "Apache-2.0"),
+    ("GPL-2.0-only", "GPL-3.0-only"),
+    ("GPL-2.0-only", "GPL-3.0-or-later"),
+    ("GPL-2.0-only", "LGPL-3.0-only"),
+    ("GPL-2.0-only", "LGPL-3.0-or-later"),
+    ("GPL-3.0-only", "GPL-2.0-only"),
+    ("GPL-3.0-or-later", "GPL-2.0-only"),
+];
+
+/// Extract the license identifier from a term of a license expression.
+///
+/// License exceptions are dropped and deprecated identifiers of the GNU
+/// licenses are normalized, e.g. `GPL-2.0+` to `GPL-2.0-or-later`.
+fn identifier(term: &str) -> String {
+    let id = term
+        .split(" WITH ")
+        .next()
+        .unwrap_or(term)
+        .trim_matches(|c: char| c == '(' || c == ')' || c.is_whitespace());
+    match id.strip_suffix('+') {
+        Some(base) if base.starts_with("GPL-") || base.starts_with("LGPL-") => {
+            format!("{}-or-later", base)
+        }
+        _ if ["GPL-2.0", "GPL-3.0", "LGPL-2.1", "LGPL-3.0"].contains(&id) => {
+            format!("{}-only", id)
+        }
+        _ => id.to_string(),
+    }
+}
+
+/// Check whether a dependency license is compatible with the root license.
+///
+/// Returns the reason for a potential conflict. License expressions are
+/// supported to the extent of `OR`, `AND`, and `WITH`: a dependency conflicts
+/// if all of its `OR` alternatives conflict. Of the root license, only the
+/// first identifier is considered.
+fn conflict(root: &str, dep: &str) -> Option<&'static str> {
+    let root = identifier(root.split([' ', '(']).find(|s| !s.is_empty())?);
+    let mut reason = None;
+    for alternative in dep.split(" OR ") {
+        let mut alt_reason = None;
+        for term in alternative.split(" AND ") {
+            alt_reason = alt_reason.or_else(|| conflict_single(&root, &identifier(term)));
+        }
+        match alt_reason {
+            Some(r) => reason = Some(r),
+            None => return None,
+        }
+    }
+    reason
+}
+
+/// Check a single dependency license identifier against the root license.
+fn conflict_single(root: &str, dep: &str) -> Option<&'static str> {
+    if INCOMPATIBLE.contains(&(root, dep)) {
+        return Some("the license terms cannot be combined");
+    }
+    match (kind(root)?, kind(dep)?) {
+        (Kind::Proprietary, Kind::StrongCopyleft) => {
+            Some("copyleft license in a proprietary package")
+        }
+        (Kind::Permissive | Kind::WeakCopyleft, Kind::StrongCopyleft) => {
+            Some("copyleft license requires the package to adopt it")
+        }
+        (Kind::StrongCopyleft, Kind::Proprietary) => {
+            Some("proprietary license in a copyleft package")
+        }
+        _ => None,
+    }
+}
@@ -7,15 +7,25 @@
 
 #![deny(missing_docs)]
 
+pub mod bump;
 pub mod checkout;
+pub mod clean;
 pub mod clone;
 pub mod completion;
 pub mod config;
+pub mod diff_lock;
+pub mod doctor;
+pub mod export_deps;
+pub mod file_info;
+pub mod fmt;
 pub mod fusesoc;
 pub mod init;
+pub mod license;
 pub mod packages;
 pub mod parents;
 pub mod path;
+pub mod plugins;
+pub mod resolve;
 pub mod script;
 pub mod sources;
 pub mod vendor;
@@ -27,6 +27,12 @@ pub fn new() -> Command {
             .help("Do not group packages by topological rank")
             .long_help("Do not group packages by topological rank. If the `--graph` option is specified, print multiple lines per package, one for each dependency.")
         )
+        .arg(Arg::new("rev")
+            .long("rev")
+            .num_args(1)
+            .value_name("REV")
+            .help("Read the manifest and lockfile from a git revision of the package instead of the working tree")
+        )
 }
 
 /// Execute the `packages` subcommand.
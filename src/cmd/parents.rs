@@ -5,8 +5,9 @@
 
 use std::io::Write;
 
-use clap::{Arg, ArgMatches, Command};
-use indexmap::IndexMap;
+use clap::builder::PossibleValue;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use indexmap::{IndexMap, IndexSet};
 use tabwriter::TabWriter;
 use tokio::runtime::Runtime;
 
@@ -24,6 +25,37 @@ pub fn new() -> Command {
                 .num_args(1)
                 .help("Package names to get the parents for"),
         )
+        .arg(
+            Arg::new("rev")
+                .long("rev")
+                .num_args(1)
+                .value_name("REV")
+                .help("Read the manifest and lockfile from a git revision of the package instead of the working tree"),
+        )
+        .arg(
+            Arg::new("transitive")
+                .long("transitive")
+                .num_args(0)
+                .action(ArgAction::SetTrue)
+                .help("Also list the packages depending on the package indirectly"),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .help("Output format, `json` emits an array with one object per parent")
+                .num_args(1)
+                .default_value("text")
+                .value_parser([PossibleValue::new("text"), PossibleValue::new("json")]),
+        )
+}
+
+/// A parent of a package as emitted in JSON.
+#[derive(Serialize)]
+struct Parent<'a> {
+    name: &'a str,
+    direct: bool,
+    requires: Option<&'a str>,
+    source: Option<&'a str>,
 }
 
 /// Execute the `parents` subcommand.
@@ -85,7 +117,63 @@ pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
         map
     };
 
-    if parent_array.is_empty() {
+    // Walk up the inverted dependency graph to collect the indirect parents.
+    // Each package is only visited once, even if it is reachable via several
+    // paths.
+    let mut ancestors = IndexSet::<&str>::new();
+    if matches.get_flag("transitive") {
+        let graph = sess.graph();
+        let mut visited = IndexSet::new();
+        visited.insert(sess.dependency_with_name(dep)?);
+        let mut i = 0;
+        while let Some(&child) = visited.get_index(i) {
+            for (&pkg, deps) in graph.iter() {
+                if deps.contains(&child) {
+                    visited.insert(pkg);
+                }
+            }
+            i += 1;
+        }
+        ancestors.extend(
+            visited
+                .iter()
+                .skip(1)
+                .map(|&id| sess.dependency_name(id))
+                .filter(|name| !parent_array.contains_key(*name)),
+        );
+        // The root package is not part of the graph.
+        let root = sess.manifest.package.name.as_str();
+        let reaches_root = visited.iter().any(|&id| {
+            sess.manifest
+                .dependencies
+                .contains_key(sess.dependency_name(id))
+        });
+        if reaches_root && !parent_array.contains_key(root) {
+            ancestors.insert(root);
+        }
+    }
+
+    if matches.get_one::<String>("format").unwrap() == "json" {
+        let parents: Vec<_> = parent_array
+            .iter()
+            .map(|(k, v)| Parent {
+                name: k,
+                direct: true,
+                requires: Some(&v[0]),
+                source: Some(&v[1]),
+            })
+            .chain(ancestors.iter().map(|name| Parent {
+                name,
+                direct: false,
+                requires: None,
+                source: None,
+            }))
+            .collect();
+        let stdout = std::io::stdout();
+        serde_json::to_writer_pretty(stdout.lock(), &parents)
+            .map_err(|cause| Error::chain("Failed to serialize parents.", cause))?;
+        println!();
+    } else if parent_array.is_empty() {
         println!("No parents found for {}.", dep);
     } else {
         println!("Parents found:");
@@ -107,6 +195,9 @@ pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
                 res.push_str(&format!("    {}\trequires: {}\tat {}\n", k, v[0], v[1]).to_string());
             }
         }
+        for name in &ancestors {
+            res.push_str(&format!("    {}\tindirect\n", name));
+        }
         let mut tw = TabWriter::new(vec![]);
         write!(&mut tw, "{}", res).unwrap();
         tw.flush().unwrap();
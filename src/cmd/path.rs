@@ -3,8 +3,12 @@
 
 //! The `path` subcommand.
 
+use std::path::{Component, Path, PathBuf};
+
+use clap::builder::PossibleValue;
 use clap::{Arg, ArgAction, ArgMatches, Command};
 use futures::future::join_all;
+use indexmap::IndexMap;
 use tokio::runtime::Runtime;
 
 use crate::error::*;
@@ -27,6 +31,21 @@ pub fn new() -> Command {
                 .action(ArgAction::SetTrue)
                 .help("Force check out of dependency."),
         )
+        .arg(
+            Arg::new("relative")
+                .long("relative")
+                .num_args(0)
+                .action(ArgAction::SetTrue)
+                .help("Print the paths relative to the package root"),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .help("Output format, `json` emits an object mapping each package to its path")
+                .num_args(1)
+                .default_value("text")
+                .value_parser([PossibleValue::new("text"), PossibleValue::new("json")]),
+        )
 }
 
 /// Execute the `path` subcommand.
@@ -61,11 +80,54 @@ pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
     }
 
     // Print paths
-    for c in paths {
-        if let Some(s) = c.to_str() {
-            println!("{}", s);
+    let paths = if matches.get_flag("relative") {
+        ids.iter()
+            .zip(paths)
+            .map(|(&(name, _), path)| relative_path(sess.root, path, name))
+            .collect()
+    } else {
+        paths
+    };
+    if matches.get_one::<String>("format").unwrap() == "json" {
+        let map: IndexMap<&str, &Path> = ids
+            .iter()
+            .zip(&paths)
+            .map(|(&(_, id), path)| (sess.dependency(id).name.as_str(), path.as_path()))
+            .collect();
+        let stdout = std::io::stdout();
+        serde_json::to_writer_pretty(stdout.lock(), &map)
+            .map_err(|cause| Error::chain("Failed to serialize package paths.", cause))?;
+        println!();
+    } else {
+        for c in paths {
+            if let Some(s) = c.to_str() {
+                println!("{}", s);
+            }
         }
     }
 
     Ok(())
 }
+
+/// Express a package path relative to the root.
+///
+/// Paths outside the root are kept absolute, since a chain of `..` is hard to
+/// follow and breaks as soon as the workspace is moved.
+fn relative_path(root: &Path, path: PathBuf, name: &str) -> PathBuf {
+    match pathdiff::diff_paths(&path, root) {
+        Some(rel) if !rel.components().any(|c| c == Component::ParentDir) => {
+            if rel.as_os_str().is_empty() {
+                PathBuf::from(".")
+            } else {
+                rel
+            }
+        }
+        _ => {
+            noteln!(
+                "Package `{}` is outside the package root, printing its absolute path.",
+                name
+            );
+            path
+        }
+    }
+}
@@ -0,0 +1,52 @@
+// Copyright (c) 2024 ETH Zurich
+
+//! The `plugins` subcommand.
+
+use std::io::Write;
+
+use clap::{ArgMatches, Command};
+use tabwriter::TabWriter;
+use tokio::runtime::Runtime;
+
+use crate::error::*;
+use crate::sess::{Session, SessionIo};
+
+/// Assemble the `plugins` subcommand.
+pub fn new() -> Command {
+    Command::new("plugins").about("List the plugins declared by the dependencies")
+}
+
+/// Execute the `plugins` subcommand.
+pub fn run(sess: &Session, _matches: &ArgMatches) -> Result<()> {
+    let rt = Runtime::new()?;
+    let io = SessionIo::new(sess);
+    let plugins = rt.block_on(io.plugins())?;
+
+    let mut tw = TabWriter::new(vec![]);
+    for plugin in plugins.values() {
+        write!(&mut tw, "{}\t{}\t", plugin.name, plugin.package_name(sess)).unwrap();
+        if let Some(ref interpreter) = plugin.interpreter {
+            write!(&mut tw, "{} ", interpreter).unwrap();
+        }
+        write!(&mut tw, "{}", plugin.path.display()).unwrap();
+        if let Some(problem) = plugin.problem() {
+            write!(&mut tw, "\t{}", problem).unwrap();
+        }
+        writeln!(&mut tw).unwrap();
+    }
+    tw.flush().unwrap();
+    print!("{}", String::from_utf8(tw.into_inner().unwrap()).unwrap());
+
+    for plugin in plugins.values() {
+        if let Some(problem) = plugin.problem() {
+            warnln!(
+                "Plugin `{}` of package `{}` cannot be run, its binary {:?} is {}.",
+                plugin.name,
+                plugin.package_name(sess),
+                plugin.path,
+                problem
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,65 @@
+// Copyright (c) 2024 ETH Zurich
+
+//! The `resolve` subcommand.
+
+use clap::{Arg, ArgMatches, Command};
+
+use crate::error::*;
+use crate::resolver::DependencyResolver;
+use crate::sess::Session;
+
+/// Assemble the `resolve` subcommand.
+pub fn new() -> Command {
+    Command::new("resolve")
+        .about("Analyze the dependency resolution without modifying the lockfile")
+        .arg(
+            Arg::new("explain")
+                .long("explain")
+                .required(true)
+                .num_args(1)
+                .value_name("NAME@VERSION")
+                .help("Check whether a version of a dependency satisfies the requirements of each package depending on it"),
+        )
+}
+
+/// Execute the `resolve` subcommand.
+pub fn run<'ctx>(sess: &'ctx Session<'ctx>, matches: &ArgMatches) -> Result<()> {
+    let spec = matches.get_one::<String>("explain").unwrap();
+    let (name, version) = match spec.split_once('@') {
+        Some((name, version)) => (name.to_lowercase(), version),
+        None => {
+            return Err(Error::new(format!(
+                "Expected `<name>@<version>`, got `{}`.",
+                spec
+            )))
+        }
+    };
+    let version = semver::Version::parse(version.trim_start_matches('v'))
+        .map_err(|cause| Error::chain(format!("Invalid version `{}`.", version), cause))?;
+
+    let verdicts = DependencyResolver::new(sess, None).explain(&name, &version)?;
+    let num_violated = verdicts.iter().filter(|(_, _, ok)| !ok).count();
+    println!("Requirements on `{}` for version {}:", name, version);
+    for (pkg_name, con, ok) in &verdicts {
+        println!(
+            "  {} requires `{}`: {}",
+            pkg_name,
+            con,
+            if *ok { "satisfied" } else { "not satisfied" }
+        );
+    }
+    if num_violated > 0 {
+        return Err(Error::new(format!(
+            "Version {} of `{}` violates {} of {} requirement(s).",
+            version,
+            name,
+            num_violated,
+            verdicts.len()
+        )));
+    }
+    println!(
+        "Version {} of `{}` satisfies all requirements.",
+        version, name
+    );
+    Ok(())
+}
@@ -13,10 +13,12 @@ use indexmap::{IndexMap, IndexSet};
 use tera::{Context, Tera};
 use tokio::runtime::Runtime;
 
+use crate::config::parse_flist;
 use crate::error::*;
 use crate::sess::{Session, SessionIo};
 use crate::src::{SourceFile, SourceGroup};
 use crate::target::{TargetSet, TargetSpec};
+use crate::util::read_list_file;
 
 /// Assemble the `script` subcommand.
 pub fn new() -> Command {
@@ -31,6 +33,28 @@ pub fn new() -> Command {
                 .action(ArgAction::Append)
                 .value_parser(value_parser!(String)),
         )
+        .arg(
+            Arg::new("target-file")
+                .long("target-file")
+                .help("Read additional targets from a file, one per line")
+                .num_args(1)
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("force-target")
+                .long("force-target")
+                .help("Always activate the given target and add its `TARGET_*` define")
+                .num_args(1)
+                .action(ArgAction::Append)
+                .value_parser(value_parser!(String)),
+        )
+        .arg(
+            Arg::new("no-target-filter")
+                .long("no-target-filter")
+                .help("Emit the sources of all targets, with the union of their defines, e.g. for indexing")
+                .num_args(0)
+                .action(ArgAction::SetTrue),
+        )
         .arg(
             Arg::new("no-default-target")
                 .long("no-default-target")
@@ -38,6 +62,13 @@ pub fn new() -> Command {
                 .num_args(0)
                 .action(ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("no-default-defines")
+                .long("no-default-defines")
+                .help("Do not add the `TARGET_*` defines of the active targets")
+                .num_args(0)
+                .action(ArgAction::SetTrue),
+        )
         .arg(
             Arg::new("format")
                 .help("Format of the generated script")
@@ -56,6 +87,8 @@ pub fn new() -> Command {
                     PossibleValue::new("vivado"),
                     PossibleValue::new("vivado-sim"),
                     PossibleValue::new("precision"),
+                    PossibleValue::new("yosys"),
+                    PossibleValue::new("makefile"),
                     PossibleValue::new("template"),
                     PossibleValue::new("template_json"),
                 ]),
@@ -65,7 +98,98 @@ pub fn new() -> Command {
                 .long("relative-path")
                 .num_args(0)
                 .action(ArgAction::SetTrue)
-                .help("Use relative paths (flist generation only)"),
+                .overrides_with("no-relative-path")
+                .help("Use relative paths (flist generation only, default for flist and flist-plus)"),
+        )
+        .arg(
+            Arg::new("no-relative-path")
+                .long("no-relative-path")
+                .num_args(0)
+                .action(ArgAction::SetTrue)
+                .overrides_with("relative-path")
+                .help("Use absolute paths, also for flist and flist-plus"),
+        )
+        .arg(
+            Arg::new("replace-path")
+                .long("replace-path")
+                .help("Rewrite the prefix FROM of the emitted paths to TO after relativization, the first matching rule applies")
+                .num_args(1)
+                .value_name("FROM=TO")
+                .action(ArgAction::Append)
+                .value_parser(value_parser!(String)),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .help("Write the script to the given file instead of stdout")
+                .num_args(1)
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("output-dir")
+                .long("output-dir")
+                .help("Write the script into the given directory, using a conventional filename for the format (e.g. `compile.flist` or `vsim.tcl`)")
+                .num_args(1)
+                .conflicts_with("output")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("recursive-flist")
+                .long("recursive-flist")
+                .help("Write one file list per package into the given directory and emit a top file list referencing them with `-f` (flist generation only)")
+                .num_args(1)
+                .conflicts_with_all(["output", "output-dir"])
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("split-output")
+                .long("split-output")
+                .help("Write one script per package into the given directory, along with a top-level script including them in dependency order")
+                .num_args(1)
+                .conflicts_with_all(["output", "output-dir", "recursive-flist", "chunk-command"])
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("split-by-language-dir")
+                .long("split-by-language-dir")
+                .help("Write the Verilog and VHDL files to `verilog.f` and `vhdl.f` in the given directory, e.g. for separate tools (flist generation only)")
+                .num_args(1)
+                .conflicts_with_all(["output", "output-dir", "split-output", "recursive-flist"])
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("normalize-line-endings")
+                .long("normalize-line-endings")
+                .help("Line endings of the generated output")
+                .num_args(1)
+                .default_value("lf")
+                .value_parser([PossibleValue::new("lf"), PossibleValue::new("crlf")]),
+        )
+        .arg(
+            Arg::new("group-separator")
+                .long("group-separator")
+                .help("Emit the given line between the files of consecutive source groups (flist and template generation only)")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("comment-style")
+                .long("comment-style")
+                .help("Comment syntax of the generated header, `none` omits it (flist generation only) [default: none]")
+                .num_args(1)
+                .value_parser([
+                    PossibleValue::new("//"),
+                    PossibleValue::new("#"),
+                    PossibleValue::new("none"),
+                ]),
+        )
+        .arg(
+            Arg::new("prefix-defines")
+                .long("prefix-defines")
+                .help("Prepend a prefix to the name of every emitted define, e.g. `FOO` becomes `PFX_FOO`")
+                .num_args(1)
+                .value_name("PFX")
+                .value_parser(value_parser!(String)),
         )
         .arg(
             Arg::new("define")
@@ -76,6 +200,23 @@ pub fn new() -> Command {
                 .action(ArgAction::Append)
                 .value_parser(value_parser!(String)),
         )
+        .arg(
+            Arg::new("undefine")
+                .short('U')
+                .long("undefine")
+                .help("Remove a define from all source files, including the ones of dependencies and `-D`")
+                .num_args(1..)
+                .action(ArgAction::Append)
+                .value_parser(value_parser!(String)),
+        )
+        .arg(
+            Arg::new("define-from-json")
+                .long("define-from-json")
+                .help("Pass the entries of a JSON object as additional defines to all source files, nested keys are joined with `_`")
+                .num_args(1)
+                .action(ArgAction::Append)
+                .value_parser(value_parser!(PathBuf)),
+        )
         .arg(
             Arg::new("vcom-arg")
                 .long("vcom-arg")
@@ -92,6 +233,39 @@ pub fn new() -> Command {
                 .action(ArgAction::Append)
                 .value_parser(value_parser!(String)),
         )
+        .arg(
+            Arg::new("vhdl-relative-work")
+                .long("vhdl-relative-work")
+                .help("Create and map a directory below the given one for each VHDL library (vsim/riviera/synopsys only)")
+                .num_args(1)
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("libdir")
+                .long("libdir")
+                .help("Pass a library directory in which modules are looked up, as `-y <dir>` (flist-plus/vsim/vcs/riviera/verilator only)")
+                .num_args(1)
+                .action(ArgAction::Append)
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("libext")
+                .long("libext")
+                .help("File extension of the modules in the library directories, passed as `+libext+<ext>`")
+                .num_args(1)
+                .action(ArgAction::Append)
+                .default_value(".v")
+                .requires("libdir")
+                .value_parser(value_parser!(String)),
+        )
+        .arg(
+            Arg::new("verilator-arg")
+                .long("verilator-arg")
+                .help("Pass an argument to verilator (verilator only)")
+                .num_args(1..)
+                .action(ArgAction::Append)
+                .value_parser(value_parser!(String)),
+        )
         .arg(
             Arg::new("only-defines")
                 .long("only-defines")
@@ -143,6 +317,35 @@ pub fn new() -> Command {
                 .action(ArgAction::SetTrue)
                 .help("Do not abort analysis/compilation on first caught error (only for programs that support early aborting)")
         )
+        .arg(
+            Arg::new("abort-file")
+                .long("abort-file")
+                .num_args(1)
+                .value_parser(value_parser!(PathBuf))
+                .conflicts_with("no-abort-on-error")
+                .help("Create the given file when aborting on an error (only for vsim and vcs)")
+        )
+        .arg(
+            Arg::new("max-files-per-group")
+                .long("max-files-per-group")
+                .help("Split source groups into chunks of at most this many files")
+                .num_args(1)
+                .value_parser(value_parser!(u64).range(1..)),
+        )
+        .arg(
+            Arg::new("chunk-command")
+                .long("chunk-command")
+                .help("Emit the given command once per chunk of files instead of the script, substituting `{files}`, `{incdirs}`, and `{defines}`")
+                .num_args(1)
+                .conflicts_with_all(["recursive-flist", "template"]),
+        )
+        .arg(
+            Arg::new("include-manifest-metadata")
+                .long("include-manifest-metadata")
+                .num_args(0)
+                .action(ArgAction::SetTrue)
+                .help("Prefix the sources of each package with a comment naming its version, revision, and source"),
+        )
         .arg(
             Arg::new("compilation_mode")
                 .long("compilation-mode")
@@ -171,6 +374,15 @@ pub fn new() -> Command {
                 .action(ArgAction::SetTrue)
                 .help("Exclude all dependencies, i.e. only top level or specified package(s)"),
         )
+        .arg(
+            Arg::new("with-deps")
+                .long("with-deps")
+                .num_args(0)
+                .action(ArgAction::SetTrue)
+                .requires("package")
+                .conflicts_with("no_deps")
+                .help("Include all transitive dependencies of the specified package(s) from the dependency graph"),
+        )
         .arg(
             Arg::new("exclude")
                 .short('e')
@@ -180,6 +392,29 @@ pub fn new() -> Command {
                 .action(ArgAction::Append)
                 .value_parser(value_parser!(String)),
         )
+        .arg(
+            Arg::new("flist")
+                .long("flist")
+                .help("Add the include directories, defines, and files of the given file list (`.f` file) to the sources")
+                .num_args(1)
+                .action(ArgAction::Append)
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("path-map")
+                .long("path-map")
+                .help("Write a JSON map from the absolute paths of the sources to stable `<package>/<path>` keys to the given file")
+                .num_args(1)
+                .value_name("FILE")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("file-list-from")
+                .long("file-list-from")
+                .help("Only include the source files listed in the given file, one per line")
+                .num_args(1)
+                .value_parser(value_parser!(PathBuf)),
+        )
         .arg(
             Arg::new("template")
                 .long("template")
@@ -201,6 +436,61 @@ where
         .collect()
 }
 
+/// Collect the targets given with `--target` and read from `--target-file`.
+fn get_targets(matches: &ArgMatches) -> Result<Vec<String>> {
+    let mut targets: Vec<String> = matches
+        .get_many::<String>("target")
+        .into_iter()
+        .flatten()
+        .cloned()
+        .collect();
+    if let Some(path) = matches.get_one::<PathBuf>("target-file") {
+        targets.extend(read_list_file(path).map_err(|cause| {
+            Error::chain(format!("Cannot read target file {:?}.", path), cause)
+        })?);
+    }
+    Ok(targets)
+}
+
+/// Keep only the listed files in the flattened source groups.
+///
+/// The groups retain their include directories and defines, and groups
+/// without any remaining files are dropped. Paths are compared after
+/// canonicalization, and listed files not among the sources are reported.
+fn filter_file_list<'ctx>(
+    srcs: Vec<SourceGroup<'ctx>>,
+    files: &[String],
+) -> Vec<SourceGroup<'ctx>> {
+    let canonical = |p: &Path| fs::canonicalize(p).unwrap_or_else(|_| p.to_path_buf());
+    let mut listed: IndexMap<PathBuf, &str> = files
+        .iter()
+        .map(|file| (canonical(Path::new(file)), file.as_str()))
+        .collect();
+    let mut found = IndexSet::new();
+    let srcs = srcs
+        .into_iter()
+        .filter_map(|mut group| {
+            group.files.retain(|file| match file {
+                SourceFile::File(p) => {
+                    let p = canonical(p);
+                    let keep = listed.contains_key(&p);
+                    if keep {
+                        found.insert(p);
+                    }
+                    keep
+                }
+                SourceFile::Group(_) => false,
+            });
+            (!group.files.is_empty()).then_some(group)
+        })
+        .collect();
+    listed.retain(|p, _| !found.contains(p));
+    for file in listed.values() {
+        warnln!("File `{}` is not among the sources and is ignored.", file);
+    }
+    srcs
+}
+
 /// Execute the `script` subcommand.
 pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
     let rt = Runtime::new()?;
@@ -227,6 +517,8 @@ pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
             "vivado" => concat(vivado_targets, &["synthesis"]),
             "vivado-sim" => concat(vivado_targets, &["simulation"]),
             "precision" => vec!["precision", "fpga", "synthesis"],
+            "yosys" => vec!["yosys", "synthesis"],
+            "makefile" => vec!["makefile"],
             "template" => vec![],
             "template_json" => vec![],
             _ => unreachable!(),
@@ -235,29 +527,46 @@ pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
         vec![]
     };
 
+    // Forced targets are always active, regardless of the default targets.
+    let format_targets: Vec<&str> = format_targets
+        .into_iter()
+        .chain(
+            matches
+                .get_many::<String>("force-target")
+                .into_iter()
+                .flatten()
+                .map(String::as_str),
+        )
+        .collect();
+
     // Filter the sources by target.
-    let targets = matches
-        .get_many::<String>("target")
-        .map(|t| {
-            TargetSet::new(
-                t.map(|element| element.as_str())
-                    .chain(format_targets.clone()),
-            )
-        })
-        .unwrap_or_else(|| TargetSet::new(format_targets));
-    srcs = srcs
-        .filter_targets(&targets)
-        .unwrap_or_else(|| SourceGroup {
-            package: Default::default(),
-            independent: true,
-            target: TargetSpec::Wildcard,
-            include_dirs: Default::default(),
-            export_incdirs: Default::default(),
-            defines: Default::default(),
-            files: Default::default(),
-            dependencies: Default::default(),
-            version: None,
-        });
+    let targets = get_targets(matches)?;
+    let targets = TargetSet::new(
+        targets
+            .iter()
+            .map(String::as_str)
+            .chain(format_targets.clone()),
+    );
+    srcs = if matches.get_flag("no-target-filter") {
+        Some(srcs.ignore_targets())
+    } else {
+        srcs.filter_targets(&targets)
+    }
+    .unwrap_or_else(|| SourceGroup {
+        package: Default::default(),
+        independent: true,
+        target: TargetSpec::Wildcard,
+        include_dirs: Default::default(),
+        export_incdirs: Default::default(),
+        defines: Default::default(),
+        target_defines: Default::default(),
+        headers: Default::default(),
+        library: None,
+        files: Default::default(),
+        dependencies: Default::default(),
+        version: None,
+    });
+    srcs.warn_conflicting_defines();
 
     // Filter the sources by specified packages.
     let packages = &srcs.get_package_list(
@@ -271,6 +580,7 @@ pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
             .map(get_package_strings)
             .unwrap_or_default(),
         matches.get_flag("no_deps"),
+        matches.get_flag("with-deps"),
     );
 
     if matches.contains_id("package")
@@ -286,14 +596,55 @@ pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
                 include_dirs: Default::default(),
                 export_incdirs: Default::default(),
                 defines: Default::default(),
+                target_defines: Default::default(),
+                headers: Default::default(),
+                library: None,
                 files: Default::default(),
                 dependencies: Default::default(),
                 version: None,
             });
     }
 
+    // Add the sources of external file lists after the ones of the packages.
+    for path in matches.get_many::<PathBuf>("flist").into_iter().flatten() {
+        let flist = parse_flist(path)?;
+        srcs.files.push(SourceFile::Group(Box::new(SourceGroup {
+            package: None,
+            independent: true,
+            target: TargetSpec::Wildcard,
+            include_dirs: flist
+                .include_dirs
+                .into_iter()
+                .map(|p| sess.intern_path(p))
+                .collect(),
+            export_incdirs: Default::default(),
+            defines: flist
+                .defines
+                .into_iter()
+                .map(|(k, v)| (sess.intern_string(k), v.map(|v| sess.intern_string(v))))
+                .collect(),
+            target_defines: Default::default(),
+            headers: Default::default(),
+            library: None,
+            files: flist
+                .files
+                .into_iter()
+                .map(|p| SourceFile::File(sess.intern_path(p)))
+                .collect(),
+            dependencies: Default::default(),
+            version: None,
+        })));
+    }
+
     // Flatten the sources.
-    let srcs = srcs.flatten();
+    let mut srcs = srcs.flatten();
+
+    // Restrict the sources to an explicit list of files.
+    if let Some(path) = matches.get_one::<PathBuf>("file-list-from") {
+        let files = read_list_file(path)
+            .map_err(|cause| Error::chain(format!("Cannot read file list {:?}.", path), cause))?;
+        srcs = filter_file_list(srcs, &files);
+    }
 
     // Validate format-specific options.
     if (matches.contains_id("vcom-arg") || matches.contains_id("vlog-arg"))
@@ -307,6 +658,39 @@ pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
             "vsim/vcs-only options can only be used for 'vcs', 'vsim' or 'riviera' format!",
         ));
     }
+    if matches.contains_id("vhdl-relative-work")
+        && format != "vsim"
+        && format != "riviera"
+        && format != "synopsys"
+        && format != "template"
+        && format != "template_json"
+    {
+        return Err(Error::new(
+            "VHDL library directories can only be used for 'vsim', 'riviera' or 'synopsys' format!",
+        ));
+    }
+    if matches.contains_id("libdir")
+        && format != "flist-plus"
+        && format != "vsim"
+        && format != "vcs"
+        && format != "riviera"
+        && format != "verilator"
+        && format != "template"
+        && format != "template_json"
+    {
+        return Err(Error::new(
+            "Library directories can only be used for 'flist-plus', 'vsim', 'vcs', 'riviera' or 'verilator' format!",
+        ));
+    }
+    if matches.contains_id("verilator-arg")
+        && format != "verilator"
+        && format != "template"
+        && format != "template_json"
+    {
+        return Err(Error::new(
+            "verilator-only options can only be used for 'verilator' format!",
+        ));
+    }
     if (matches.get_flag("only-defines")
         || matches.get_flag("only-includes")
         || matches.get_flag("only-sources")
@@ -320,8 +704,65 @@ pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
         ));
     }
 
+    if (matches.contains_id("recursive-flist")
+        || matches.contains_id("comment-style")
+        || matches.contains_id("split-by-language-dir"))
+        && format != "flist"
+        && format != "flist-plus"
+    {
+        return Err(Error::new(
+            "flist-only options can only be used for 'flist' or 'flist-plus' format!",
+        ));
+    }
+    if matches.contains_id("group-separator")
+        && format != "flist"
+        && format != "flist-plus"
+        && format != "template"
+        && format != "template_json"
+    {
+        return Err(Error::new(
+            "The group separator can only be used for 'flist', 'flist-plus' or template formats!",
+        ));
+    }
+
+    if matches.get_flag("include-manifest-metadata")
+        && matches!(
+            format.as_str(),
+            "flist" | "flist-plus" | "precision" | "makefile"
+        )
+    {
+        return Err(Error::new(format!(
+            "Manifest metadata comments are not supported for '{}' format!",
+            format
+        )));
+    }
+
+    // Fail early if no filename can be determined for the output.
+    if matches.contains_id("output-dir") {
+        output_file_name(format)?;
+    }
+    if matches.contains_id("split-output") {
+        include_line(format, Path::new(""))?;
+    }
+
+    if let Some(path) = matches.get_one::<PathBuf>("path-map") {
+        write_path_map(sess, &srcs, path)?;
+    }
+
     // Generate the corresponding output.
     match format.as_str() {
+        "flist" | "flist-plus" if matches.contains_id("recursive-flist") => emit_recursive_flist(
+            sess,
+            if format == "flist" {
+                include_str!("../script_fmt/flist.tera")
+            } else {
+                include_str!("../script_fmt/flist-plus.tera")
+            },
+            matches,
+            targets,
+            srcs,
+            matches.get_one::<PathBuf>("recursive-flist").unwrap(),
+        ),
         "flist" => emit_template(
             sess,
             include_str!("../script_fmt/flist.tera"),
@@ -406,6 +847,20 @@ pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
             targets,
             srcs,
         ),
+        "yosys" => emit_template(
+            sess,
+            include_str!("../script_fmt/yosys_ys.tera"),
+            matches,
+            targets,
+            srcs,
+        ),
+        "makefile" => emit_template(
+            sess,
+            include_str!("../script_fmt/makefile.tera"),
+            matches,
+            targets,
+            srcs,
+        ),
         "template" => {
             let custom_tpl_path = Path::new(matches.get_one::<String>("template").unwrap());
             let custom_tpl_str =
@@ -453,6 +908,21 @@ enum SourceType {
     Vhdl,
 }
 
+/// Whether paths below the root are emitted relative to it.
+///
+/// File lists are usually consumed from a known directory and default to
+/// relative paths, while the tool scripts embed absolute paths.
+fn relative_paths(matches: &ArgMatches) -> bool {
+    if matches.get_flag("no-relative-path") {
+        return false;
+    }
+    matches.get_flag("relative-path")
+        || matches!(
+            matches.get_one::<String>("format").unwrap().as_str(),
+            "flist" | "flist-plus"
+        )
+}
+
 fn relativize_path(path: &std::path::Path, root: &std::path::Path) -> String {
     if path.starts_with(root) {
         format!(
@@ -466,7 +936,72 @@ fn relativize_path(path: &std::path::Path, root: &std::path::Path) -> String {
 
 static HEADER_AUTOGEN: &str = "This script was generated automatically by bender.";
 
-fn add_defines_from_matches(defines: &mut IndexMap<String, Option<String>>, matches: &ArgMatches) {
+/// Add the `TARGET_*` defines, without overriding defines that are already set.
+///
+/// Defines are resolved with the following precedence, from highest to lowest:
+/// command line `-D` defines, the defines of a source group itself (including
+/// its `target_defines` that match), the defines inherited from enclosing groups
+/// up to the package's top-level sources, and finally the target defines. The
+/// inheritance is resolved when flattening the source groups.
+fn add_target_defines(
+    defines: &mut IndexMap<String, Option<String>>,
+    target_defines: &IndexMap<String, Option<String>>,
+) {
+    for (name, value) in target_defines {
+        defines.entry(name.clone()).or_insert_with(|| value.clone());
+    }
+}
+
+/// Parse the `FROM=TO` rules given with `--replace-path`.
+fn replace_path_rules(matches: &ArgMatches) -> Result<Vec<(PathBuf, PathBuf)>> {
+    matches
+        .get_many::<String>("replace-path")
+        .into_iter()
+        .flatten()
+        .map(|rule| match rule.split_once('=') {
+            Some((from, to)) if !from.is_empty() => Ok((PathBuf::from(from), PathBuf::from(to))),
+            _ => Err(Error::new(format!(
+                "Invalid path replacement `{}`, expected `FROM=TO`.",
+                rule
+            ))),
+        })
+        .collect()
+}
+
+/// Rewrite the prefix of a path with the first matching `--replace-path` rule.
+fn replace_path(path: &Path, rules: &[(PathBuf, PathBuf)]) -> PathBuf {
+    for (from, to) in rules {
+        if let Ok(rest) = path.strip_prefix(from) {
+            if rest.as_os_str().is_empty() {
+                return to.clone();
+            }
+            return to.join(rest);
+        }
+    }
+    path.to_path_buf()
+}
+
+/// Collect the defines given with `--define-from-json` and `-D`, in this order.
+fn defines_from_matches(matches: &ArgMatches) -> Result<IndexMap<String, Option<String>>> {
+    let mut defines = IndexMap::new();
+    for path in matches
+        .get_many::<PathBuf>("define-from-json")
+        .into_iter()
+        .flatten()
+    {
+        let data = fs::read_to_string(path)
+            .map_err(|cause| Error::chain(format!("Cannot read define file {:?}.", path), cause))?;
+        let value: serde_json::Value = serde_json::from_str(&data).map_err(|cause| {
+            Error::chain(format!("Syntax error in define file {:?}.", path), cause)
+        })?;
+        if !value.is_object() {
+            return Err(Error::new(format!(
+                "Define file {:?} must contain a JSON object.",
+                path
+            )));
+        }
+        add_json_defines(&mut defines, None, &value);
+    }
     if let Some(d) = matches.get_many::<String>("define") {
         defines.extend(d.map(|t| {
             let mut parts = t.splitn(2, '=');
@@ -475,6 +1010,69 @@ fn add_defines_from_matches(defines: &mut IndexMap<String, Option<String>>, matc
             (name.to_string(), value)
         }));
     }
+    Ok(defines)
+}
+
+/// Add the entries of a JSON value as defines.
+///
+/// Nested objects are flattened by joining the keys with `_`. Strings are
+/// used verbatim, `null` yields a define without value, and other values are
+/// emitted in their JSON representation.
+fn add_json_defines(
+    defines: &mut IndexMap<String, Option<String>>,
+    name: Option<&str>,
+    value: &serde_json::Value,
+) {
+    use serde_json::Value;
+    match (name, value) {
+        (_, Value::Object(map)) => {
+            for (key, value) in map {
+                let key = match name {
+                    Some(name) => format!("{}_{}", name, key),
+                    None => key.clone(),
+                };
+                add_json_defines(defines, Some(&key), value);
+            }
+        }
+        (None, _) => unreachable!(),
+        (Some(name), Value::Null) => {
+            defines.insert(name.to_string(), None);
+        }
+        (Some(name), Value::String(s)) => {
+            defines.insert(name.to_string(), Some(s.clone()));
+        }
+        (Some(name), value) => {
+            defines.insert(name.to_string(), Some(value.to_string()));
+        }
+    }
+}
+
+/// Remove the defines given with `-U`.
+///
+/// This is applied last, such that it also removes the defines of the
+/// manifests, the targets, and `-D`.
+fn undefine(
+    mut defines: IndexMap<String, Option<String>>,
+    matches: &ArgMatches,
+) -> IndexMap<String, Option<String>> {
+    for name in matches.get_many::<String>("undefine").into_iter().flatten() {
+        defines.shift_remove(name.trim());
+    }
+    defines
+}
+
+/// Prepend the `--prefix-defines` prefix, if any, to the names of the defines.
+fn prefix_defines(
+    defines: IndexMap<String, Option<String>>,
+    matches: &ArgMatches,
+) -> IndexMap<String, Option<String>> {
+    match matches.get_one::<String>("prefix-defines") {
+        Some(prefix) => defines
+            .into_iter()
+            .map(|(name, value)| (format!("{}_{}", prefix, name), value))
+            .collect(),
+        None => defines,
+    }
 }
 
 static JSON: &str = "json";
@@ -486,30 +1084,317 @@ fn emit_template(
     targets: TargetSet,
     srcs: Vec<SourceGroup>,
 ) -> Result<()> {
+    if let Some(dir) = matches.get_one::<PathBuf>("split-output") {
+        return emit_split_output(sess, template, matches, targets, srcs, dir);
+    }
+    if let Some(dir) = matches.get_one::<PathBuf>("split-by-language-dir") {
+        return emit_language_split(sess, template, matches, targets, srcs, dir);
+    }
+    let script = render_template(sess, template, matches, targets, srcs)?;
+    let path = match (
+        matches.get_one::<PathBuf>("output"),
+        matches.get_one::<PathBuf>("output-dir"),
+    ) {
+        (Some(path), _) => path.clone(),
+        (None, Some(dir)) => {
+            fs::create_dir_all(dir).map_err(|cause| {
+                Error::chain(format!("Failed to create directory {:?}.", dir), cause)
+            })?;
+            dir.join(output_file_name(
+                matches.get_one::<String>("format").unwrap(),
+            )?)
+        }
+        (None, None) => {
+            print!("{}", script);
+            return Ok(());
+        }
+    };
+    fs::write(&path, script)
+        .map_err(|cause| Error::chain(format!("Failed to write {:?}.", path), cause))?;
+    stageln!("Generated", "{}", path.display());
+    Ok(())
+}
+
+/// The conventional filename of a script format, used with `--output-dir`.
+fn output_file_name(format: &str) -> Result<&'static str> {
+    Ok(match format {
+        "flist" => "compile.flist",
+        "flist-plus" => "compile_plus.flist",
+        "vsim" => "vsim.tcl",
+        "vcs" => "vcs.sh",
+        "verilator" => "verilator.sh",
+        "synopsys" => "synopsys.tcl",
+        "formality" => "formality.tcl",
+        "riviera" => "riviera.tcl",
+        "genus" => "genus.tcl",
+        "vivado" => "vivado.tcl",
+        "vivado-sim" => "vivado_sim.tcl",
+        "precision" => "precision.tcl",
+        "yosys" => "yosys.ys",
+        "makefile" => "sources.mk",
+        "template_json" => "template.json",
+        _ => {
+            return Err(Error::new(format!(
+                "There is no conventional filename for the `{}` format. Use `--output` instead.",
+                format
+            )))
+        }
+    })
+}
+
+/// Write one file list per package and emit a top file list including them.
+fn emit_recursive_flist(
+    sess: &Session,
+    template: &str,
+    matches: &ArgMatches,
+    targets: TargetSet,
+    srcs: Vec<SourceGroup>,
+    dir: &Path,
+) -> Result<()> {
+    fs::create_dir_all(dir)
+        .map_err(|cause| Error::chain(format!("Failed to create directory {:?}.", dir), cause))?;
+
+    let mut top = comment_prefix(matches)
+        .map(|prefix| format!("{} {}\n", prefix, HEADER_AUTOGEN))
+        .unwrap_or_default();
+    for (package, srcs) in group_by_package(&sess.manifest.package.name, srcs) {
+        let path = dir.join(format!("{}.f", package));
+        let flist = render_template(sess, template, matches, targets.clone(), srcs)?;
+        fs::write(&path, flist)
+            .map_err(|cause| Error::chain(format!("Failed to write {:?}.", path), cause))?;
+        top.push_str(&format!("-f {}\n", path.display()));
+    }
+    print!("{}", normalize_line_endings(&top, matches));
+    Ok(())
+}
+
+/// Write the Verilog and VHDL files to separate file lists.
+///
+/// The Verilog file list carries the include directories and defines of the
+/// Verilog files as in the given format. VHDL has no preprocessor, such that
+/// the VHDL file list only lists the files.
+fn emit_language_split(
+    sess: &Session,
+    template: &str,
+    matches: &ArgMatches,
+    targets: TargetSet,
+    srcs: Vec<SourceGroup>,
+    dir: &Path,
+) -> Result<()> {
+    fs::create_dir_all(dir)
+        .map_err(|cause| Error::chain(format!("Failed to create directory {:?}.", dir), cause))?;
+    let languages = [
+        ("verilog.f", template, &["sv", "v", "vp"][..]),
+        (
+            "vhdl.f",
+            include_str!("../script_fmt/flist.tera"),
+            &["vhd", "vhdl"][..],
+        ),
+    ];
+    for (name, template, extensions) in languages {
+        let srcs = srcs
+            .iter()
+            .filter_map(|src| {
+                let files: Vec<_> = src
+                    .files
+                    .iter()
+                    .filter(|file| match file {
+                        SourceFile::File(p) => p
+                            .extension()
+                            .and_then(std::ffi::OsStr::to_str)
+                            .is_some_and(|ext| extensions.contains(&ext)),
+                        SourceFile::Group(_) => false,
+                    })
+                    .cloned()
+                    .collect();
+                (!files.is_empty()).then(|| SourceGroup {
+                    files,
+                    ..src.clone()
+                })
+            })
+            .collect();
+        let script = render_template(sess, template, matches, targets.clone(), srcs)?;
+        let path = dir.join(name);
+        fs::write(&path, script)
+            .map_err(|cause| Error::chain(format!("Failed to write {:?}.", path), cause))?;
+        stageln!("Generated", "{}", path.display());
+    }
+    Ok(())
+}
+
+/// Write one script per package and a top-level script including them.
+fn emit_split_output(
+    sess: &Session,
+    template: &str,
+    matches: &ArgMatches,
+    targets: TargetSet,
+    srcs: Vec<SourceGroup>,
+    dir: &Path,
+) -> Result<()> {
+    fs::create_dir_all(dir)
+        .map_err(|cause| Error::chain(format!("Failed to create directory {:?}.", dir), cause))?;
+    // Include the package scripts by absolute path, such that the top-level
+    // script works regardless of the working directory.
+    let dir = &dir
+        .canonicalize()
+        .map_err(|cause| Error::chain(format!("Failed to canonicalize {:?}.", dir), cause))?;
+    let format = matches.get_one::<String>("format").unwrap();
+    let top_name = output_file_name(format)?;
+    let extension = Path::new(top_name)
+        .extension()
+        .unwrap()
+        .to_string_lossy()
+        .into_owned();
+
+    let mut top = match format.as_str() {
+        "flist" | "flist-plus" => comment_prefix(matches)
+            .map(|prefix| format!("{} {}\n", prefix, HEADER_AUTOGEN))
+            .unwrap_or_default(),
+        "verilator" => String::new(),
+        "vcs" => format!("#!/usr/bin/env bash\n# {}\nset -e\n", HEADER_AUTOGEN),
+        _ => format!("# {}\n", HEADER_AUTOGEN),
+    };
+    for (package, srcs) in group_by_package(&sess.manifest.package.name, srcs) {
+        let name = format!("{}.{}", package, extension);
+        if name == top_name {
+            return Err(Error::new(format!(
+                "The script of package `{}` would overwrite the top-level script {:?}.",
+                package, top_name
+            )));
+        }
+        let path = dir.join(name);
+        let script = render_template(sess, template, matches, targets.clone(), srcs)?;
+        fs::write(&path, script)
+            .map_err(|cause| Error::chain(format!("Failed to write {:?}.", path), cause))?;
+        top.push_str(&include_line(format, &path)?);
+        top.push('\n');
+    }
+
+    let path = dir.join(top_name);
+    fs::write(&path, normalize_line_endings(&top, matches))
+        .map_err(|cause| Error::chain(format!("Failed to write {:?}.", path), cause))?;
+    stageln!("Generated", "{}", path.display());
+    Ok(())
+}
+
+/// The line of a top-level script that includes the script of a package,
+/// used with `--split-output`.
+///
+/// Formats that set defines or include directories globally cannot be split.
+fn include_line(format: &str, path: &Path) -> Result<String> {
+    let path = path.display();
+    Ok(match format {
+        "flist" | "flist-plus" | "verilator" => format!("-f {}", path),
+        "vcs" => format!("bash \"{}\"", path),
+        "yosys" => format!("script {}", path),
+        // The scripts `return 1` on compilation errors, which ends `source`.
+        "vsim" | "riviera" | "synopsys" | "formality" | "genus" => {
+            format!("if {{[source \"{}\"] == 1}} {{return 1}}", path)
+        }
+        _ => {
+            return Err(Error::new(format!(
+                "The `{}` format cannot be split into one script per package.",
+                format
+            )))
+        }
+    })
+}
+
+/// Group the sources by package, keeping the dependency order.
+fn group_by_package<'a>(
+    root: &'a str,
+    srcs: Vec<SourceGroup<'a>>,
+) -> IndexMap<&'a str, Vec<SourceGroup<'a>>> {
+    let mut packages: IndexMap<&str, Vec<SourceGroup>> = IndexMap::new();
+    for src in srcs {
+        let package = src.package.unwrap_or(root);
+        packages.entry(package).or_default().push(src);
+    }
+    packages
+}
+
+/// The prefix of comment lines selected with `--comment-style`, if any.
+fn comment_prefix(matches: &ArgMatches) -> Option<&str> {
+    matches
+        .get_one::<String>("comment-style")
+        .map(String::as_str)
+        .filter(|&style| style != "none")
+}
+
+/// The content of the manifest's `script_prelude` for the selected format.
+fn script_prelude(sess: &Session, matches: &ArgMatches) -> Result<Option<String>> {
+    let format = matches.get_one::<String>("format").unwrap();
+    let path = match sess.manifest.script_prelude.get(format) {
+        Some(path) => path,
+        None => return Ok(None),
+    };
+    let mut prelude = fs::read_to_string(path).map_err(|cause| {
+        Error::chain(
+            format!("Failed to read script prelude {:?} for `{}`.", path, format),
+            cause,
+        )
+    })?;
+    if !prelude.ends_with('\n') {
+        prelude.push('\n');
+    }
+    Ok(Some(prelude))
+}
+
+fn render_template(
+    sess: &Session,
+    template: &str,
+    matches: &ArgMatches,
+    targets: TargetSet,
+    srcs: Vec<SourceGroup>,
+) -> Result<String> {
     let mut tera_obj = Tera::default();
     let mut tera_context = Context::new();
     tera_context.insert("HEADER_AUTOGEN", HEADER_AUTOGEN);
+    tera_context.insert("comment_prefix", &comment_prefix(matches));
     tera_context.insert("root", sess.root);
     // tera_context.insert("srcs", &srcs);
     tera_context.insert("abort_on_error", &!matches.get_flag("no-abort-on-error"));
+    tera_context.insert("abort_file", &matches.get_one::<PathBuf>("abort-file"));
+    tera_context.insert("prelude", &script_prelude(sess, matches)?);
+
+    // Paths below the root are relativized by the templates of most formats,
+    // and are only rewritten if they are emitted as they are.
+    let rules = replace_path_rules(matches)?;
+    let relativized = match matches.get_one::<String>("format").unwrap().as_str() {
+        "flist" | "flist-plus" => relative_paths(matches),
+        "precision" | "verilator" | "yosys" | "template" | "template_json" => false,
+        _ => true,
+    };
+    let rewrite = |path: &Path| {
+        if relativized && path.starts_with(sess.root) {
+            path.to_path_buf()
+        } else {
+            replace_path(path, &rules)
+        }
+    };
 
+    let cli_defines = defines_from_matches(matches)?;
     let mut target_defines: IndexMap<String, Option<String>> = IndexMap::new();
-    target_defines.extend(
-        targets
-            .iter()
-            .map(|t| (format!("TARGET_{}", t.to_uppercase()), None)),
-    );
-    target_defines.sort_keys();
+    if !matches.get_flag("no-default-defines") {
+        target_defines.extend(
+            targets
+                .iter()
+                .map(|t| (format!("TARGET_{}", t.to_uppercase()), None)),
+        );
+        target_defines.sort_keys();
+    }
 
     let mut global_defines = target_defines.clone();
-    add_defines_from_matches(&mut global_defines, matches);
+    global_defines.extend(cli_defines.clone());
+    let global_defines = prefix_defines(undefine(global_defines, matches), matches);
     tera_context.insert("global_defines", &global_defines);
 
-    let mut all_defines = IndexMap::new();
+    let mut all_defines: IndexMap<String, Option<String>> = IndexMap::new();
     let mut all_incdirs = vec![];
     let mut all_files = vec![];
     let mut all_verilog = vec![];
     let mut all_vhdl = vec![];
+    let mut all_headers = IndexSet::new();
     for src in &srcs {
         all_defines.extend(
             src.defines
@@ -518,9 +1403,11 @@ fn emit_template(
         );
         all_incdirs.append(&mut src.clone().get_incdirs());
         all_files.append(&mut src.files.clone());
+        all_headers.extend(src.headers.iter().map(|p| p.to_path_buf()));
     }
-    all_defines.extend(target_defines.clone());
-    add_defines_from_matches(&mut all_defines, matches);
+    add_target_defines(&mut all_defines, &target_defines);
+    all_defines.extend(cli_defines.clone());
+    let all_defines = prefix_defines(undefine(all_defines, matches), matches);
     let all_defines = if (!matches.get_flag("only-includes") && !matches.get_flag("only-sources"))
         || matches.get_flag("only-defines")
     {
@@ -535,7 +1422,7 @@ fn emit_template(
         && !matches.get_flag("only-sources"))
         || matches.get_flag("only-includes")
     {
-        all_incdirs.into_iter().map(|p| p.to_path_buf()).collect()
+        all_incdirs.into_iter().map(rewrite).collect()
     } else {
         IndexSet::new()
     };
@@ -554,10 +1441,76 @@ fn emit_template(
     } else {
         IndexSet::new()
     };
-    tera_context.insert("all_files", &all_files);
+    tera_context.insert(
+        "all_files",
+        &all_files
+            .iter()
+            .map(|p| rewrite(p))
+            .collect::<IndexSet<_>>(),
+    );
+
+    // Keep the files of `all_files` grouped by source group, together with the
+    // library of each group.
+    let mut seen_files = IndexSet::new();
+    let (file_groups, file_group_libraries): (Vec<Vec<PathBuf>>, Vec<Option<Option<&str>>>) = srcs
+        .iter()
+        .map(|src| {
+            let files = src
+                .files
+                .iter()
+                .filter_map(|file| match file {
+                    SourceFile::File(p) if all_files.contains(*p) && seen_files.insert(*p) => {
+                        Some(rewrite(p))
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            // The library only applies to VHDL files.
+            let has_vhdl = files.iter().any(|f: &PathBuf| {
+                matches!(
+                    f.extension().and_then(std::ffi::OsStr::to_str),
+                    Some("vhd") | Some("vhdl")
+                )
+            });
+            (files, has_vhdl.then_some(src.library))
+        })
+        .filter(|(files, _)| !files.is_empty())
+        .unzip();
+    // Once any library is set, annotate all groups with VHDL files, such that
+    // the files of the default `work` library can be told apart.
+    let file_group_libraries: Vec<Option<&str>> = if file_group_libraries
+        .iter()
+        .any(|l| matches!(l, Some(Some(_))))
+    {
+        file_group_libraries
+            .into_iter()
+            .map(|l| l.map(|l| l.unwrap_or("work")))
+            .collect()
+    } else {
+        vec![None; file_group_libraries.len()]
+    };
+    tera_context.insert("file_groups", &file_groups);
+    tera_context.insert("file_group_libraries", &file_group_libraries);
+    tera_context.insert(
+        "group_separator",
+        &matches.get_one::<String>("group-separator"),
+    );
+    let all_headers: IndexSet<PathBuf> =
+        if !matches.get_flag("only-defines") && !matches.get_flag("only-includes") {
+            all_headers.iter().map(|p| rewrite(p)).collect()
+        } else {
+            IndexSet::new()
+        };
+    tera_context.insert("all_headers", &all_headers);
 
     let mut split_srcs = vec![];
+    let mut last_package = None;
     for src in srcs {
+        // Only annotate the first group of each package.
+        let package = src.package;
+        let mut metadata = (matches.get_flag("include-manifest-metadata")
+            && last_package != Some(package))
+        .then(|| package_metadata(sess, package));
         separate_files_in_group(
             src,
             |f| match f {
@@ -570,6 +1523,7 @@ fn emit_template(
             },
             |src, ty, files| {
                 split_srcs.push(TplSrcStruct {
+                    metadata: metadata.take(),
                     defines: {
                         let mut local_defines = IndexMap::new();
                         local_defines.extend(
@@ -577,9 +1531,11 @@ fn emit_template(
                                 .iter()
                                 .map(|(k, &v)| (k.to_string(), v.map(String::from))),
                         );
-                        local_defines.extend(target_defines.clone());
-                        add_defines_from_matches(&mut local_defines, matches);
-                        local_defines.into_iter().collect()
+                        add_target_defines(&mut local_defines, &target_defines);
+                        local_defines.extend(cli_defines.clone());
+                        prefix_defines(undefine(local_defines, matches), matches)
+                            .into_iter()
+                            .collect()
                     },
                     incdirs: {
                         let mut incdirs = src
@@ -589,12 +1545,13 @@ fn emit_template(
                             .map(|p| p.to_path_buf())
                             .collect::<IndexSet<_>>();
                         incdirs.sort();
-                        incdirs
+                        incdirs.iter().map(|p| rewrite(p)).collect()
                     },
+                    headers: src.headers.iter().map(|p| rewrite(p)).collect(),
                     files: files
                         .iter()
                         .map(|f| match f {
-                            SourceFile::File(p) => p.to_path_buf(),
+                            SourceFile::File(p) => rewrite(p),
                             SourceFile::Group(_) => unreachable!(),
                         })
                         .collect(),
@@ -602,9 +1559,16 @@ fn emit_template(
                         SourceType::Verilog => "verilog".to_string(),
                         SourceType::Vhdl => "vhdl".to_string(),
                     },
+                    library: match ty {
+                        SourceType::Verilog => None,
+                        SourceType::Vhdl => src.library.map(String::from),
+                    },
                 });
             },
         );
+        if metadata.is_none() {
+            last_package = Some(package);
+        }
     }
     for src in &split_srcs {
         match src.file_type.as_str() {
@@ -622,8 +1586,45 @@ fn emit_template(
     } else {
         vec![]
     };
+    let split_srcs = match matches.get_one::<u64>("max-files-per-group") {
+        Some(&max) => split_srcs
+            .into_iter()
+            .flat_map(|src| {
+                let files: Vec<_> = src.files.iter().cloned().collect();
+                files
+                    .chunks(max as usize)
+                    .enumerate()
+                    .map(|(i, chunk)| TplSrcStruct {
+                        metadata: if i == 0 { src.metadata.clone() } else { None },
+                        defines: src.defines.clone(),
+                        incdirs: src.incdirs.clone(),
+                        headers: src.headers.clone(),
+                        files: chunk.iter().cloned().collect(),
+                        file_type: src.file_type.clone(),
+                        library: src.library.clone(),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect(),
+        None => split_srcs,
+    };
+    if let Some(command) = matches.get_one::<String>("chunk-command") {
+        return Ok(chunk_commands(command, &split_srcs));
+    }
     tera_context.insert("srcs", &split_srcs);
 
+    // The libraries the VHDL files are compiled into, `work` by default.
+    let vhdl_libraries: IndexSet<&str> = split_srcs
+        .iter()
+        .filter(|src| src.file_type == "vhdl")
+        .map(|src| src.library.as_deref().unwrap_or("work"))
+        .collect();
+    tera_context.insert("vhdl_libraries", &vhdl_libraries);
+    tera_context.insert(
+        "vhdl_work_dir",
+        &matches.get_one::<PathBuf>("vhdl-relative-work"),
+    );
+
     let all_verilog: IndexSet<PathBuf> =
         if !matches.get_flag("only-defines") && !matches.get_flag("only-includes") {
             all_verilog.into_iter().collect()
@@ -651,10 +1652,32 @@ fn emit_template(
         [].to_vec()
     };
     tera_context.insert("vcom_args", &vcom_args);
+    let verilator_args: Vec<String> =
+        if let Some(args) = matches.get_many::<String>("verilator-arg") {
+            args.map(Into::into).collect()
+        } else {
+            [].to_vec()
+        };
+    tera_context.insert("verilator_args", &verilator_args);
+
+    // Library directories are taken relative to the current directory, such
+    // that the ones inside the package are written relative to its root.
+    let cwd = std::env::current_dir()
+        .map_err(|cause| Error::chain("Failed to determine current directory.", cause))?;
+    let libdirs: Vec<PathBuf> = matches
+        .get_many::<PathBuf>("libdir")
+        .map(|dirs| dirs.map(|dir| cwd.join(dir)).collect())
+        .unwrap_or_default();
+    tera_context.insert("libdirs", &libdirs);
+    let libexts: Vec<&String> = matches
+        .get_many::<String>("libext")
+        .map(Iterator::collect)
+        .unwrap_or_default();
+    tera_context.insert("libexts", &libexts);
 
     tera_context.insert("vlogan_bin", &matches.get_one::<String>("vlogan-bin"));
     tera_context.insert("vhdlan_bin", &matches.get_one::<String>("vhdlan-bin"));
-    tera_context.insert("relativize_path", &matches.get_flag("relative-path"));
+    tera_context.insert("relativize_path", &relative_paths(matches));
     tera_context.insert(
         "compilation_mode",
         &matches.get_one::<String>("compilation_mode"),
@@ -669,24 +1692,150 @@ fn emit_template(
     tera_context.insert("vivado_filesets", &vivado_filesets);
 
     if template == "json" {
-        println!("{:#}", tera_context.into_json());
-        return Ok(());
+        return Ok(normalize_line_endings(
+            &format!("{:#}\n", tera_context.into_json()),
+            matches,
+        ));
     }
 
-    print!(
-        "{}",
-        tera_obj
-            .render_str(template, &tera_context)
-            .map_err(|e| { Error::chain("Failed to render template.", e) })?
-    );
+    tera_obj
+        .render_str(template, &tera_context)
+        .map(|script| normalize_line_endings(&script, matches))
+        .map_err(|e| Error::chain("Failed to render template.", e))
+}
+
+/// Convert all line endings to the ones selected with
+/// `--normalize-line-endings`.
+fn normalize_line_endings(text: &str, matches: &ArgMatches) -> String {
+    let text = text.replace("\r\n", "\n");
+    match matches
+        .get_one::<String>("normalize-line-endings")
+        .unwrap()
+        .as_str()
+    {
+        "crlf" => text.replace('\n', "\r\n"),
+        _ => text,
+    }
+}
+
+/// Emit a command for every chunk of files.
+///
+/// The `{files}`, `{incdirs}`, and `{defines}` placeholders are replaced by the
+/// space-separated files, include directories, and `NAME[=VALUE]` defines of
+/// the chunk.
+fn chunk_commands(command: &str, srcs: &[TplSrcStruct]) -> String {
+    fn join<T: AsRef<std::path::Path>>(paths: &IndexSet<T>) -> String {
+        paths
+            .iter()
+            .map(|p| p.as_ref().display().to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+    let mut out = String::new();
+    for src in srcs {
+        let defines = src
+            .defines
+            .iter()
+            .map(|(name, value)| match value {
+                Some(value) => format!("{}={}", name.to_uppercase(), value),
+                None => name.to_uppercase(),
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        out.push_str(
+            &command
+                .replace("{files}", &join(&src.files))
+                .replace("{incdirs}", &join(&src.incdirs))
+                .replace("{defines}", &defines),
+        );
+        out.push('\n');
+    }
+    out
+}
 
+/// Write a JSON map from the absolute paths of the files, include directories,
+/// and headers to keys of the form `<package>/<path>`, where the path is
+/// relative to the package. The keys do not depend on the checkout location,
+/// e.g. to key lint waivers on them. Paths outside of their package are left
+/// out.
+fn write_path_map(sess: &Session, srcs: &[SourceGroup], path: &Path) -> Result<()> {
+    let io = SessionIo::new(sess);
+    let mut roots: IndexMap<String, Option<PathBuf>> = IndexMap::new();
+    let mut map: IndexMap<String, String> = IndexMap::new();
+    let mut insert = |package: &str, file: &Path| {
+        let root = roots.entry(package.to_string()).or_insert_with(|| {
+            if package == sess.manifest.package.name {
+                Some(sess.root.to_path_buf())
+            } else {
+                let id = sess.dependency_with_name(package).ok()?;
+                Some(io.get_package_path(id))
+            }
+        });
+        let rel = match root.as_ref().and_then(|root| file.strip_prefix(root).ok()) {
+            Some(rel) => rel,
+            None => return,
+        };
+        let key = std::iter::once(package.to_string())
+            .chain(rel.iter().map(|c| c.to_string_lossy().into_owned()))
+            .collect::<Vec<_>>()
+            .join("/");
+        map.entry(file.display().to_string()).or_insert(key);
+    };
+    for src in srcs {
+        let package = src.package.unwrap_or(&sess.manifest.package.name);
+        for file in &src.files {
+            if let SourceFile::File(file) = file {
+                insert(package, file);
+            }
+        }
+        for dir in src.include_dirs.iter().chain(&src.headers) {
+            insert(package, dir);
+        }
+        for (dep, dirs) in &src.export_incdirs {
+            for dir in dirs {
+                insert(dep, dir);
+            }
+        }
+    }
+
+    let file = fs::File::create(path)
+        .map_err(|cause| Error::chain(format!("Failed to create {:?}.", path), cause))?;
+    serde_json::to_writer_pretty(file, &map)
+        .map_err(|cause| Error::chain(format!("Failed to write {:?}.", path), cause))?;
     Ok(())
 }
 
+/// Describe the package of a source group for a metadata comment.
+fn package_metadata(sess: &Session, package: Option<&str>) -> String {
+    let name = package.unwrap_or(&sess.manifest.package.name);
+    if name == sess.manifest.package.name {
+        return format!("Package `{}` at {:?}", name, sess.root);
+    }
+    let dep = match sess.dependency_with_name(name) {
+        Ok(id) => sess.dependency(id),
+        Err(_) => return format!("Package `{}`", name),
+    };
+    let mut metadata = format!("Package `{}`", name);
+    if let Some(ref version) = dep.version {
+        metadata.push_str(&format!(" version {}", version));
+    }
+    match dep.revision {
+        Some(ref rev) if dep.version.as_ref().map(|v| v.to_string()) != Some(rev.clone()) => {
+            metadata.push_str(&format!(" revision {}", rev))
+        }
+        _ => (),
+    }
+    metadata.push_str(&format!(" from {}", dep.source));
+    metadata
+}
+
 #[derive(Debug, Serialize)]
 struct TplSrcStruct {
+    metadata: Option<String>,
     defines: IndexSet<(String, Option<String>)>,
     incdirs: IndexSet<PathBuf>,
+    headers: IndexSet<PathBuf>,
     files: IndexSet<PathBuf>,
     file_type: String,
+    library: Option<String>,
 }
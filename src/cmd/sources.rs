@@ -4,16 +4,20 @@
 //! The `sources` subcommand.
 
 use std;
+use std::path::{Path, PathBuf};
 
+use clap::builder::PossibleValue;
 use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
-use indexmap::IndexSet;
+use indexmap::{IndexMap, IndexSet};
+use serde::Serialize;
 use serde_json;
 use tokio::runtime::Runtime;
 
 use crate::error::*;
 use crate::sess::{Session, SessionIo};
-use crate::src::SourceGroup;
+use crate::src::{SourceFile, SourceGroup};
 use crate::target::{TargetSet, TargetSpec};
+use crate::util::read_list_file;
 
 /// Assemble the `sources` subcommand.
 pub fn new() -> Command {
@@ -28,6 +32,13 @@ pub fn new() -> Command {
                 .action(ArgAction::Append)
                 .value_parser(value_parser!(String)),
         )
+        .arg(
+            Arg::new("target-file")
+                .long("target-file")
+                .help("Read additional targets from a file, one per line")
+                .num_args(1)
+                .value_parser(value_parser!(PathBuf)),
+        )
         .arg(
             Arg::new("flatten")
                 .short('f')
@@ -36,6 +47,30 @@ pub fn new() -> Command {
                 .num_args(0)
                 .action(ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("per-file")
+                .long("per-file")
+                .help("Flatten to one entry per file with its effective include directories and defines")
+                .num_args(0)
+                .action(ArgAction::SetTrue)
+                .requires("flatten"),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .help("Output format, `json` emits a normalized tree of the resolved source groups")
+                .num_args(1)
+                .default_value("pretty")
+                .value_parser([PossibleValue::new("pretty"), PossibleValue::new("json")])
+                .conflicts_with_all(["raw", "per-file"]),
+        )
+        .arg(
+            Arg::new("relative-path")
+                .long("relative-path")
+                .help("Emit paths relative to the package root (json format only)")
+                .num_args(0)
+                .action(ArgAction::SetTrue),
+        )
         .arg(
             Arg::new("package")
                 .short('p')
@@ -82,6 +117,22 @@ where
         .collect()
 }
 
+/// Collect the targets given with `--target` and read from `--target-file`.
+fn get_targets(matches: &ArgMatches) -> Result<Vec<String>> {
+    let mut targets: Vec<String> = matches
+        .get_many::<String>("target")
+        .into_iter()
+        .flatten()
+        .cloned()
+        .collect();
+    if let Some(path) = matches.get_one::<PathBuf>("target-file") {
+        targets.extend(read_list_file(path).map_err(|cause| {
+            Error::chain(format!("Cannot read target file {:?}.", path), cause)
+        })?);
+    }
+    Ok(targets)
+}
+
 /// Execute the `sources` subcommand.
 pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
     let rt = Runtime::new()?;
@@ -96,10 +147,7 @@ pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
     }
 
     // Filter the sources by target.
-    let targets = matches
-        .get_many::<String>("target")
-        .map(TargetSet::new)
-        .unwrap_or_else(TargetSet::empty);
+    let targets = TargetSet::new(get_targets(matches)?);
     srcs = srcs
         .filter_targets(&targets)
         .unwrap_or_else(|| SourceGroup {
@@ -109,10 +157,14 @@ pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
             include_dirs: Default::default(),
             export_incdirs: Default::default(),
             defines: Default::default(),
+            target_defines: Default::default(),
+            headers: Default::default(),
+            library: None,
             files: Default::default(),
             dependencies: Default::default(),
             version: None,
         });
+    srcs.warn_conflicting_defines();
 
     // Filter the sources by specified packages.
     let packages = &srcs.get_package_list(
@@ -126,6 +178,7 @@ pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
             .map(get_package_strings)
             .unwrap_or_default(),
         matches.get_flag("no_deps"),
+        false,
     );
 
     if matches.contains_id("package")
@@ -141,6 +194,9 @@ pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
                 include_dirs: Default::default(),
                 export_incdirs: Default::default(),
                 defines: Default::default(),
+                target_defines: Default::default(),
+                headers: Default::default(),
+                library: None,
                 files: Default::default(),
                 dependencies: Default::default(),
                 version: None,
@@ -150,7 +206,24 @@ pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
     let result = {
         let stdout = std::io::stdout();
         let handle = stdout.lock();
-        if matches.get_flag("flatten") {
+        let json = matches.get_one::<String>("format").unwrap() == "json";
+        let root = if matches.get_flag("relative-path") {
+            Some(sess.root)
+        } else {
+            None
+        };
+        if json && matches.get_flag("flatten") {
+            let nodes: Vec<_> = srcs
+                .flatten()
+                .iter()
+                .map(|group| SourceNode::new(group, root))
+                .collect();
+            serde_json::to_writer_pretty(handle, &nodes)
+        } else if json {
+            serde_json::to_writer_pretty(handle, &SourceNode::new(&srcs, root))
+        } else if matches.get_flag("per-file") {
+            serde_json::to_writer_pretty(handle, &flatten_files(srcs))
+        } else if matches.get_flag("flatten") {
             let srcs = srcs.flatten();
             serde_json::to_writer_pretty(handle, &srcs)
         } else {
@@ -160,3 +233,110 @@ pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
     println!();
     result.map_err(|cause| Error::chain("Failed to serialize source file manifest.", cause))
 }
+
+/// A single source file with its effective configuration.
+#[derive(Serialize)]
+struct FlatFile<'ctx> {
+    file: &'ctx Path,
+    package: Option<&'ctx str>,
+    include_dirs: Vec<&'ctx Path>,
+    defines: IndexMap<&'ctx str, Option<&'ctx str>>,
+}
+
+/// Flatten the sources into a list of files in compile order.
+fn flatten_files(srcs: SourceGroup) -> Vec<FlatFile> {
+    srcs.flatten()
+        .into_iter()
+        .flat_map(|group| {
+            let package = group.package;
+            let defines = group.defines.clone();
+            let files = group.files.clone();
+            let include_dirs = group.get_incdirs();
+            files.into_iter().filter_map(move |file| match file {
+                SourceFile::File(path) => Some(FlatFile {
+                    file: path,
+                    package,
+                    include_dirs: include_dirs.clone(),
+                    defines: defines.clone(),
+                }),
+                SourceFile::Group(_) => None,
+            })
+        })
+        .collect()
+}
+
+/// A node of the normalized source tree emitted by `--format json`.
+///
+/// A node either lists files, or nested groups. Files that are interleaved
+/// with subgroups are wrapped in a group of their own to retain their order.
+#[derive(Serialize)]
+struct SourceNode<'ctx> {
+    package: Option<&'ctx str>,
+    target: String,
+    include_dirs: Vec<String>,
+    defines: IndexMap<&'ctx str, Option<&'ctx str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    files: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    groups: Option<Vec<SourceNode<'ctx>>>,
+}
+
+impl<'ctx> SourceNode<'ctx> {
+    fn new(group: &SourceGroup<'ctx>, root: Option<&Path>) -> Self {
+        let path = |p: &Path| match root.and_then(|root| p.strip_prefix(root).ok()) {
+            Some(rel) => rel.display().to_string(),
+            None => p.display().to_string(),
+        };
+        let leaf = |files: Vec<String>| SourceNode {
+            package: group.package,
+            target: TargetSpec::Wildcard.to_string(),
+            include_dirs: vec![],
+            defines: IndexMap::new(),
+            files: Some(files),
+            groups: None,
+        };
+        let mut node = SourceNode {
+            package: group.package,
+            target: group.target.to_string(),
+            include_dirs: group.clone().get_incdirs().into_iter().map(&path).collect(),
+            defines: group.defines.clone(),
+            files: None,
+            groups: None,
+        };
+        if group
+            .files
+            .iter()
+            .all(|file| matches!(file, SourceFile::File(_)))
+        {
+            node.files = Some(
+                group
+                    .files
+                    .iter()
+                    .filter_map(|file| match file {
+                        SourceFile::File(p) => Some(path(p)),
+                        SourceFile::Group(_) => None,
+                    })
+                    .collect(),
+            );
+            return node;
+        }
+        let mut groups = vec![];
+        let mut files = vec![];
+        for file in &group.files {
+            match file {
+                SourceFile::File(p) => files.push(path(p)),
+                SourceFile::Group(sub) => {
+                    if !files.is_empty() {
+                        groups.push(leaf(std::mem::take(&mut files)));
+                    }
+                    groups.push(SourceNode::new(sub, root));
+                }
+            }
+        }
+        if !files.is_empty() {
+            groups.push(leaf(files));
+        }
+        node.groups = Some(groups);
+        node
+    }
+}
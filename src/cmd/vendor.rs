@@ -50,6 +50,10 @@ pub fn new() -> Command {
                     .help("Return error code 1 when a diff is encountered. (Optional) override the error message by providing a value."),
             )
         )
+        .subcommand(Command::new("status")
+            .about("Summarize which external dependencies differ from the upstream tree with patches applied.")
+            .long_about("Summarize which external dependencies differ from the upstream tree with patches applied. Returns error code 1 if any dependency differs or its patches fail to apply.")
+        )
         .subcommand(Command::new("init")
             .about("(Re-)initialize the external dependencies.")
             .long_about("(Re-)initialize the external dependencies. Copies the upstream files into the target directories and applies existing patches.")
@@ -61,6 +65,23 @@ pub fn new() -> Command {
                     .help("Do not apply patches when initializing dependencies"),
             )
         )
+        .subcommand(Command::new("update")
+            .about("Update the upstream revision of an external dependency.")
+            .long_about("Update the upstream revision of an external dependency. Sets the new revision in the manifest, copies the upstream files at that revision into the target directory, and reapplies existing patches, reporting any that no longer apply cleanly.")
+            .arg(
+                Arg::new("name")
+                    .required(true)
+                    .num_args(1)
+                    .help("Name of the external dependency to update"),
+            )
+            .arg(
+                Arg::new("rev")
+                    .long("rev")
+                    .required(true)
+                    .num_args(1)
+                    .help("The new upstream revision"),
+            )
+        )
         .subcommand(Command::new("patch")
             .about("Generate a patch file from staged local changes")
             .arg(
@@ -84,16 +105,25 @@ pub fn new() -> Command {
 /// Execute the `vendor` subcommand.
 pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
     let rt = Runtime::new()?;
+    let mut num_dirty = 0;
 
-    for vendor_package in &sess.manifest.vendor_package {
+    // `update` only handles the named package, pinned to the new revision.
+    let vendor_packages = match matches.subcommand() {
+        Some(("update", matches)) => vec![updated_package(sess, matches)?],
+        _ => sess.manifest.vendor_package.clone(),
+    };
+
+    for vendor_package in &vendor_packages {
         // Clone upstream into a temporary directory (or make use of .bender/db?)
         let dep_src = DependencySource::from(&vendor_package.upstream);
         let tmp_dir = TempDir::new()?;
         let tmp_path = tmp_dir.path();
+        let mut resolved_rev = None;
         let dep_path = match dep_src {
             DependencySource::Path(path) => path,
             DependencySource::Git(ref url) => {
-                let git = Git::new(tmp_path, &sess.config.git);
+                let git = Git::new(tmp_path, &sess.config.git)
+                    .with_config(sess.config.git_config_for(url));
                 rt.block_on(async {
                     stageln!("Cloning", "{} ({})", vendor_package.name, url);
                     git.spawn_with(|c| c.arg("clone").arg(url).arg("."))
@@ -112,27 +142,53 @@ pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
                         _ => Err(Error::new("Please ensure your vendor reference is a commit hash to avoid upstream changes impacting your checkout")),
                     }?;
                     git.spawn_with(|c| c.arg("checkout").arg(rev_hash)).await?;
-                    if *rev_hash != git.spawn_with(|c| c.arg("rev-parse").arg("--verify").arg(format!("{}^{{commit}}", rev_hash))).await?.trim_end_matches('\n') {
-                        Err(Error::new("Please ensure your vendor reference is a commit hash to avoid upstream changes impacting your checkout"))
-                    } else {
+                    let commit = git.spawn_with(|c| c.arg("rev-parse").arg("--verify").arg(format!("{}^{{commit}}", rev_hash))).await?.trim_end_matches('\n').to_string();
+                    if *rev_hash == commit {
+                        Ok(())
+                    } else if vendor_package.allow_non_commit {
+                        // Record the commit the reference currently resolves to.
+                        resolved_rev = Some((url.clone(), rev_hash.clone(), commit));
                         Ok(())
+                    } else {
+                        Err(Error::new("Please ensure your vendor reference is a commit hash to avoid upstream changes impacting your checkout"))
                     }
                 })?;
 
                 tmp_path.to_path_buf()
             }
-            DependencySource::Registry => unimplemented!(),
+            DependencySource::Registry => {
+                return Err(Error::new(format!(
+                    "Cannot vendor package `{}` from a registry. Use a `git` or `path` upstream instead.",
+                    vendor_package.name
+                )))
+            }
         };
 
-        // Extract patch dirs of links
+        // Extract patch dirs of links, expanding glob patterns in `from`
         let mut patch_links: Vec<PatchLink> = Vec::new();
         for link in vendor_package.mapping.clone() {
-            patch_links.push(PatchLink {
-                patch_dir: link.patch_dir,
-                from_prefix: link.from,
-                to_prefix: link.to,
-                exclude: vec![],
-            })
+            let expanded = expand_link(&link.from, &link.to, &dep_path)?;
+            let is_pattern = expanded.len() != 1 || expanded[0].0 != link.from;
+            for (from, to) in expanded {
+                // Identical targets from overlapping patterns are only handled once
+                if patch_links.iter().any(|l| l.to_prefix == to) {
+                    continue;
+                }
+                // Directories matched by a pattern keep their patches apart, as
+                // they are applied relative to each directory.
+                let patch_dir = match link.patch_dir {
+                    Some(ref dir) if is_pattern && dep_path.join(&from).is_dir() => {
+                        Some(dir.join(to.strip_prefix(&link.to).unwrap()))
+                    }
+                    _ => link.patch_dir.clone(),
+                };
+                patch_links.push(PatchLink {
+                    patch_dir,
+                    from_prefix: from,
+                    to_prefix: to,
+                    exclude: vec![],
+                })
+            }
         }
 
         // If links do not specify patch dirs, use package-wide patch dir
@@ -213,7 +269,43 @@ pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
                 })
             }
 
-            Some(("init", matches)) => {
+            Some(("status", _)) => {
+                // Apply patches
+                let patched = sorted_links.clone().into_iter().try_for_each(|patch_link| {
+                    apply_patches(&rt, git, vendor_package.name.clone(), patch_link).map(|_| ())
+                });
+
+                if patched.is_err() {
+                    println!("{}: patches failed to apply", vendor_package.name);
+                    num_dirty += 1;
+                } else {
+                    // Stage applied patches to clean working tree
+                    rt.block_on(git.add_all())?;
+
+                    // Count the changed files for each link
+                    let mut num_files = 0;
+                    for patch_link in sorted_links {
+                        let get_diff = diff(&rt, git, vendor_package, patch_link, dep_path.clone())
+                            .map_err(|cause| Error::chain("Failed to get diff.", cause))?;
+                        num_files += get_diff
+                            .lines()
+                            .filter(|line| line.starts_with("diff --git "))
+                            .count();
+                    }
+                    if num_files == 0 {
+                        println!("{}: up-to-date", vendor_package.name);
+                    } else {
+                        println!("{}: {} files changed", vendor_package.name, num_files);
+                        num_dirty += 1;
+                    }
+                }
+                Ok(())
+            }
+
+            Some((subcommand @ ("init" | "update"), matches)) => {
+                let updating = subcommand == "update";
+                let no_patch = updating || matches.get_flag("no_patch");
+                let mut num_failed = 0;
                 sorted_links.into_iter().rev().try_for_each(|patch_link| {
                     stageln!("Copying", "{} files from upstream", vendor_package.name);
                     // Remove existing directories before importing them again
@@ -232,6 +324,17 @@ pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
                         })?;
                     }
 
+                    // When following upstream, report patches that no longer apply
+                    // instead of aborting, such that they can be regenerated.
+                    if updating {
+                        if let Err(cause) =
+                            apply_patches(&rt, git, vendor_package.name.clone(), patch_link.clone())
+                        {
+                            warnln!("{}", cause);
+                            num_failed += 1;
+                        }
+                    }
+
                     // init
                     init(
                         &rt,
@@ -239,9 +342,35 @@ pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
                         vendor_package,
                         patch_link,
                         dep_path.clone(),
-                        matches,
+                        no_patch,
                     )
-                })
+                })?;
+
+                // Note the commit a non-commit upstream reference was resolved to
+                if let Some((url, rev, commit)) = &resolved_rev {
+                    let rev_path = vendor_package.target_dir.join(".bender_vendor_rev");
+                    std::fs::write(
+                        &rev_path,
+                        format!(
+                            "# Vendored from {} at `{}`, resolved to commit {}\n",
+                            url, rev, commit
+                        ),
+                    )
+                    .map_err(|cause| {
+                        Error::chain(format!("Failed to write {:?}.", rev_path), cause)
+                    })?;
+                }
+
+                if updating {
+                    update_manifest_rev(sess, vendor_package)?;
+                    if num_failed > 0 {
+                        return Err(Error::new(format!(
+                            "Patches of {} failed to apply cleanly to the new upstream revision, please fix and regenerate them (e.g. using bender vendor patch).",
+                            vendor_package.name
+                        )));
+                    }
+                }
+                Ok(())
             }
 
             Some(("patch", matches)) => {
@@ -289,6 +418,13 @@ pub fn run(sess: &Session, matches: &ArgMatches) -> Result<()> {
         }?;
     }
 
+    if num_dirty > 0 {
+        return Err(Error::new(format!(
+            "{} external dependencies differ from upstream, please patch (e.g. using bender vendor patch).",
+            num_dirty
+        )));
+    }
+
     Ok(())
 }
 
@@ -299,7 +435,7 @@ pub fn init(
     vendor_package: &config::VendorPackage,
     patch_link: PatchLink,
     dep_path: impl AsRef<Path>,
-    matches: &ArgMatches,
+    no_patch: bool,
 ) -> Result<()> {
     // import necessary files from upstream, apply patches
     let dep_path = dep_path.as_ref();
@@ -317,13 +453,15 @@ pub fn init(
         )
     })?;
 
-    if !matches.get_flag("no_patch") {
+    if !no_patch {
         apply_patches(rt, git, vendor_package.name.clone(), patch_link.clone())?;
     }
 
     // Check if includes exist
     for path in vendor_package.include_from_upstream.clone() {
-        if !PathBuf::from(extend_paths(&[path.clone()], dep_path, true)?[0].clone()).exists() {
+        if !PathBuf::from(extend_paths(std::slice::from_ref(&path), dep_path, true)?[0].clone())
+            .exists()
+        {
             warnln!("{} not found in upstream, continuing.", path);
         }
     }
@@ -365,6 +503,90 @@ pub fn init(
     Ok(())
 }
 
+/// Look up the external dependency to update and pin it to the new revision
+fn updated_package(sess: &Session, matches: &ArgMatches) -> Result<config::VendorPackage> {
+    let name = matches.get_one::<String>("name").unwrap();
+    let rev = matches.get_one::<String>("rev").unwrap();
+    let vendor_package = sess
+        .manifest
+        .vendor_package
+        .iter()
+        .find(|vp| &vp.name == name)
+        .ok_or_else(|| Error::new(format!("Unknown external dependency `{}`.", name)))?;
+    match vendor_package.upstream {
+        config::Dependency::GitRevision(ref url, _) => Ok(config::VendorPackage {
+            upstream: config::Dependency::GitRevision(url.clone(), rev.clone()),
+            ..vendor_package.clone()
+        }),
+        _ => Err(Error::new(format!(
+            "External dependency `{}` does not refer to a git revision upstream.",
+            name
+        ))),
+    }
+}
+
+/// Replace the upstream revision of an external dependency in the manifest
+fn update_manifest_rev(sess: &Session, vendor_package: &config::VendorPackage) -> Result<()> {
+    let new_rev = match vendor_package.upstream {
+        config::Dependency::GitRevision(_, ref rev) => rev,
+        _ => unreachable!(),
+    };
+    let old_rev = match sess
+        .manifest
+        .vendor_package
+        .iter()
+        .find(|vp| vp.name == vendor_package.name)
+        .map(|vp| &vp.upstream)
+    {
+        Some(config::Dependency::GitRevision(_, rev)) => rev,
+        _ => unreachable!(),
+    };
+
+    let manifest_path = sess.manifest_path;
+    let content = std::fs::read_to_string(manifest_path).map_err(|cause| {
+        Error::chain(format!("Cannot read manifest {:?}.", manifest_path), cause)
+    })?;
+    let mut lines: Vec<String> = content.lines().map(String::from).collect();
+
+    // The revision is the first occurrence after the package's name.
+    let is_name = |line: &str| {
+        line.trim_start()
+            .trim_start_matches("- ")
+            .strip_prefix("name:")
+            .map(|n| n.trim().trim_matches(['"', '\'']) == vendor_package.name)
+            .unwrap_or(false)
+    };
+    let line = lines
+        .iter()
+        .position(|l| is_name(l))
+        .and_then(|start| {
+            (start..lines.len())
+                .take_while(|&i| i == start || !lines[i].trim_start().starts_with("- name:"))
+                .find(|&i| lines[i].contains("rev:") && lines[i].contains(old_rev.as_str()))
+        })
+        .ok_or_else(|| {
+            Error::new(format!(
+                "Unable to locate the upstream revision of `{}` in {:?}. Please set it to `{}` manually.",
+                vendor_package.name, manifest_path, new_rev
+            ))
+        })?;
+    lines[line] = lines[line].replacen(old_rev.as_str(), new_rev, 1);
+
+    let mut content = lines.join("\n");
+    content.push('\n');
+    std::fs::write(manifest_path, content).map_err(|cause| {
+        Error::chain(format!("Cannot write manifest {:?}.", manifest_path), cause)
+    })?;
+    stageln!(
+        "Updated",
+        "{} from {} to {}",
+        vendor_package.name,
+        old_rev,
+        new_rev
+    );
+    Ok(())
+}
+
 /// apply existing patches
 pub fn apply_patches(
     rt: &Runtime,
@@ -793,6 +1015,58 @@ pub fn copy_recursively(
     Ok(())
 }
 
+/// Expand a mapping whose `from` may contain a glob pattern.
+///
+/// Literal mappings are returned unchanged. For patterns, one mapping is
+/// returned per matched upstream path, with `to` extended by the path relative
+/// to the literal (non-glob) prefix of `from`, preserving the directory
+/// structure below it.
+pub fn expand_link(
+    from: &Path,
+    to: &Path,
+    dep_path: impl AsRef<Path>,
+) -> Result<Vec<(PathBuf, PathBuf)>> {
+    let from_str = from.to_str().unwrap();
+    if !is_glob(from_str) {
+        return Ok(vec![(from.to_path_buf(), to.to_path_buf())]);
+    }
+
+    // The literal base directory of the pattern, relative to which the matches
+    // are placed below `to`.
+    let base: PathBuf = from
+        .components()
+        .take_while(|c| !is_glob(c.as_os_str().to_str().unwrap()))
+        .collect();
+
+    let pattern = dep_path.as_ref().join(from);
+    let mut links = Vec::new();
+    for entry in glob::glob(pattern.to_str().unwrap())
+        .map_err(|cause| Error::chain(format!("Invalid mapping pattern {:?}.", from), cause))?
+    {
+        let path = entry.map_err(|cause| {
+            Error::chain(
+                format!("Failed to expand mapping pattern {:?}.", from),
+                cause,
+            )
+        })?;
+        let rel_from = path.strip_prefix(dep_path.as_ref()).unwrap().to_path_buf();
+        let rel_to = to.join(rel_from.strip_prefix(&base).unwrap());
+        links.push((rel_from, rel_to));
+    }
+    if links.is_empty() {
+        warnln!(
+            "{:?} does not match anything in upstream, continuing.",
+            from
+        );
+    }
+    Ok(links)
+}
+
+/// Check whether a path contains glob metacharacters.
+fn is_glob(path: &str) -> bool {
+    path.contains(['*', '?', '['])
+}
+
 /// Prefix paths with prefix. Append ** to directories.
 pub fn extend_paths(
     include_from_upstream: &[String],
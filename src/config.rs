@@ -15,14 +15,14 @@ use std::hash::Hash;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
-use indexmap::IndexMap;
+use indexmap::{IndexMap, IndexSet};
 use semver;
 use serde::de::{Deserialize, Deserializer};
 use serde::ser::{Serialize, Serializer};
 use subst;
 
 use crate::error::*;
-use crate::target::TargetSpec;
+use crate::target::{TargetSet, TargetSpec};
 use crate::util::*;
 
 /// A package manifest.
@@ -34,18 +34,47 @@ pub struct Manifest {
     pub package: Package,
     /// The dependencies.
     pub dependencies: IndexMap<String, Dependency>,
+    /// The paths to check out of the git dependencies with a sparse checkout,
+    /// by dependency name.
+    pub sparse: IndexMap<String, Vec<String>>,
     /// The source files.
     pub sources: Option<Sources>,
     /// The include directories exported to dependent packages.
     pub export_include_dirs: Vec<PathBuf>,
+    /// The include directories listed as private in `export_include_dirs`,
+    /// which are only used by the package itself.
+    pub private_include_dirs: Vec<PathBuf>,
     /// The plugin binaries.
-    pub plugins: IndexMap<String, PathBuf>,
+    pub plugins: IndexMap<String, PluginCommand>,
+    /// The files prepended to the generated scripts, by script format.
+    pub script_prelude: IndexMap<String, PathBuf>,
+    /// The steps run before the sources are gathered.
+    pub prebuild: Vec<PreBuildStep>,
     /// Whether the dependencies of the manifest are frozen.
     pub frozen: bool,
     /// The workspace configuration.
     pub workspace: Workspace,
     /// Vendorized dependencies
     pub vendor_package: Vec<VendorPackage>,
+    /// The unknown top-level fields, kept for external tools.
+    pub extra: IndexMap<String, serde_yaml::Value>,
+    /// The unknown fields of the dependencies, by dependency name.
+    pub dependency_extra: IndexMap<String, IndexMap<String, serde_yaml::Value>>,
+}
+
+impl Manifest {
+    /// List the unknown fields of the manifest by their path, e.g.
+    /// `dependencies.foo.bar`.
+    pub fn unknown_fields(&self) -> Vec<String> {
+        let top = self.extra.keys().cloned();
+        let package = self.package.extra.keys().map(|k| format!("package.{}", k));
+        let deps = self.dependency_extra.iter().flat_map(|(name, extra)| {
+            extra
+                .keys()
+                .map(move |k| format!("dependencies.{}.{}", name, k))
+        });
+        top.chain(package).chain(deps).collect()
+    }
 }
 
 impl PrefixPaths for Manifest {
@@ -53,6 +82,7 @@ impl PrefixPaths for Manifest {
         Ok(Manifest {
             package: self.package,
             dependencies: self.dependencies.prefix_paths(prefix)?,
+            sparse: self.sparse,
             sources: self
                 .sources
                 .map_or(Ok::<Option<Sources>, Error>(None), |src| {
@@ -63,10 +93,19 @@ impl PrefixPaths for Manifest {
                 .into_iter()
                 .map(|src| src.prefix_paths(prefix))
                 .collect::<Result<_>>()?,
+            private_include_dirs: self
+                .private_include_dirs
+                .into_iter()
+                .map(|src| src.prefix_paths(prefix))
+                .collect::<Result<_>>()?,
             plugins: self.plugins.prefix_paths(prefix)?,
+            script_prelude: self.script_prelude.prefix_paths(prefix)?,
+            prebuild: self.prebuild.prefix_paths(prefix)?,
             frozen: self.frozen,
             workspace: self.workspace.prefix_paths(prefix)?,
             vendor_package: self.vendor_package.prefix_paths(prefix)?,
+            extra: self.extra,
+            dependency_extra: self.dependency_extra,
         })
     }
 }
@@ -81,13 +120,18 @@ pub struct Package {
     /// A list of package authors. Each author should be of the form `John Doe
     /// <john@doe.com>`.
     pub authors: Option<Vec<String>>,
+    /// The SPDX license expression of the package, e.g. `Apache-2.0`.
+    pub license: Option<String>,
+    /// The unknown fields, kept for external tools.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, serde_yaml::Value>,
 }
 
 /// A dependency.
 ///
 /// The name of the dependency is given implicitly by the key in the hash map
 /// that this `Dependency` is accessible through.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum Dependency {
     /// A dependency that can be found in one of the package repositories.
     Version(semver::VersionReq),
@@ -146,8 +190,33 @@ pub struct Sources {
     pub include_dirs: Vec<PathBuf>,
     /// The preprocessor definitions.
     pub defines: IndexMap<String, Option<String>>,
+    /// The preprocessor definitions that only apply to certain targets.
+    pub target_defines: IndexMap<TargetSpec, IndexMap<String, Option<String>>>,
+    /// The header files included by the source files.
+    pub headers: Vec<PathBuf>,
+    /// The library to compile the VHDL files into.
+    pub library: Option<String>,
     /// The source files.
     pub files: Vec<SourceFile>,
+    /// The file lists whose contents are added after the source files.
+    pub external_flists: Vec<PathBuf>,
+}
+
+impl Sources {
+    /// The directories containing the headers of these sources and their subgroups.
+    pub fn header_dirs(&self) -> IndexSet<PathBuf> {
+        let mut dirs: IndexSet<PathBuf> = self
+            .headers
+            .iter()
+            .map(|h| h.parent().map(Path::to_path_buf).unwrap_or_default())
+            .collect();
+        for file in &self.files {
+            if let SourceFile::Group(group) = file {
+                dirs.extend(group.header_dirs());
+            }
+        }
+        dirs
+    }
 }
 
 impl PrefixPaths for Sources {
@@ -156,7 +225,11 @@ impl PrefixPaths for Sources {
             target: self.target,
             include_dirs: self.include_dirs.prefix_paths(prefix)?,
             defines: self.defines,
+            target_defines: self.target_defines,
+            headers: self.headers.prefix_paths(prefix)?,
+            library: self.library,
             files: self.files.prefix_paths(prefix)?,
+            external_flists: self.external_flists.prefix_paths(prefix)?,
         })
     }
 }
@@ -194,6 +267,8 @@ pub struct Workspace {
     pub checkout_dir: Option<PathBuf>,
     /// The locally linked packages.
     pub package_links: IndexMap<PathBuf, String>,
+    /// The directories of the member packages aggregated into this workspace.
+    pub members: Vec<PathBuf>,
 }
 
 impl PrefixPaths for Workspace {
@@ -205,6 +280,7 @@ impl PrefixPaths for Workspace {
                 .into_iter()
                 .map(|(k, v)| Ok((k.prefix_paths(prefix)?, v)))
                 .collect::<Result<_>>()?,
+            members: self.members.prefix_paths(prefix)?,
         })
     }
 }
@@ -289,15 +365,22 @@ pub struct PartialManifest {
     /// The source files.
     pub sources: Option<SeqOrStruct<PartialSources, PartialSourceFile>>,
     /// The include directories exported to dependent packages.
-    pub export_include_dirs: Option<Vec<String>>,
+    pub export_include_dirs: Option<Vec<StringOrStruct<PartialExportIncludeDir>>>,
     /// The plugin binaries.
-    pub plugins: Option<IndexMap<String, String>>,
+    pub plugins: Option<IndexMap<String, StringOrStruct<PartialPluginCommand>>>,
+    /// The files prepended to the generated scripts, by script format.
+    pub script_prelude: Option<IndexMap<String, String>>,
+    /// The steps run before the sources are gathered.
+    pub prebuild: Option<Vec<PartialPreBuildStep>>,
     /// Whether the dependencies of the manifest are frozen.
     pub frozen: Option<bool>,
     /// The workspace configuration.
     pub workspace: Option<PartialWorkspace>,
     /// External Import dependencies
     pub vendor_package: Option<Vec<PartialVendorPackage>>,
+    /// The unknown fields, kept for external tools.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, serde_yaml::Value>,
 }
 
 impl Validate for PartialManifest {
@@ -311,6 +394,19 @@ impl Validate for PartialManifest {
             }
             None => return Err(Error::new("Missing package information.")),
         };
+        let sparse = self
+            .dependencies
+            .iter()
+            .flatten()
+            .filter_map(|(k, v)| Some((k.to_lowercase(), v.0.sparse.clone()?)))
+            .collect();
+        let dependency_extra = self
+            .dependencies
+            .iter()
+            .flatten()
+            .filter(|(_, v)| !v.0.extra.is_empty())
+            .map(|(k, v)| (k.to_lowercase(), v.0.extra.clone()))
+            .collect();
         let deps = match self.dependencies {
             Some(d) => d
                 .into_iter()
@@ -331,14 +427,50 @@ impl Validate for PartialManifest {
             })?),
             None => None,
         };
-        let exp_inc_dirs = self.export_include_dirs.unwrap_or_default();
+        // Without explicit export include dirs, export the directories of the
+        // declared headers.
+        let mut exp_inc_dirs = vec![];
+        let mut priv_inc_dirs = vec![];
+        match self.export_include_dirs {
+            Some(dirs) => {
+                for StringOrStruct(dir) in dirs {
+                    let path = env_path_from_string(dir.dir)?;
+                    if dir.public.unwrap_or(true) {
+                        exp_inc_dirs.push(path);
+                    } else {
+                        priv_inc_dirs.push(path);
+                    }
+                }
+            }
+            None => {
+                exp_inc_dirs = srcs
+                    .as_ref()
+                    .map(|s| s.header_dirs().into_iter().collect())
+                    .unwrap_or_default()
+            }
+        }
         let plugins = match self.plugins {
+            Some(s) => s.validate().map_err(|(key, cause)| {
+                Error::chain(
+                    format!("In plugin `{}` of package `{}`:", key, pkg.name),
+                    cause,
+                )
+            })?,
+            None => IndexMap::new(),
+        };
+        let script_prelude = match self.script_prelude {
             Some(s) => s
-                .iter()
-                .map(|(k, v)| Ok((k.clone(), env_path_from_string(v.to_string())?)))
+                .into_iter()
+                .map(|(k, v)| Ok((k, env_path_from_string(v)?)))
                 .collect::<Result<IndexMap<_, _>>>()?,
             None => IndexMap::new(),
         };
+        let prebuild = match self.prebuild {
+            Some(steps) => steps
+                .validate()
+                .map_err(|cause| Error::chain("In prebuild steps:", cause))?,
+            None => Vec::new(),
+        };
         let frozen = self.frozen.unwrap_or(false);
         let workspace = match self.workspace {
             Some(w) => w
@@ -355,15 +487,146 @@ impl Validate for PartialManifest {
         Ok(Manifest {
             package: pkg,
             dependencies: deps,
+            sparse,
             sources: srcs,
-            export_include_dirs: exp_inc_dirs
-                .iter()
-                .map(|path| env_path_from_string(path.to_string()))
-                .collect::<Result<Vec<_>>>()?,
+            export_include_dirs: exp_inc_dirs,
+            private_include_dirs: priv_inc_dirs,
             plugins,
+            script_prelude,
+            prebuild,
             frozen,
             workspace,
             vendor_package,
+            extra: self.extra,
+            dependency_extra,
+        })
+    }
+}
+
+/// A step run before the sources of a package are gathered, e.g. to generate
+/// some of them.
+#[derive(Serialize, Clone, Debug)]
+pub struct PreBuildStep {
+    /// The command, which is run by the shell.
+    pub command: String,
+    /// The working directory of the command.
+    pub dir: PathBuf,
+}
+
+impl PrefixPaths for PreBuildStep {
+    fn prefix_paths(self, prefix: &Path) -> Result<Self> {
+        Ok(PreBuildStep {
+            command: self.command,
+            dir: self.dir.prefix_paths(prefix)?,
+        })
+    }
+}
+
+/// A partial prebuild step.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PartialPreBuildStep {
+    /// The command, which is run by the shell.
+    pub command: Option<String>,
+    /// The working directory of the command, relative to the package. Defaults
+    /// to the package root.
+    pub dir: Option<String>,
+}
+
+impl Validate for PartialPreBuildStep {
+    type Output = PreBuildStep;
+    type Error = Error;
+    fn validate(self) -> Result<PreBuildStep> {
+        Ok(PreBuildStep {
+            command: match self.command {
+                Some(command) => command,
+                None => return Err(Error::new("prebuild step command missing")),
+            },
+            dir: match self.dir {
+                Some(dir) => env_path_from_string(dir)?,
+                None => PathBuf::new(),
+            },
+        })
+    }
+}
+
+/// The command implementing a plugin.
+#[derive(Serialize, Clone, Debug)]
+pub struct PluginCommand {
+    /// The binary or script.
+    pub path: PathBuf,
+    /// The interpreter which runs the script, if any.
+    pub interpreter: Option<String>,
+    /// The arguments passed before the ones given on the command line.
+    pub args: Vec<String>,
+}
+
+impl PrefixPaths for PluginCommand {
+    fn prefix_paths(self, prefix: &Path) -> Result<Self> {
+        Ok(PluginCommand {
+            path: self.path.prefix_paths(prefix)?,
+            interpreter: self.interpreter,
+            args: self.args,
+        })
+    }
+}
+
+/// A partial plugin command.
+///
+/// A plain string is the path of a binary.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PartialPluginCommand {
+    /// The binary or script, relative to the package.
+    pub path: Option<String>,
+    /// The interpreter which runs the script, e.g. `python3`.
+    pub interpreter: Option<String>,
+    /// The arguments passed before the ones given on the command line.
+    pub args: Option<Vec<String>>,
+}
+
+impl FromStr for PartialPluginCommand {
+    type Err = Void;
+    fn from_str(s: &str) -> std::result::Result<Self, Void> {
+        Ok(PartialPluginCommand {
+            path: Some(s.into()),
+            interpreter: None,
+            args: None,
+        })
+    }
+}
+
+impl Validate for PartialPluginCommand {
+    type Output = PluginCommand;
+    type Error = Error;
+    fn validate(self) -> Result<PluginCommand> {
+        Ok(PluginCommand {
+            path: match self.path {
+                Some(path) => env_path_from_string(path)?,
+                None => return Err(Error::new("plugin path missing")),
+            },
+            interpreter: self.interpreter,
+            args: self.args.unwrap_or_default(),
+        })
+    }
+}
+
+/// A partial exported include directory.
+///
+/// A plain string is a public include directory.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PartialExportIncludeDir {
+    /// The include directory.
+    pub dir: String,
+    /// Whether the directory is exported to dependent packages. Defaults to
+    /// true.
+    pub public: Option<bool>,
+}
+
+impl FromStr for PartialExportIncludeDir {
+    type Err = Void;
+    fn from_str(s: &str) -> std::result::Result<Self, Void> {
+        Ok(PartialExportIncludeDir {
+            dir: s.into(),
+            public: None,
         })
     }
 }
@@ -391,6 +654,11 @@ pub struct PartialDependency {
     /// The version requirement of the package. This will be parsed into a
     /// semantic versioning requirement.
     version: Option<String>,
+    /// The paths to check out of a git dependency with a sparse checkout.
+    sparse: Option<Vec<String>>,
+    /// The unknown fields, kept for external tools.
+    #[serde(flatten)]
+    extra: IndexMap<String, serde_yaml::Value>,
 }
 
 impl FromStr for PartialDependency {
@@ -401,6 +669,8 @@ impl FromStr for PartialDependency {
             git: None,
             rev: None,
             version: Some(s.into()),
+            sparse: None,
+            extra: IndexMap::new(),
         })
     }
 }
@@ -432,6 +702,11 @@ impl Validate for PartialDependency {
                 "A dependency cannot specify `version` and `rev` at the same time.",
             ));
         }
+        if self.sparse.is_some() && self.git.is_none() {
+            return Err(Error::new(
+                "Only a `git` dependency can have a `sparse` field.",
+            ));
+        }
         if let Some(path) = self.path {
             if let Some(list) = string_list(
                 self.git
@@ -450,6 +725,7 @@ impl Validate for PartialDependency {
                 Ok(Dependency::Path(env_path_from_string(path)?))
             }
         } else if let Some(git) = self.git {
+            let git = env_string_from_string(git)?;
             if let Some(rev) = self.rev {
                 Ok(Dependency::GitRevision(git, rev))
             } else if let Some(version) = version {
@@ -469,6 +745,58 @@ impl Validate for PartialDependency {
     }
 }
 
+/// A partial dependency override.
+///
+/// Contains the same fields as a dependency, and optionally the targets for
+/// which the override applies.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PartialOverride {
+    /// The targets for which the override applies. Applies to all targets if
+    /// omitted.
+    target: Option<TargetSpec>,
+    /// The overriding dependency.
+    #[serde(flatten)]
+    dependency: PartialDependency,
+}
+
+/// One or more overrides of a dependency.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(untagged)]
+pub enum PartialOverrides {
+    /// A single override.
+    One(PartialOverride),
+    /// Multiple overrides, usually for different targets.
+    Many(Vec<PartialOverride>),
+}
+
+impl PartialOverrides {
+    /// Obtain the overrides in their order of declaration.
+    pub fn into_vec(self) -> Vec<PartialOverride> {
+        match self {
+            PartialOverrides::One(o) => vec![o],
+            PartialOverrides::Many(o) => o,
+        }
+    }
+}
+
+impl PrefixPaths for PartialOverride {
+    fn prefix_paths(self, prefix: &Path) -> Result<Self> {
+        Ok(PartialOverride {
+            dependency: self.dependency.prefix_paths(prefix)?,
+            ..self
+        })
+    }
+}
+
+impl PrefixPaths for PartialOverrides {
+    fn prefix_paths(self, prefix: &Path) -> Result<Self> {
+        Ok(match self {
+            PartialOverrides::One(o) => PartialOverrides::One(o.prefix_paths(prefix)?),
+            PartialOverrides::Many(o) => PartialOverrides::Many(o.prefix_paths(prefix)?),
+        })
+    }
+}
+
 /// A partial group of source files.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct PartialSources {
@@ -478,8 +806,17 @@ pub struct PartialSources {
     pub include_dirs: Option<Vec<String>>,
     /// The preprocessor definitions.
     pub defines: Option<IndexMap<String, Option<String>>>,
+    /// The preprocessor definitions that only apply to certain targets.
+    pub target_defines: Option<IndexMap<TargetSpec, IndexMap<String, Option<String>>>>,
+    /// The header files included by the source files.
+    pub headers: Option<Vec<String>>,
+    /// The library to compile the VHDL files into.
+    pub library: Option<String>,
     /// The source file paths.
     pub files: Vec<PartialSourceFile>,
+    /// The file lists whose include directories, defines, and files are added
+    /// after the source files.
+    pub external_flists: Option<Vec<String>>,
 }
 
 impl From<Vec<PartialSourceFile>> for PartialSources {
@@ -488,7 +825,11 @@ impl From<Vec<PartialSourceFile>> for PartialSources {
             target: None,
             include_dirs: None,
             defines: None,
+            target_defines: None,
+            headers: None,
+            library: None,
             files: v,
+            external_flists: None,
         }
     }
 }
@@ -504,12 +845,29 @@ impl Validate for PartialSources {
             .map(|path| env_path_from_string(path.to_string()))
             .collect();
         let defines = self.defines.unwrap_or_default();
+        let target_defines = self.target_defines.unwrap_or_default();
+        let headers: Result<Vec<_>> = self
+            .headers
+            .unwrap_or_default()
+            .iter()
+            .map(|path| env_path_from_string(path.to_string()))
+            .collect();
         let files: Result<Vec<_>> = self.files.into_iter().map(|f| f.validate()).collect();
+        let external_flists: Result<Vec<_>> = self
+            .external_flists
+            .unwrap_or_default()
+            .into_iter()
+            .map(env_path_from_string)
+            .collect();
         Ok(Sources {
             target: self.target.unwrap_or(TargetSpec::Wildcard),
             include_dirs: include_dirs?,
             defines,
+            target_defines,
+            headers: headers?,
+            library: self.library,
             files: files?,
+            external_flists: external_flists?,
         })
     }
 }
@@ -594,6 +952,8 @@ pub struct PartialWorkspace {
     pub checkout_dir: Option<String>,
     /// The locally linked packages.
     pub package_links: Option<IndexMap<String, String>>,
+    /// The directories of the member packages aggregated into this workspace.
+    pub members: Option<Vec<String>>,
 }
 
 impl Validate for PartialWorkspace {
@@ -612,6 +972,12 @@ impl Validate for PartialWorkspace {
                 None => None,
             },
             package_links: package_links?,
+            members: self
+                .members
+                .unwrap_or_default()
+                .into_iter()
+                .map(env_path_from_string)
+                .collect::<Result<_>>()?,
         })
     }
 }
@@ -689,8 +1055,59 @@ pub struct Config {
     pub git: String,
     /// The dependency overrides.
     pub overrides: IndexMap<String, Dependency>,
+    /// The dependency overrides that only apply to certain targets, in the
+    /// order in which they were declared.
+    pub target_overrides: IndexMap<String, Vec<(TargetSpec, Dependency)>>,
     /// The auxiliary plugin dependencies.
     pub plugins: IndexMap<String, Dependency>,
+    /// The timeout in seconds for git network operations.
+    pub git_timeout_secs: Option<u64>,
+    /// Extra `-c key=value` arguments passed to git, by repository URL prefix.
+    pub git_config: IndexMap<String, Vec<String>>,
+    /// Whether to pin the dependencies of path dependencies to their own lockfile.
+    pub respect_nested_lock: bool,
+    /// Whether to warn about headers provided by multiple packages.
+    pub check_include_collisions: bool,
+    /// The URL or path of the package registry index.
+    pub registry: Option<String>,
+    /// Whether a mismatch of dependency and package names is an error.
+    pub strict_names: bool,
+    /// Whether likely mistakes in the manifests are errors instead of warnings.
+    pub strict: bool,
+    /// Whether unknown fields in the manifest are accepted without a warning.
+    pub allow_extra_fields: bool,
+}
+
+impl Config {
+    /// Determine the override of a dependency, if any.
+    ///
+    /// Overrides restricted to targets only apply if a set of targets is given
+    /// and matches, and take precedence over the ones for all targets. Among
+    /// multiple matching overrides, the one declared last wins.
+    pub fn override_for(&self, name: &str, targets: Option<&TargetSet>) -> Option<&Dependency> {
+        targets
+            .and_then(|targets| {
+                self.target_overrides
+                    .get(name)?
+                    .iter()
+                    .rev()
+                    .find(|(spec, _)| spec.matches(targets))
+            })
+            .map(|(_, dep)| dep)
+            .or_else(|| self.overrides.get(name))
+    }
+
+    /// Determine the extra git configuration for a repository URL.
+    ///
+    /// Among multiple matching URL prefixes, the longest one wins.
+    pub fn git_config_for(&self, url: &str) -> &[String] {
+        self.git_config
+            .iter()
+            .filter(|(prefix, _)| url.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, config)| config.as_slice())
+            .unwrap_or_default()
+    }
 }
 
 /// A partial configuration.
@@ -701,9 +1118,25 @@ pub struct PartialConfig {
     /// The git command or path to the binary.
     pub git: Option<String>,
     /// The dependency overrides.
-    pub overrides: Option<IndexMap<String, PartialDependency>>,
+    pub overrides: Option<IndexMap<String, PartialOverrides>>,
     /// The auxiliary plugin dependencies.
     pub plugins: Option<IndexMap<String, PartialDependency>>,
+    /// The timeout in seconds for git network operations.
+    pub git_timeout_secs: Option<u64>,
+    /// Extra `-c key=value` arguments passed to git, by repository URL prefix.
+    pub git_config: Option<IndexMap<String, Vec<String>>>,
+    /// Whether to pin the dependencies of path dependencies to their own lockfile.
+    pub respect_nested_lock: Option<bool>,
+    /// Whether to warn about headers provided by multiple packages.
+    pub check_include_collisions: Option<bool>,
+    /// The URL or path of the package registry index.
+    pub registry: Option<String>,
+    /// Whether a mismatch of dependency and package names is an error.
+    pub strict_names: Option<bool>,
+    /// Whether likely mistakes in the manifests are errors instead of warnings.
+    pub strict: Option<bool>,
+    /// Whether unknown fields in the manifest are accepted without a warning.
+    pub allow_extra_fields: Option<bool>,
 }
 
 impl PartialConfig {
@@ -714,6 +1147,14 @@ impl PartialConfig {
             git: None,
             overrides: None,
             plugins: None,
+            git_timeout_secs: None,
+            git_config: None,
+            respect_nested_lock: None,
+            check_include_collisions: None,
+            registry: None,
+            strict_names: None,
+            strict: None,
+            allow_extra_fields: None,
         }
     }
 }
@@ -730,6 +1171,11 @@ impl PrefixPaths for PartialConfig {
             database: self.database.prefix_paths(prefix)?,
             overrides: self.overrides.prefix_paths(prefix)?,
             plugins: self.plugins.prefix_paths(prefix)?,
+            // Only local registry indices are relative to the configuration.
+            registry: match self.registry {
+                Some(index) if !index.contains("://") => Some(index.prefix_paths(prefix)?),
+                other => other,
+            },
             ..self
         })
     }
@@ -743,7 +1189,19 @@ impl Merge for PartialConfig {
             overrides: match (self.overrides, other.overrides) {
                 (Some(o), None) | (None, Some(o)) => Some(o),
                 (Some(mut o1), Some(o2)) => {
-                    o1.extend(o2);
+                    // Keep all overrides of a dependency, such that the ones
+                    // restricted to targets are retained.
+                    for (name, o) in o2 {
+                        let merged = match o1.swap_remove(&name) {
+                            Some(existing) => {
+                                let mut list = existing.into_vec();
+                                list.extend(o.into_vec());
+                                PartialOverrides::Many(list)
+                            }
+                            None => o,
+                        };
+                        o1.insert(name, merged);
+                    }
                     Some(o1)
                 }
                 (None, None) => None,
@@ -756,6 +1214,25 @@ impl Merge for PartialConfig {
                 }
                 (None, None) => None,
             },
+            git_timeout_secs: self.git_timeout_secs.or(other.git_timeout_secs),
+            git_config: match (self.git_config, other.git_config) {
+                (Some(o), None) | (None, Some(o)) => Some(o),
+                (Some(mut o1), Some(o2)) => {
+                    for (prefix, config) in o2 {
+                        o1.entry(prefix).or_insert(config);
+                    }
+                    Some(o1)
+                }
+                (None, None) => None,
+            },
+            respect_nested_lock: self.respect_nested_lock.or(other.respect_nested_lock),
+            check_include_collisions: self
+                .check_include_collisions
+                .or(other.check_include_collisions),
+            registry: self.registry.or(other.registry),
+            strict_names: self.strict_names.or(other.strict_names),
+            strict: self.strict.or(other.strict),
+            allow_extra_fields: self.allow_extra_fields.or(other.allow_extra_fields),
         }
     }
 }
@@ -764,6 +1241,26 @@ impl Validate for PartialConfig {
     type Output = Config;
     type Error = Error;
     fn validate(self) -> Result<Config> {
+        // Validate the overrides, keeping their order of declaration.
+        let mut overrides = IndexMap::<String, Vec<(TargetSpec, Dependency)>>::new();
+        for (name, ovs) in self.overrides.unwrap_or_default() {
+            for ov in ovs.into_vec() {
+                if ov.dependency.sparse.is_some() {
+                    return Err(Error::new(format!(
+                        "In override `{}`: `sparse` is only supported in manifests.",
+                        name
+                    )));
+                }
+                let dep = ov
+                    .dependency
+                    .validate()
+                    .map_err(|cause| Error::chain(format!("In override `{}`:", name), cause))?;
+                overrides
+                    .entry(name.clone())
+                    .or_default()
+                    .push((ov.target.unwrap_or(TargetSpec::Wildcard), dep));
+            }
+        }
         Ok(Config {
             database: match self.database {
                 Some(db) => env_path_from_string(db)?,
@@ -773,24 +1270,44 @@ impl Validate for PartialConfig {
                 Some(git) => git,
                 None => return Err(Error::new("Git command or path to binary not configured")),
             },
-            overrides: match self.overrides {
-                Some(d) => d.validate().map_err(|(key, cause)| {
-                    Error::chain(format!("In override `{}`:", key), cause)
-                })?,
-                None => IndexMap::new(),
-            },
+            overrides: overrides
+                .iter()
+                .filter_map(|(name, ovs)| {
+                    let (_, dep) = ovs.iter().rev().find(|(spec, _)| spec.is_wildcard())?;
+                    Some((name.clone(), dep.clone()))
+                })
+                .collect(),
+            target_overrides: overrides
+                .into_iter()
+                .map(|(name, ovs)| {
+                    let ovs: Vec<_> = ovs
+                        .into_iter()
+                        .filter(|(spec, _)| !spec.is_wildcard())
+                        .collect();
+                    (name, ovs)
+                })
+                .filter(|(_, ovs)| !ovs.is_empty())
+                .collect(),
             plugins: match self.plugins {
                 Some(d) => d
                     .validate()
                     .map_err(|(key, cause)| Error::chain(format!("In plugin `{}`:", key), cause))?,
                 None => IndexMap::new(),
             },
+            git_timeout_secs: self.git_timeout_secs,
+            git_config: self.git_config.unwrap_or_default(),
+            respect_nested_lock: self.respect_nested_lock.unwrap_or(false),
+            check_include_collisions: self.check_include_collisions.unwrap_or(false),
+            registry: self.registry,
+            strict_names: self.strict_names.or(self.strict).unwrap_or(false),
+            strict: self.strict.unwrap_or(false),
+            allow_extra_fields: self.allow_extra_fields.unwrap_or(false),
         })
     }
 }
 
 /// An external import dependency
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Clone, Debug)]
 pub struct VendorPackage {
     /// External dependency name
     pub name: String,
@@ -806,6 +1323,8 @@ pub struct VendorPackage {
     pub include_from_upstream: Vec<String>,
     /// exclude from upstream
     pub exclude_from_upstream: Vec<String>,
+    /// Allow the upstream revision to be a branch or tag instead of a commit hash
+    pub allow_non_commit: bool,
 }
 
 impl PrefixPaths for VendorPackage {
@@ -838,6 +1357,7 @@ impl PrefixPaths for VendorPackage {
             patch_dir: patch_root,
             include_from_upstream: self.include_from_upstream,
             exclude_from_upstream: self.exclude_from_upstream,
+            allow_non_commit: self.allow_non_commit,
         })
     }
 }
@@ -861,6 +1381,8 @@ pub struct PartialVendorPackage {
     pub include_from_upstream: Option<Vec<String>>,
     /// exclude from upstream
     pub exclude_from_upstream: Option<Vec<String>>,
+    /// Allow the upstream revision to be a branch or tag instead of a commit hash
+    pub allow_non_commit: Option<bool>,
 }
 
 impl Validate for PartialVendorPackage {
@@ -896,6 +1418,7 @@ impl Validate for PartialVendorPackage {
                 excl.push(String::from(".git"));
                 excl
             },
+            allow_non_commit: self.allow_non_commit.unwrap_or(false),
         })
     }
 }
@@ -935,6 +1458,9 @@ pub struct LockedPackage {
     pub source: LockedSource,
     /// Other packages this package depends on.
     pub dependencies: BTreeSet<String>,
+    /// The paths checked out with a sparse checkout, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sparse: Option<Vec<String>>,
 }
 
 /// A source description for a locked dependency.
@@ -948,19 +1474,124 @@ pub enum LockedSource {
     Registry(String),
 }
 
-#[cfg(unix)]
+impl LockedSource {
+    /// The kind of source, i.e. `path`, `git`, or `registry`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            LockedSource::Path(_) => "path",
+            LockedSource::Git(_) => "git",
+            LockedSource::Registry(_) => "registry",
+        }
+    }
+
+    /// The path or URL of the source.
+    pub fn location(&self) -> String {
+        match self {
+            LockedSource::Path(path) => path.to_string_lossy().into_owned(),
+            LockedSource::Git(url) | LockedSource::Registry(url) => url.clone(),
+        }
+    }
+}
+
+/// The contents of a file list, i.e. a `.f` file.
+#[derive(Debug, Default)]
+pub struct Flist {
+    /// The include directories given with `+incdir+`.
+    pub include_dirs: Vec<PathBuf>,
+    /// The defines given with `+define+`.
+    pub defines: IndexMap<String, Option<String>>,
+    /// The source files.
+    pub files: Vec<PathBuf>,
+}
+
+/// Parse a file list.
+///
+/// Supports `+incdir+` and `+define+` arguments with one or more `+`-separated
+/// values, nested file lists included with `-f` or `-F`, and `//` and `#`
+/// comments. Any other token is a source file. Relative paths are relative to
+/// the directory of the file list they appear in, and environment variables
+/// in paths are expanded. Cyclic includes are an error.
+pub fn parse_flist(path: &Path) -> Result<Flist> {
+    let mut flist = Flist::default();
+    parse_flist_into(path, &mut flist, &mut IndexSet::new())?;
+    Ok(flist)
+}
+
+fn parse_flist_into(path: &Path, flist: &mut Flist, seen: &mut IndexSet<PathBuf>) -> Result<()> {
+    // Compare canonical paths, such that cycles are detected regardless of how
+    // the file lists refer to each other.
+    let path = &std::fs::canonicalize(path)
+        .map_err(|cause| Error::chain(format!("Cannot find file list {:?}.", path), cause))?;
+    if seen.contains(path) {
+        let chain: Vec<_> = seen
+            .iter()
+            .skip_while(|p| *p != path)
+            .chain(Some(&path.to_path_buf()))
+            .map(|p| format!("{:?}", p))
+            .collect();
+        return Err(Error::new(format!(
+            "File list {:?} includes itself: {}.",
+            path,
+            chain.join(" -> ")
+        )));
+    }
+    seen.insert(path.to_path_buf());
+    let content = std::fs::read_to_string(path)
+        .map_err(|cause| Error::chain(format!("Cannot read file list {:?}.", path), cause))?;
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let resolve = |p: &str| -> Result<PathBuf> { Ok(dir.join(env_path_from_string(p.into())?)) };
+    let mut tokens = content.lines().flat_map(|line| {
+        line.split("//")
+            .next()
+            .unwrap()
+            .split_whitespace()
+            .take_while(|token| !token.starts_with('#'))
+    });
+    while let Some(token) = tokens.next() {
+        if let Some(dirs) = token.strip_prefix("+incdir+") {
+            for d in dirs.split('+').filter(|d| !d.is_empty()) {
+                flist.include_dirs.push(resolve(d)?);
+            }
+        } else if let Some(defines) = token.strip_prefix("+define+") {
+            for d in defines.split('+').filter(|d| !d.is_empty()) {
+                let mut parts = d.splitn(2, '=');
+                let name = parts.next().unwrap();
+                let value = parts.next().map(String::from);
+                flist.defines.insert(name.to_string(), value);
+            }
+        } else if token == "-f" || token == "-F" {
+            let nested = tokens.next().ok_or_else(|| {
+                Error::new(format!(
+                    "Missing file list after `{}` in {:?}.",
+                    token, path
+                ))
+            })?;
+            parse_flist_into(&resolve(nested)?, flist, seen)?;
+        } else if token.starts_with('+') || token.starts_with('-') {
+            warnln!(
+                "Ignoring unsupported argument `{}` in file list {:?}.",
+                token,
+                path
+            );
+        } else {
+            flist.files.push(resolve(token)?);
+        }
+    }
+    seen.pop();
+    Ok(())
+}
+
 fn env_path_from_string(path_str: String) -> Result<PathBuf> {
-    Ok(PathBuf::from(
-        subst::substitute(&path_str, &subst::Env).map_err(|cause| {
-            Error::chain(
-                format!("Unable to substitute with env: {}", path_str),
-                cause,
-            )
-        })?,
-    ))
+    Ok(PathBuf::from(env_string_from_string(path_str)?))
+}
+
+#[cfg(unix)]
+fn env_string_from_string(s: String) -> Result<String> {
+    subst::substitute(&s, &subst::Env)
+        .map_err(|cause| Error::chain(format!("Unable to substitute with env: {}", s), cause))
 }
 
 #[cfg(windows)]
-fn env_path_from_string(path_str: String) -> Result<PathBuf> {
-    Ok(PathBuf::from(path_str))
+fn env_string_from_string(s: String) -> Result<String> {
+    Ok(s)
 }
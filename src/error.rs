@@ -12,6 +12,10 @@ use std::sync::Arc;
 #[allow(deprecated)]
 pub static ENABLE_DEBUG: AtomicBool = ATOMIC_BOOL_INIT;
 
+/// Whether diagnostics and stage progress are printed in color.
+#[allow(deprecated)]
+pub static ENABLE_COLOR: AtomicBool = ATOMIC_BOOL_INIT;
+
 /// Print an error.
 #[macro_export]
 macro_rules! errorln {
@@ -54,7 +58,10 @@ macro_rules! debugln {
 /// Emit a diagnostic message.
 macro_rules! diagnostic {
     ($severity:expr; $($arg:tt)*) => {
-        eprintln!("{} {}", $severity, format!($($arg)*))
+        {
+            let msg = format!("{} {}", $severity, format!($($arg)*));
+            $crate::progress::suspend(|| eprintln!("{}", msg))
+        }
     }
 }
 
@@ -75,7 +82,11 @@ impl fmt::Display for Severity {
             Severity::Note => ("\x1B[;1m", "note"),
             Severity::Debug => ("\x1B[34;1m", "debug"),
         };
-        write!(f, "{}{}:\x1B[m", color, prefix)
+        if ENABLE_COLOR.load(std::sync::atomic::Ordering::Relaxed) {
+            write!(f, "{}{}:\x1B[m", color, prefix)
+        } else {
+            write!(f, "{}:", prefix)
+        }
     }
 }
 
@@ -158,5 +169,14 @@ macro_rules! stageln {
 
 /// Print stage progress.
 pub fn println_stage(stage: &str, message: &str) {
-    eprintln!("\x1B[32;1m{:>12}\x1B[0m {}", stage, message);
+    if crate::progress::json_enabled() {
+        return crate::progress::stage(stage, message);
+    }
+    crate::progress::suspend(|| {
+        if ENABLE_COLOR.load(std::sync::atomic::Ordering::Relaxed) {
+            eprintln!("\x1B[32;1m{:>12}\x1B[0m {}", stage, message);
+        } else {
+            eprintln!("{:>12} {}", stage, message);
+        }
+    })
 }
@@ -7,7 +7,9 @@
 
 use std::ffi::OsStr;
 use std::path::Path;
+use std::time::Duration;
 
+use futures::future;
 use futures::TryFutureExt;
 use tokio::process::Command;
 
@@ -23,12 +25,48 @@ pub struct Git<'ctx> {
     pub path: &'ctx Path,
     /// The session within which commands will be executed.
     pub git: &'ctx String,
+    /// The timeout in seconds after which a spawned command is killed.
+    pub timeout: Option<u64>,
+    /// The extra `key=value` configuration passed to every command via `-c`.
+    pub config: &'ctx [String],
 }
 
-impl<'git, 'ctx> Git<'ctx> {
+impl<'ctx> Git<'ctx> {
     /// Create a new git context.
     pub fn new(path: &'ctx Path, git: &'ctx String) -> Git<'ctx> {
-        Git { path, git }
+        Git {
+            path,
+            git,
+            timeout: None,
+            config: &[],
+        }
+    }
+
+    /// Set the timeout after which spawned commands are killed.
+    ///
+    /// A value of `None` lets commands run indefinitely.
+    pub fn with_timeout(self, timeout: Option<u64>) -> Git<'ctx> {
+        Git { timeout, ..self }
+    }
+
+    /// Set the extra configuration passed to every spawned command.
+    ///
+    /// Each entry has the form `key=value` and is passed to git via `-c`.
+    pub fn with_config(self, config: &'ctx [String]) -> Git<'ctx> {
+        Git { config, ..self }
+    }
+
+    /// Create a new git command without a subcommand.
+    ///
+    /// The command is pre-configured to operate in the repository's path and
+    /// carries the extra configuration.
+    fn base_command(self) -> Command {
+        let mut cmd = Command::new(self.git);
+        for config in self.config {
+            cmd.arg("-c").arg(config);
+        }
+        cmd.current_dir(self.path);
+        cmd
     }
 
     /// Create a new git command.
@@ -36,9 +74,8 @@ impl<'git, 'ctx> Git<'ctx> {
     /// The command will have the form `git <subcommand>` and be pre-configured
     /// to operate in the repository's path.
     pub fn command(self, subcommand: &str) -> Command {
-        let mut cmd = Command::new(self.git);
+        let mut cmd = self.base_command();
         cmd.arg(subcommand);
-        cmd.current_dir(self.path);
         cmd
     }
 
@@ -51,10 +88,23 @@ impl<'git, 'ctx> Git<'ctx> {
     /// Returns a future that will resolve to the command's stdout.
     ///
     /// If `check` is false, the stdout will be returned regardless of the
-    /// command's exit code.
+    /// command's exit code. If a timeout is set, the command is killed and an
+    /// error is returned once it expires.
     #[allow(clippy::format_push_string)]
     pub async fn spawn(self, mut cmd: Command, check: bool) -> Result<String> {
-        let output = cmd.output().map_err(|cause| {
+        cmd.kill_on_drop(true);
+        let output = match self.timeout {
+            Some(secs) => tokio::time::timeout(Duration::from_secs(secs), cmd.output())
+                .await
+                .map_err(|_| {
+                    Error::new(format!(
+                        "Git command ({:?}) in directory {:?} timed out after {} seconds.",
+                        cmd, self.path, secs
+                    ))
+                })?,
+            None => cmd.output().await,
+        };
+        let output = future::ready(output).map_err(|cause| {
             if cause
                 .to_string()
                 .to_lowercase()
@@ -109,8 +159,7 @@ impl<'git, 'ctx> Git<'ctx> {
     where
         F: FnOnce(&mut Command) -> &mut Command,
     {
-        let mut cmd = Command::new(self.git);
-        cmd.current_dir(self.path);
+        let mut cmd = self.base_command();
         f(&mut cmd);
         self.spawn(cmd, true).await
     }
@@ -123,8 +172,7 @@ impl<'git, 'ctx> Git<'ctx> {
     where
         F: FnOnce(&mut Command) -> &mut Command,
     {
-        let mut cmd = Command::new(self.git);
-        cmd.current_dir(self.path);
+        let mut cmd = self.base_command();
         f(&mut cmd);
         self.spawn(cmd, false).await
     }
@@ -137,8 +185,7 @@ impl<'git, 'ctx> Git<'ctx> {
     where
         F: FnOnce(&mut Command) -> &mut Command,
     {
-        let mut cmd = Command::new(self.git);
-        cmd.current_dir(self.path);
+        let mut cmd = self.base_command();
         f(&mut cmd);
         cmd.spawn()?.wait().await?;
         Ok(())
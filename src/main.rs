@@ -34,6 +34,8 @@ pub mod cmd;
 pub mod config;
 // pub mod future_throttle;
 pub mod git;
+pub mod progress;
+pub mod registry;
 pub mod resolver;
 #[allow(clippy::bind_instead_of_map)]
 pub mod sess;
@@ -0,0 +1,182 @@
+// Copyright (c) 2024 ETH Zurich
+
+//! An aggregate progress bar for long-running dependency operations.
+//!
+//! The bar is drawn on the last line of stderr if it is a terminal. Stage
+//! progress and diagnostics printed while a bar is active are emitted above
+//! it. If stderr is not a terminal, nothing is drawn and only the regular
+//! line output remains.
+//!
+//! In JSON mode, the bar and the stage progress are instead emitted as one
+//! JSON object per line, such that a wrapping tool can display them.
+
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use is_terminal::IsTerminal;
+
+/// The width of the bar in characters.
+const WIDTH: usize = 30;
+
+/// The state of the currently active bar.
+struct State {
+    /// The label printed in front of the bar.
+    label: &'static str,
+    /// The number of completed steps.
+    done: usize,
+    /// The total number of steps.
+    total: usize,
+    /// Whether the bar is currently drawn.
+    drawn: bool,
+}
+
+/// The currently active bar, if any.
+static ACTIVE: Mutex<Option<State>> = Mutex::new(None);
+
+/// Whether progress is emitted as JSON lines.
+static JSON: AtomicBool = AtomicBool::new(false);
+
+/// Emit progress as JSON lines instead of drawing a bar.
+pub fn enable_json() {
+    JSON.store(true, Ordering::Relaxed);
+}
+
+/// Check whether progress is emitted as JSON lines.
+pub fn json_enabled() -> bool {
+    JSON.load(Ordering::Relaxed)
+}
+
+/// Emit a progress event as a JSON line on stderr.
+fn emit(event: serde_json::Value) {
+    let mut stderr = std::io::stderr();
+    let _ = writeln!(stderr, "{}", event);
+    let _ = stderr.flush();
+}
+
+/// Emit a stage message as a JSON progress event.
+pub fn stage(stage: &str, message: &str) {
+    emit(serde_json::json!({
+        "event": "stage",
+        "stage": stage,
+        "message": message,
+    }));
+}
+
+/// A handle to the active progress bar.
+///
+/// The bar is removed once the handle is dropped, which also covers early
+/// returns due to errors.
+pub struct Progress {
+    /// Whether this handle owns the active bar.
+    enabled: bool,
+}
+
+impl Progress {
+    /// Start a progress bar with the given label and number of steps.
+    ///
+    /// Nothing is drawn if stderr is not a terminal, if there are no steps, or
+    /// if another bar is already active. In JSON mode, a `start` event is
+    /// emitted instead.
+    pub fn new(label: &'static str, total: usize) -> Progress {
+        let mut active = ACTIVE.lock().unwrap();
+        let enabled =
+            total > 0 && active.is_none() && (json_enabled() || std::io::stderr().is_terminal());
+        if enabled && json_enabled() {
+            emit(serde_json::json!({ "event": "start", "label": label, "total": total }));
+        }
+        if enabled {
+            *active = Some(State {
+                label,
+                done: 0,
+                total,
+                drawn: false,
+            });
+        }
+        Progress { enabled }
+    }
+
+    /// Mark one step as completed and redraw the bar.
+    pub fn inc(&self) {
+        if !self.enabled {
+            return;
+        }
+        let mut active = ACTIVE.lock().unwrap();
+        if let Some(ref mut state) = *active {
+            state.done = (state.done + 1).min(state.total);
+            draw(state);
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        if !self.enabled {
+            return;
+        }
+        let mut active = ACTIVE.lock().unwrap();
+        if let Some(state) = active.take() {
+            if json_enabled() {
+                emit(serde_json::json!({
+                    "event": "finish",
+                    "label": state.label,
+                    "done": state.done,
+                    "total": state.total,
+                }));
+            } else if state.drawn {
+                clear();
+            }
+        }
+    }
+}
+
+/// Run `f`, which prints to stderr, without garbling the active bar.
+///
+/// The bar is cleared before and redrawn after `f` is run.
+pub fn suspend<R>(f: impl FnOnce() -> R) -> R {
+    let mut active = ACTIVE.lock().unwrap();
+    match *active {
+        Some(ref mut state) if state.drawn => {
+            clear();
+            let result = f();
+            draw(state);
+            result
+        }
+        _ => f(),
+    }
+}
+
+/// Draw the bar over the current line of stderr.
+///
+/// In JSON mode, a `progress` event is emitted instead.
+fn draw(state: &mut State) {
+    if json_enabled() {
+        emit(serde_json::json!({
+            "event": "progress",
+            "label": state.label,
+            "done": state.done,
+            "total": state.total,
+        }));
+        return;
+    }
+    let filled = state.done * WIDTH / state.total;
+    let mut stderr = std::io::stderr();
+    let _ = write!(
+        stderr,
+        "\r\x1B[2K{:>12} [{}{}] {}/{}",
+        state.label,
+        "=".repeat(filled),
+        " ".repeat(WIDTH - filled),
+        state.done,
+        state.total
+    );
+    let _ = stderr.flush();
+    state.drawn = true;
+}
+
+/// Clear the current line of stderr.
+fn clear() {
+    let mut stderr = std::io::stderr();
+    let _ = write!(stderr, "\r\x1B[2K");
+    let _ = stderr.flush();
+}
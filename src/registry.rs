@@ -0,0 +1,120 @@
+// Copyright (c) 2024 ETH Zurich
+
+//! A minimal read-only package registry client.
+//!
+//! A registry is an index that is either a local directory or an HTTP(S) URL.
+//! For every package, the index provides a list of available versions at
+//! `<index>/<name>.json`, in the form `{"versions": ["1.0.0", "1.2.0"]}`, and
+//! the manifest of every version at `<index>/<name>/<version>/Bender.yml`.
+//! HTTP(S) indices are fetched with the external `curl` command.
+
+#![deny(missing_docs)]
+
+use std::path::Path;
+
+use serde::Deserialize;
+use tokio::process::Command;
+
+use crate::error::*;
+
+/// A package registry.
+#[derive(Copy, Clone, Debug)]
+pub struct Registry<'ctx> {
+    /// The URL or path of the registry index.
+    pub index: &'ctx str,
+}
+
+/// The index entry of a package.
+#[derive(Deserialize)]
+struct IndexEntry {
+    versions: Vec<String>,
+}
+
+impl<'ctx> Registry<'ctx> {
+    /// Create a new registry client for an index.
+    pub fn new(index: &'ctx str) -> Registry<'ctx> {
+        Registry { index }
+    }
+
+    /// Determine the versions of a package available in the registry.
+    ///
+    /// The versions are returned newest first.
+    pub async fn versions(self, name: &str) -> Result<Vec<semver::Version>> {
+        let data = self.fetch(&format!("{}.json", name)).await?;
+        let entry: IndexEntry = serde_json::from_str(&data).map_err(|cause| {
+            Error::chain(
+                format!("Syntax error in registry index of package `{}`.", name),
+                cause,
+            )
+        })?;
+        let mut versions = entry
+            .versions
+            .iter()
+            .map(|v| {
+                semver::Version::parse(v).map_err(|cause| {
+                    Error::chain(
+                        format!(
+                            "Registry index of package `{}` lists invalid version `{}`.",
+                            name, v
+                        ),
+                        cause,
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        versions.sort_by(|a, b| b.cmp(a));
+        versions.dedup();
+        Ok(versions)
+    }
+
+    /// Obtain the manifest of a specific version of a package.
+    pub async fn manifest(self, name: &str, version: &str) -> Result<String> {
+        self.fetch(&format!("{}/{}/Bender.yml", name, version))
+            .await
+    }
+
+    /// Fetch a file from the registry index.
+    async fn fetch(self, file: &str) -> Result<String> {
+        let url = format!("{}/{}", self.index.trim_end_matches('/'), file);
+        if url.starts_with("http://") || url.starts_with("https://") {
+            debugln!("registry: fetching {}", url);
+            let output = Command::new("curl")
+                .arg("--fail")
+                .arg("--silent")
+                .arg("--show-error")
+                .arg("--location")
+                .arg(&url)
+                .output()
+                .await
+                .map_err(|cause| {
+                    if cause.kind() == std::io::ErrorKind::NotFound {
+                        Error::new(format!(
+                            "Cannot fetch `{}`, since HTTP(S) registries require the `curl` command, which was not found. Install `curl` or use a local registry directory.",
+                            url
+                        ))
+                    } else {
+                        Error::chain("Failed to spawn `curl`.", cause)
+                    }
+                })?;
+            if !output.status.success() {
+                return Err(Error::new(format!(
+                    "Failed to fetch `{}` from the registry:\n\n{}",
+                    url,
+                    String::from_utf8_lossy(&output.stderr)
+                )));
+            }
+            String::from_utf8(output.stdout).map_err(|cause| {
+                Error::chain(
+                    format!("Registry file `{}` is not valid UTF-8.", url),
+                    cause,
+                )
+            })
+        } else {
+            let path = Path::new(url.strip_prefix("file://").unwrap_or(&url));
+            debugln!("registry: reading {:?}", path);
+            std::fs::read_to_string(path).map_err(|cause| {
+                Error::chain(format!("Cannot read registry file {:?}.", path), cause)
+            })
+        }
+    }
+}
@@ -5,10 +5,12 @@
 
 #![deny(missing_docs)]
 
+use std::collections::BTreeSet;
 use std::fmt;
 use std::fmt::Write as _;
 use std::fs;
 use std::mem;
+use std::path::{Path, PathBuf};
 
 use futures::future::join_all;
 use indexmap::{IndexMap, IndexSet};
@@ -22,10 +24,12 @@ use std::io::{self, Write};
 
 use crate::config::{self, Manifest};
 use crate::error::*;
+use crate::progress::Progress;
 use crate::sess::{
     self, DependencyConstraint, DependencyRef, DependencyVersion, DependencyVersions, Session,
     SessionIo,
 };
+use crate::target::TargetSet;
 
 /// A dependency resolver.
 pub struct DependencyResolver<'ctx> {
@@ -37,17 +41,116 @@ pub struct DependencyResolver<'ctx> {
     decisions: IndexMap<&'ctx str, DependencyConstraint>,
     /// Checkout Directory overrides in case checkout_dir is defined and contains folders.
     checked_out: IndexMap<String, config::Dependency>,
+    /// The locked revisions to restrict resolution to, if any.
+    locked_revisions: Option<IndexMap<String, Option<String>>>,
+    /// The revisions pinned by the lockfiles of path dependencies, as tuples
+    /// of the pinned dependency, the package whose lockfile pins it, and the
+    /// revision.
+    nested_locks: IndexSet<(String, &'ctx str, String)>,
+    /// The previously locked versions to guard against downgrades, if any.
+    previous_versions: Option<IndexMap<String, semver::Version>>,
+    /// Whether downgrades are accepted without confirmation.
+    allow_downgrade: bool,
+    /// The targets to select the target-specific overrides with, if any.
+    targets: Option<TargetSet>,
+    /// The file to write a reproduction archive to if resolution fails.
+    repro: Option<PathBuf>,
 }
 
 impl<'ctx> DependencyResolver<'ctx> {
     /// Create a new dependency resolver.
-    pub fn new(sess: &'ctx Session<'ctx>) -> DependencyResolver<'ctx> {
+    ///
+    /// The overrides restricted to certain targets only apply if `targets` is
+    /// given and matches them.
+    pub fn new(sess: &'ctx Session<'ctx>, targets: Option<TargetSet>) -> DependencyResolver<'ctx> {
         // TODO: Populate the table with the contents of the lock file.
         DependencyResolver {
             sess,
             table: IndexMap::new(),
             decisions: IndexMap::new(),
             checked_out: IndexMap::new(),
+            locked_revisions: None,
+            nested_locks: IndexSet::new(),
+            previous_versions: None,
+            allow_downgrade: false,
+            targets,
+            repro: None,
+        }
+    }
+
+    /// Write a reproduction archive to `path` if resolution fails.
+    ///
+    /// The archive contains the root manifest, the lockfile, and the manifests
+    /// of all versions of the dependencies involved. The git dependencies are
+    /// converted into a local registry, such that the failure can be
+    /// reproduced offline with `bender --local update`.
+    pub fn repro_on_failure(&mut self, path: &Path) {
+        self.repro = Some(path.to_path_buf());
+    }
+
+    /// Guard against downgrading the versions recorded in a lockfile.
+    ///
+    /// If resolution picks an older version of a dependency than the lockfile,
+    /// the user is asked for confirmation when attached to a terminal, and
+    /// resolution fails otherwise, unless `allow` is set.
+    pub fn guard_downgrades(&mut self, locked: &config::Locked, allow: bool) {
+        self.previous_versions = Some(
+            locked
+                .packages
+                .iter()
+                .filter_map(|(name, pkg)| {
+                    let version = semver::Version::parse(pkg.version.as_ref()?).ok()?;
+                    Some((name.clone(), version))
+                })
+                .collect(),
+        );
+        self.allow_downgrade = allow;
+    }
+
+    /// Restrict resolution to the revisions recorded in a lockfile.
+    ///
+    /// Dependencies may then only resolve to their locked revision, and
+    /// resolution fails if the constraints cannot be satisfied that way.
+    pub fn restrict_to_locked(&mut self, locked: &config::Locked) {
+        self.locked_revisions = Some(
+            locked
+                .packages
+                .iter()
+                .map(|(name, pkg)| (name.clone(), pkg.revision.clone()))
+                .collect(),
+        );
+    }
+
+    /// Determine the version indices a dependency is restricted to by the lockfile.
+    ///
+    /// Returns `None` if resolution is not restricted to the lockfile or the
+    /// source is not a git dependency.
+    fn locked_indices(
+        &self,
+        name: &str,
+        src: &DependencySource<'ctx>,
+    ) -> Result<Option<IndexSet<usize>>> {
+        let locked = match (&self.locked_revisions, &src.versions) {
+            (Some(locked), DependencyVersions::Git(..) | DependencyVersions::Registry(..)) => {
+                locked
+            }
+            _ => return Ok(None),
+        };
+        match (locked.get(name), &src.versions) {
+            (Some(Some(rev)), DependencyVersions::Git(gv)) => Ok(Some(
+                gv.revs.iter().position(|r| r == rev).into_iter().collect(),
+            )),
+            (Some(Some(rev)), DependencyVersions::Registry(rv)) => Ok(Some(
+                rv.versions
+                    .iter()
+                    .position(|(_, v)| v == rev)
+                    .into_iter()
+                    .collect(),
+            )),
+            _ => Err(Error::new(format!(
+                "Dependency `{}` has no locked revision, but resolution is restricted to the lockfile.",
+                name
+            ))),
         }
     }
 
@@ -59,14 +162,10 @@ impl<'ctx> DependencyResolver<'ctx> {
         })
     }
 
-    /// Resolve dependencies.
-    pub fn resolve(mut self) -> Result<config::Locked> {
-        let rt = Runtime::new()?;
-        let io = SessionIo::new(self.sess);
-
-        // Store path dependencies already in checkout_dir
+    /// Store path dependencies already in checkout_dir.
+    fn load_checked_out(&mut self) {
         if let Some(checkout) = self.sess.manifest.workspace.checkout_dir.clone() {
-            if checkout.exists() {
+            if checkout.is_dir() {
                 for dir in fs::read_dir(checkout).unwrap() {
                     self.checked_out.insert(
                         dir.as_ref()
@@ -82,21 +181,26 @@ impl<'ctx> DependencyResolver<'ctx> {
                 }
             }
         }
+    }
+
+    /// Fill the version table and pick a version for each dependency.
+    fn resolve_table(&mut self, rt: &Runtime, io: &SessionIo<'ctx, 'ctx>) -> Result<()> {
+        self.load_checked_out();
 
         // Load the plugin dependencies.
         self.register_dependencies_in_manifest(
             &self.sess.config.plugins,
             self.sess.manifest,
-            &rt,
-            &io,
+            rt,
+            io,
         )?;
 
         // Load the dependencies in the root manifest.
         self.register_dependencies_in_manifest(
             &self.sess.manifest.dependencies,
             self.sess.manifest,
-            &rt,
-            &io,
+            rt,
+            io,
         )?;
 
         let mut iteration = 0;
@@ -114,18 +218,61 @@ impl<'ctx> DependencyResolver<'ctx> {
 
             // Go through each dependency's versions and apply the constraints
             // imposed by the others.
-            self.mark(&rt, &io)?;
+            self.mark(rt, io)?;
 
             // Pick a version for each dependency.
             any_changes = self.pick()?;
 
             // Close the dependency set.
-            self.close(&rt, &io)?;
+            self.close(rt, io)?;
         }
         debugln!("resolve: resolved after {} iterations", iteration);
+        Ok(())
+    }
 
-        // Convert the resolved dependencies into a lockfile.
+    /// Resolve dependencies.
+    pub fn resolve(mut self) -> Result<config::Locked> {
+        let rt = Runtime::new()?;
+        let io = SessionIo::new(self.sess);
+
+        if let Err(e) = self.resolve_table(&rt, &io) {
+            if let Some(path) = self.repro.take() {
+                match self.write_repro(&rt, &io, &path) {
+                    Ok(()) => {
+                        noteln!("Wrote reproduction archive to {:?}.", path);
+                    }
+                    Err(cause) => {
+                        warnln!("Failed to write reproduction archive: {}", cause);
+                    }
+                }
+            }
+            return Err(e);
+        }
+
+        // Merge the sparse checkouts of each git dependency requested by the
+        // packages depending on it. A single package requiring the full
+        // checkout disables the sparse checkout.
         let sess = self.sess;
+        let mut sparse: IndexMap<&str, Option<BTreeSet<String>>> = IndexMap::new();
+        let manifests = self.table.values().filter_map(|dep| dep.manifest);
+        for manifest in std::iter::once(sess.manifest).chain(manifests) {
+            for name in manifest.dependencies.keys() {
+                let paths = manifest.sparse.get(name);
+                let entry = sparse
+                    .entry(name.as_str())
+                    .or_insert_with(|| paths.map(|_| BTreeSet::new()));
+                match paths {
+                    Some(paths) => {
+                        if let Some(set) = entry {
+                            set.extend(paths.iter().cloned());
+                        }
+                    }
+                    None => *entry = None,
+                }
+            }
+        }
+
+        // Convert the resolved dependencies into a lockfile.
         let packages = self
             .table
             .into_iter()
@@ -147,13 +294,19 @@ impl<'ctx> DependencyResolver<'ctx> {
                             version: None,
                             source: config::LockedSource::Path(path),
                             dependencies: deps,
+                            sparse: None,
                         }
                     }
-                    DependencyVersions::Registry(ref _rv) => {
-                        return Err(Error::new(format!(
-                            "Registry dependencies such as `{}` not yet supported.",
-                            name
-                        )));
+                    DependencyVersions::Registry(ref rv) => {
+                        let index = sess.registry()?.index.to_string();
+                        let version = rv.versions[src.state.pick().unwrap()].1;
+                        config::LockedPackage {
+                            revision: Some(String::from(version)),
+                            version: Some(String::from(version)),
+                            source: config::LockedSource::Registry(index),
+                            dependencies: deps,
+                            sparse: None,
+                        }
                     }
                     DependencyVersions::Git(ref gv) => {
                         let url = match sess_src {
@@ -174,13 +327,199 @@ impl<'ctx> DependencyResolver<'ctx> {
                             version,
                             source: config::LockedSource::Git(url),
                             dependencies: deps,
+                            sparse: sparse
+                                .get(name)
+                                .cloned()
+                                .flatten()
+                                .map(|paths| paths.into_iter().collect()),
                         }
                     }
                 };
                 Ok((name.to_string(), pkg))
             })
             .collect::<Result<_>>()?;
-        Ok(config::Locked { packages })
+        let locked = config::Locked { packages };
+        check_duplicate_urls(&locked, sess.config.strict)?;
+        check_downgrades(
+            self.previous_versions.as_ref(),
+            self.allow_downgrade,
+            &locked,
+        )?;
+        Ok(locked)
+    }
+
+    /// Write an archive to reproduce the resolution offline.
+    fn write_repro(&self, rt: &Runtime, io: &SessionIo<'ctx, 'ctx>, path: &Path) -> Result<()> {
+        // Gather the manifests of all versions of the involved dependencies.
+        let mut registry: IndexMap<&str, IndexMap<semver::Version, Option<String>>> =
+            IndexMap::new();
+        for (&name, dep) in &self.table {
+            for src in dep.sources.values() {
+                let versions: Vec<_> = match src.versions {
+                    DependencyVersions::Git(ref gv) => gv
+                        .versions
+                        .iter()
+                        .map(|(v, rev)| (v, DependencyVersion::Git(rev)))
+                        .collect(),
+                    DependencyVersions::Registry(ref rv) => rv
+                        .versions
+                        .iter()
+                        .map(|(v, ver)| (v, DependencyVersion::Registry(ver)))
+                        .collect(),
+                    DependencyVersions::Path => continue,
+                };
+                for (version, dep_version) in versions {
+                    let data = rt.block_on(io.dependency_manifest_data(src.id, dep_version))?;
+                    registry
+                        .entry(name)
+                        .or_default()
+                        .insert(version.clone(), data);
+                }
+            }
+        }
+
+        // Assemble the archive contents in a temporary directory.
+        let tmp = tempfile::tempdir()
+            .map_err(|cause| Error::chain("Failed to create temporary directory.", cause))?;
+        let dir = tmp.path().join("bender-repro");
+        let write = |path: PathBuf, data: String| -> Result<()> {
+            fs::create_dir_all(path.parent().unwrap()).map_err(|cause| {
+                Error::chain(format!("Failed to create directory {:?}.", path), cause)
+            })?;
+            fs::write(&path, data)
+                .map_err(|cause| Error::chain(format!("Failed to write {:?}.", path), cause))
+        };
+        let root_manifest = self.sess.manifest_path;
+        let data = fs::read_to_string(root_manifest).map_err(|cause| {
+            Error::chain(format!("Cannot read manifest {:?}.", root_manifest), cause)
+        })?;
+        write(dir.join("Bender.yml"), offline_manifest(&data)?)?;
+        let lockfile = self.sess.lock_path;
+        if lockfile.exists() {
+            let data = fs::read_to_string(lockfile).map_err(|cause| {
+                Error::chain(format!("Cannot read lockfile {:?}.", lockfile), cause)
+            })?;
+            write(dir.join("Bender.lock"), data)?;
+        }
+        write(
+            dir.join("Bender.local"),
+            String::from(
+                "# Resolve the dependencies offline from the manifests in the archive.\n\
+                 registry: registry\n",
+            ),
+        )?;
+        for (name, versions) in registry {
+            let index = serde_json::json!({
+                "versions": versions.keys().map(|v| v.to_string()).collect::<Vec<_>>()
+            });
+            write(
+                dir.join("registry").join(format!("{}.json", name)),
+                format!("{:#}\n", index),
+            )?;
+            for (version, data) in versions {
+                let manifest = match data {
+                    Some(data) => offline_manifest(&data)?,
+                    None => format!("package:\n  name: {}\n", name),
+                };
+                write(
+                    dir.join("registry")
+                        .join(name)
+                        .join(version.to_string())
+                        .join("Bender.yml"),
+                    manifest,
+                )?;
+            }
+        }
+
+        let output = std::process::Command::new("tar")
+            .arg("-czf")
+            .arg(path)
+            .arg("-C")
+            .arg(tmp.path())
+            .arg("bender-repro")
+            .output()
+            .map_err(|cause| Error::chain("Failed to spawn `tar`.", cause))?;
+        if !output.status.success() {
+            return Err(Error::new(format!(
+                "Failed to create archive {:?}:\n\n{}",
+                path,
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check whether a version of a dependency satisfies the requirements on it.
+    ///
+    /// The requirements are gathered from the root manifest and the manifests
+    /// of the locked packages, in the same way as during resolution. Returns
+    /// each requiring package and its requirement, together with whether the
+    /// version satisfies it. Neither the session nor the lockfile is modified.
+    pub fn explain(
+        mut self,
+        name: &str,
+        version: &semver::Version,
+    ) -> Result<Vec<(&'ctx str, DependencyConstraint, bool)>> {
+        let rt = Runtime::new()?;
+        let io = SessionIo::new(self.sess);
+        self.load_checked_out();
+
+        // Load the manifests of the locked packages.
+        let manifests: Vec<_> = self
+            .sess
+            .packages()
+            .iter()
+            .flatten()
+            .map(|&id| io.dependency_manifest_version(id, self.sess.dependency(id).version()))
+            .collect();
+        let manifests = rt
+            .block_on(join_all(manifests))
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+        let mut cons_map = self.gather_constraints(
+            std::iter::once(self.sess.manifest).chain(manifests.into_iter().flatten()),
+        );
+        let cons = match cons_map.swap_remove(name) {
+            Some(cons) => cons,
+            None => return Err(Error::new(format!("No package depends on `{}`.", name))),
+        };
+
+        // Determine the revision of the version in question.
+        let id = self.sess.dependency_with_name(name)?;
+        let mut src = DependencySource::new(id, rt.block_on(io.dependency_versions(id, false))?);
+        let mut index = version_index(&src.versions, version);
+        if index.is_none() {
+            src.versions = rt.block_on(io.dependency_versions(id, true))?;
+            index = version_index(&src.versions, version);
+        }
+        let index = match (index, &src.versions) {
+            (_, DependencyVersions::Path) => {
+                return Err(Error::new(format!(
+                    "`{}` is a path dependency and has no versions.",
+                    name
+                )))
+            }
+            (Some(index), _) => index,
+            (None, _) => {
+                return Err(Error::new(format!(
+                    "Version {} of `{}` does not exist.",
+                    version, name
+                )))
+            }
+        };
+
+        // Evaluate the requirements one by one.
+        Ok(cons
+            .into_iter()
+            .map(|(pkg_name, con)| {
+                let satisfied = match self.req_indices(name, &con, &src) {
+                    Ok(Some(ids)) => ids.contains(&index),
+                    Ok(None) => true,
+                    Err(_) => false,
+                };
+                (pkg_name, con, satisfied)
+            })
+            .collect())
     }
 
     fn register_dependency(
@@ -212,7 +551,11 @@ impl<'ctx> DependencyResolver<'ctx> {
             .map(|(name, dep)| {
                 let name = name.as_str();
                 let dep = self.checked_out.get(name).unwrap_or(dep);
-                let dep = self.sess.config.overrides.get(name).unwrap_or(dep);
+                let dep = self
+                    .sess
+                    .config
+                    .override_for(name, self.targets.as_ref())
+                    .unwrap_or(dep);
                 (name, self.sess.load_dependency(name, dep, manifest))
             })
             .collect();
@@ -221,12 +564,14 @@ impl<'ctx> DependencyResolver<'ctx> {
         // debugln!("resolve: dep ids {:?}", ids);
 
         // Determine the available versions for the dependencies.
+        let progress = Progress::new("Fetching", ids.len());
+        let progress = &progress;
         let versions: Vec<_> = ids
             .iter()
             .map(|&id| async move {
-                io.dependency_versions(id, false)
-                    .await
-                    .map(move |v| (id, v))
+                let versions = io.dependency_versions(id, false).await;
+                progress.inc();
+                versions.map(move |v| (id, v))
             })
             .collect();
         let versions: IndexMap<_, _> = rt
@@ -263,7 +608,8 @@ impl<'ctx> DependencyResolver<'ctx> {
     /// This populates the dependency's set of possible versions with all
     /// available versions, such that they may then be constrained.
     fn init(&mut self) -> Result<()> {
-        for dep in self.table.values_mut() {
+        let mut table = mem::take(&mut self.table);
+        let result = table.values_mut().try_for_each(|dep| {
             for src in dep.sources.values_mut() {
                 if !src.state.is_open() {
                     continue;
@@ -271,18 +617,60 @@ impl<'ctx> DependencyResolver<'ctx> {
                 debugln!("resolve: initializing `{}[{}]`", dep.name, src.id);
                 let ids = match src.versions {
                     DependencyVersions::Path => (0..1).collect(),
-                    DependencyVersions::Registry(ref _rv) => {
-                        return Err(Error::new(format!(
-                            "Resolution of registry dependency `{}` not yet implemented",
-                            dep.name
-                        )));
+                    DependencyVersions::Registry(ref rv) => {
+                        match self.locked_indices(dep.name, src)? {
+                            Some(ids) => ids,
+                            None => (0..rv.versions.len()).collect(),
+                        }
                     }
-                    DependencyVersions::Git(ref gv) => (0..gv.revs.len()).collect(),
+                    DependencyVersions::Git(ref gv) => match self.locked_indices(dep.name, src)? {
+                        Some(ids) => ids,
+                        None => (0..gv.revs.len()).collect(),
+                    },
                 };
                 src.state = State::Constrained(ids);
             }
+            Ok(())
+        });
+        self.table = table;
+        result
+    }
+
+    /// Gather the constraints imposed by a set of manifests, grouped by dependency.
+    fn gather_constraints(
+        &self,
+        manifests: impl Iterator<Item = &'ctx Manifest>,
+    ) -> IndexMap<&'ctx str, Vec<(&'ctx str, DependencyConstraint)>> {
+        let mut map = IndexMap::<&str, Vec<(&str, DependencyConstraint)>>::new();
+        let dep_iter = manifests
+            .flat_map(|m| {
+                let pkg_name = self.sess.intern_string(m.package.name.clone());
+                m.dependencies.iter().map(move |(n, d)| (n, (pkg_name, d)))
+            })
+            .map(|(name, (pkg_name, dep))| {
+                (name, (pkg_name, self.checked_out.get(name).unwrap_or(dep)))
+            })
+            .map(|(name, (pkg_name, dep))| {
+                (
+                    name,
+                    pkg_name,
+                    self.sess
+                        .config
+                        .override_for(name, self.targets.as_ref())
+                        .unwrap_or(dep),
+                )
+            });
+        for (name, pkg_name, dep) in dep_iter {
+            let v = map.entry(name.as_str()).or_default();
+            v.push((pkg_name, DependencyConstraint::from(dep)));
         }
-        Ok(())
+        // Path dependencies with their own lockfile pin their dependencies.
+        for (name, pkg_name, rev) in &self.nested_locks {
+            if let Some(v) = map.get_mut(name.as_str()) {
+                v.push((pkg_name, DependencyConstraint::Revision(rev.clone())));
+            }
+        }
+        map
     }
 
     /// Apply constraints to each dependency's versions.
@@ -291,30 +679,9 @@ impl<'ctx> DependencyResolver<'ctx> {
 
         // Gather the constraints from the available manifests. Group them by
         // constraint.
-        let cons_map = {
-            let mut map = IndexMap::<&str, Vec<(&str, DependencyConstraint)>>::new();
-            let dep_iter = once(self.sess.manifest)
-                .chain(self.table.values().filter_map(|dep| dep.manifest))
-                .flat_map(|m| {
-                    let pkg_name = self.sess.intern_string(m.package.name.clone());
-                    m.dependencies.iter().map(move |(n, d)| (n, (pkg_name, d)))
-                })
-                .map(|(name, (pkg_name, dep))| {
-                    (name, (pkg_name, self.checked_out.get(name).unwrap_or(dep)))
-                })
-                .map(|(name, (pkg_name, dep))| {
-                    (
-                        name,
-                        pkg_name,
-                        self.sess.config.overrides.get(name).unwrap_or(dep),
-                    )
-                });
-            for (name, pkg_name, dep) in dep_iter {
-                let v = map.entry(name.as_str()).or_default();
-                v.push((pkg_name, DependencyConstraint::from(dep)));
-            }
-            map
-        };
+        let cons_map = self.gather_constraints(
+            once(self.sess.manifest).chain(self.table.values().filter_map(|dep| dep.manifest)),
+        );
 
         // // Gather the constraints from locked and picked dependencies.
         // for dep in self.table.values_mut() {
@@ -333,19 +700,20 @@ impl<'ctx> DependencyResolver<'ctx> {
             ConstraintsDumper(&cons_map)
         );
 
-        // Impose the constraints on the dependencies.
+        // Impose the constraints on the dependencies. The table is restored
+        // even if this fails, such that it can still be inspected.
         let mut table = mem::take(&mut self.table);
-        for (name, cons) in cons_map {
-            for (_, con) in &cons {
+        let result = cons_map.iter().try_for_each(|(&name, cons)| {
+            for (_, con) in cons {
                 debugln!("resolve: impose `{}` on `{}`", con, name);
                 for src in table.get_mut(name).unwrap().sources.values_mut() {
-                    self.impose(name, con, src, &cons, rt, io)?;
+                    self.impose(name, con, src, cons, rt, io)?;
                 }
             }
-        }
+            Ok(())
+        });
         self.table = table;
-
-        Ok(())
+        result
     }
 
     fn req_indices(
@@ -366,31 +734,15 @@ impl<'ctx> DependencyResolver<'ctx> {
                     .enumerate()
                     .map(|(id, &hash)| (hash, id))
                     .collect();
-                let mut revs_tmp: IndexMap<_, _> = gv
-                    .versions
-                    .iter()
-                    .sorted()
-                    .filter_map(
-                        |&(ref v, h)| {
-                            if con.matches(v) {
-                                Some((v, h))
-                            } else {
-                                None
-                            }
-                        },
-                    )
-                    .collect();
-                revs_tmp.reverse();
-                let revs: IndexSet<usize> = revs_tmp
-                    .iter()
-                    .filter_map(|(v, h)| {
-                        if con.matches(v) {
-                            Some(hash_ids[h])
-                        } else {
-                            None
-                        }
-                    })
-                    .collect();
+                // Order the matching revisions such that the pick does not
+                // depend on the order of the revisions in the local clone:
+                // highest version first, where tags of equal precedence are
+                // ordered by their build metadata, then by the lexically
+                // smallest hash.
+                let mut revs_tmp: Vec<_> =
+                    gv.versions.iter().filter(|(v, _)| con.matches(v)).collect();
+                revs_tmp.sort_by(|(va, ha), (vb, hb)| vb.cmp(va).then_with(|| ha.cmp(hb)));
+                let revs: IndexSet<usize> = revs_tmp.iter().map(|(_, h)| hash_ids[h]).collect();
                 // debugln!("resolve: `{}` matches version requirement `{}` for revs {:?}", name, con, revs);
                 Ok(Some(revs))
             }
@@ -419,10 +771,20 @@ impl<'ctx> DependencyResolver<'ctx> {
                 // debugln!("resolve: `{}` matches revision `{}` for revs {:?}", name, con, revs);
                 Ok(Some(revs))
             }
-            (DepCon::Version(_con), DepVer::Registry(_rv)) => Err(Error::new(format!(
-                "Constraints on registry dependency `{}` not implemented",
-                name
-            ))),
+            (DepCon::Version(con), DepVer::Registry(rv)) => Ok(Some(
+                rv.versions
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, (v, _))| if con.matches(v) { Some(i) } else { None })
+                    .collect(),
+            )),
+            (DepCon::Revision(con), DepVer::Registry(rv)) => Ok(Some(
+                rv.versions
+                    .iter()
+                    .position(|&(_, v)| v == con)
+                    .into_iter()
+                    .collect(),
+            )),
 
             // Handle the error cases.
             // TODO: These need to improve a lot!
@@ -451,7 +813,7 @@ impl<'ctx> DependencyResolver<'ctx> {
         rt: &Runtime,
         io: &SessionIo<'ctx, 'ctx>,
     ) -> Result<()> {
-        let indices = match self.req_indices(name, con, src) {
+        let mut indices = match self.req_indices(name, con, src) {
             Ok(o) => match o {
                 Some(v) => v,
                 None => return Ok(()),
@@ -463,7 +825,7 @@ impl<'ctx> DependencyResolver<'ctx> {
         if indices.is_empty() {
             src.versions = rt.block_on(io.dependency_versions(src.id, true))?;
 
-            let indices = match self.req_indices(name, con, src) {
+            indices = match self.req_indices(name, con, src) {
                 Ok(o) => match o {
                     Some(v) => v,
                     None => return Ok(()),
@@ -478,6 +840,16 @@ impl<'ctx> DependencyResolver<'ctx> {
                     con
                 )));
             }
+
+            // The state refers to the versions before the fetch. Start over
+            // from all versions, the other constraints are imposed again in
+            // the next iteration.
+            let num_versions = match src.versions {
+                DependencyVersions::Path => 1,
+                DependencyVersions::Registry(ref rv) => rv.versions.len(),
+                DependencyVersions::Git(ref gv) => gv.revs.len(),
+            };
+            src.state = State::Constrained((0..num_versions).collect());
         }
 
         // Mark all other versions of the dependency as invalid.
@@ -541,7 +913,10 @@ impl<'ctx> DependencyResolver<'ctx> {
                         };
                         match self.req_indices(name, &decision, src) {
                             Ok(o) => match o {
-                                Some(v) => Ok(v),
+                                Some(v) => match self.locked_indices(name, src)? {
+                                    Some(locked) => Ok(v.intersection(&locked).copied().collect()),
+                                    None => Ok(v),
+                                },
                                 None => unreachable!(),
                             },
                             Err(e) => Err(e),
@@ -587,13 +962,12 @@ impl<'ctx> DependencyResolver<'ctx> {
                                 );
                                 State::Picked(0, IndexSet::new())
                             }
-                            DependencyVersions::Git(..) => {
+                            DependencyVersions::Git(..) | DependencyVersions::Registry(..) => {
                                 debugln!("resolve: picking version for `{}[{}]`", dep.name, src.id);
+                                // The candidates are ordered by preference,
+                                // see `req_indices`.
                                 State::Picked(ids.first().copied().unwrap(), ids.clone())
                             }
-                            DependencyVersions::Registry(..) => {
-                                return Err(Error::new(format!("Version picking for registry dependency `{}` not yet implemented", dep.name)));
-                            }
                         }
                     }
                     State::Picked(id, ref ids) => {
@@ -640,10 +1014,36 @@ impl<'ctx> DependencyResolver<'ctx> {
         Ok(any_changes)
     }
 
+    /// Pin the dependencies of a path dependency to the revisions in its lockfile.
+    fn load_nested_lock(&mut self, name: &'ctx str) -> Result<()> {
+        let src = self.table[name].source();
+        let path = match self.sess.dependency_source(src.id) {
+            sess::DependencySource::Path(path) => path.join("Bender.lock"),
+            _ => return Ok(()),
+        };
+        if !path.exists() {
+            return Ok(());
+        }
+        let file = fs::File::open(&path)
+            .map_err(|cause| Error::chain(format!("Cannot open lockfile {:?}.", path), cause))?;
+        let locked: config::Locked = serde_yaml::from_reader(file).map_err(|cause| {
+            Error::chain(format!("Syntax error in lockfile {:?}.", path), cause)
+        })?;
+        for (dep_name, pkg) in locked.packages {
+            if let Some(rev) = pkg.revision {
+                debugln!("resolve: `{}` pins `{}` to `{}`", name, dep_name, rev);
+                self.nested_locks.insert((dep_name, name, rev));
+            }
+        }
+        Ok(())
+    }
+
     /// Close the set of dependencies.
     fn close(&mut self, rt: &Runtime, io: &SessionIo<'ctx, 'ctx>) -> Result<()> {
         debugln!("resolve: computing closure over dependencies");
         let manifests: Vec<(&str, Option<&Manifest>)> = {
+            let progress = Progress::new("Loading", self.table.len());
+            let progress = &progress;
             let mut sub_deps = Vec::new();
             for dep in self.table.values() {
                 let src = dep.source();
@@ -652,7 +1052,11 @@ impl<'ctx> DependencyResolver<'ctx> {
                     None => continue,
                 };
                 let manifest = io.dependency_manifest_version(src.id, version);
-                sub_deps.push(async move { manifest.await.map(move |m| (dep.name, m)) });
+                sub_deps.push(async move {
+                    let manifest = manifest.await;
+                    progress.inc();
+                    manifest.map(move |m| (dep.name, m))
+                });
             }
             rt.block_on(join_all(sub_deps))
                 .into_iter()
@@ -662,6 +1066,9 @@ impl<'ctx> DependencyResolver<'ctx> {
             if let Some(m) = manifest {
                 debugln!("resolve: for `{}` loaded manifest {:#?}", name, m);
                 self.register_dependencies_in_manifest(&m.dependencies, m, rt, io)?;
+                if self.sess.config.respect_nested_lock {
+                    self.load_nested_lock(name)?;
+                }
             }
             let existing = &mut self.table.get_mut(name).unwrap().manifest;
             *existing = manifest;
@@ -670,6 +1077,143 @@ impl<'ctx> DependencyResolver<'ctx> {
     }
 }
 
+/// Determine the index of a version among the versions of a dependency.
+fn version_index(versions: &DependencyVersions, version: &semver::Version) -> Option<usize> {
+    match versions {
+        DependencyVersions::Path => None,
+        DependencyVersions::Registry(rv) => rv.versions.iter().position(|(v, _)| v == version),
+        DependencyVersions::Git(gv) => gv
+            .versions
+            .iter()
+            .find(|(v, _)| v == version)
+            .and_then(|&(_, rev)| gv.revs.iter().position(|&r| r == rev)),
+    }
+}
+
+/// Check for git dependencies that are resolved under different names from
+/// the same URL.
+///
+/// Such packages are checked out separately, which typically leads to
+/// duplicate module definitions during elaboration.
+fn check_duplicate_urls(locked: &config::Locked, strict: bool) -> Result<()> {
+    let mut urls: IndexMap<&str, Vec<&str>> = IndexMap::new();
+    for (name, pkg) in &locked.packages {
+        if let config::LockedSource::Git(ref url) = pkg.source {
+            urls.entry(url.as_str()).or_default().push(name.as_str());
+        }
+    }
+    let duplicates: Vec<_> = urls
+        .iter()
+        .filter(|(_, names)| names.len() > 1)
+        .map(|(url, names)| {
+            let names: Vec<_> = names.iter().map(|name| format!("`{}`", name)).collect();
+            format!("\n- {} from {}", names.join(", "), url)
+        })
+        .collect();
+    if duplicates.is_empty() {
+        return Ok(());
+    }
+    let msg = format!(
+        "Multiple dependencies resolve to the same git repository:{}",
+        duplicates.join("")
+    );
+    if strict {
+        return Err(Error::new(format!(
+            "{}\n\nRename the dependencies to use a single name.",
+            msg
+        )));
+    }
+    warnln!("{}", msg);
+    Ok(())
+}
+
+/// Convert the git dependencies of a manifest into registry dependencies.
+///
+/// Used for reproduction archives, which contain the manifests of all versions
+/// of the git dependencies in a local registry. Dependencies on a specific
+/// revision or path cannot be converted and are kept.
+fn offline_manifest(data: &str) -> Result<String> {
+    let mut manifest: serde_yaml::Value = serde_yaml::from_str(data)
+        .map_err(|cause| Error::chain("Syntax error in manifest.", cause))?;
+    let package = manifest["package"]["name"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    if let Some(deps) = manifest
+        .get_mut("dependencies")
+        .and_then(|deps| deps.as_mapping_mut())
+    {
+        for (name, dep) in deps.iter_mut() {
+            if dep.is_string() {
+                continue;
+            }
+            match (dep.get("git"), dep.get("version").and_then(|v| v.as_str())) {
+                (Some(_), Some(version)) => *dep = version.into(),
+                _ => {
+                    warnln!(
+                        "Dependency `{}` of package `{}` cannot be resolved offline and is kept as is.",
+                        name.as_str().unwrap_or_default(),
+                        package
+                    );
+                }
+            }
+        }
+    }
+    serde_yaml::to_string(&manifest)
+        .map_err(|cause| Error::chain("Failed to serialize manifest.", cause))
+}
+
+/// Check the resolved packages for downgrades.
+fn check_downgrades(
+    previous: Option<&IndexMap<String, semver::Version>>,
+    allow: bool,
+    locked: &config::Locked,
+) -> Result<()> {
+    let previous = match previous {
+        Some(previous) => previous,
+        None => return Ok(()),
+    };
+    let downgrades: Vec<_> = locked
+        .packages
+        .iter()
+        .filter_map(|(name, pkg)| {
+            let old = previous.get(name)?;
+            let new = semver::Version::parse(pkg.version.as_ref()?).ok()?;
+            if new < *old {
+                Some(format!("\n- `{}` from {} to {}", name, old, new))
+            } else {
+                None
+            }
+        })
+        .collect();
+    if downgrades.is_empty() {
+        return Ok(());
+    }
+    let msg = format!("Resolution downgrades dependencies:{}", downgrades.join(""));
+    if allow {
+        warnln!("{}", msg);
+        return Ok(());
+    }
+    // Let user confirm the downgrade if both stderr and stdin go to a TTY.
+    if std::io::stderr().is_terminal() && std::io::stdin().is_terminal() {
+        eprint!("{}\n\nProceed with the downgrade? [y/N] ", msg);
+        io::stderr()
+            .flush()
+            .map_err(|cause| Error::chain("Failed to write the downgrade prompt.", cause))?;
+        let mut buffer = String::new();
+        io::stdin()
+            .read_line(&mut buffer)
+            .map_err(|cause| Error::chain("Failed to read the downgrade confirmation.", cause))?;
+        if matches!(buffer.trim(), "y" | "Y" | "yes") {
+            return Ok(());
+        }
+    }
+    Err(Error::new(format!(
+        "{}\n\nPass `--allow-downgrade` to accept the downgrade.",
+        msg
+    )))
+}
+
 /// A dependency in the version table.
 ///
 /// One such entry exists per dependency name. Note that multiple sources may
@@ -756,7 +1300,9 @@ impl<'ctx> DependencySource<'ctx> {
             State::Open | State::Constrained(..) => None,
             State::Locked(id) | State::Picked(id, _) => match self.versions {
                 DependencyVersions::Path => Some(DependencyVersion::Path),
-                DependencyVersions::Registry(ref _rv) => None,
+                DependencyVersions::Registry(ref rv) => {
+                    Some(DependencyVersion::Registry(rv.versions[id].1))
+                }
                 DependencyVersions::Git(ref gv) => Some(DependencyVersion::Git(gv.revs[id])),
             },
         }
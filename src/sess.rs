@@ -34,7 +34,9 @@ use crate::config::{self, Config, Manifest};
 use crate::error::*;
 // use crate::future_throttle::FutureThrottle;
 use crate::git::Git;
-use crate::src::SourceGroup;
+use crate::progress::Progress;
+use crate::registry::Registry;
+use crate::src::{SourceFile, SourceGroup};
 use crate::target::TargetSpec;
 use crate::util::try_modification_time;
 
@@ -45,6 +47,10 @@ use crate::util::try_modification_time;
 pub struct Session<'ctx> {
     /// The path of the package within which the tool was executed.
     pub root: &'ctx Path,
+    /// The path of the root package's manifest, usually `Bender.yml`.
+    pub manifest_path: &'ctx Path,
+    /// The path of the root package's lockfile, usually `Bender.lock`.
+    pub lock_path: &'ctx Path,
     /// The manifest of the root package.
     pub manifest: &'ctx Manifest,
     /// The tool configuration.
@@ -78,20 +84,28 @@ pub struct Session<'ctx> {
     // git_throttle: FutureThrottle,
     /// A toggle to disable remote fetches & clones
     pub local_only: bool,
+    /// An alternative base directory for the checkouts of git dependencies.
+    pub checkout_prefix: Option<PathBuf>,
 }
 
-impl<'sess, 'ctx: 'sess> Session<'ctx> {
+impl<'ctx> Session<'ctx> {
     /// Create a new session.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         root: &'ctx Path,
+        manifest_path: &'ctx Path,
+        lock_path: &'ctx Path,
         manifest: &'ctx Manifest,
         config: &'ctx Config,
         arenas: &'ctx SessionArenas,
         local_only: bool,
         force_fetch: bool,
+        checkout_prefix: Option<PathBuf>,
     ) -> Session<'ctx> {
         Session {
             root,
+            manifest_path,
+            lock_path,
             manifest,
             config,
             arenas,
@@ -99,7 +113,7 @@ impl<'sess, 'ctx: 'sess> Session<'ctx> {
                 if force_fetch {
                     Some(SystemTime::now())
                 } else {
-                    try_modification_time(root.join("Bender.yml"))
+                    try_modification_time(manifest_path)
                 }
             },
             stats: Default::default(),
@@ -114,6 +128,7 @@ impl<'sess, 'ctx: 'sess> Session<'ctx> {
             cache: Default::default(),
             // git_throttle: FutureThrottle::new(8),
             local_only,
+            checkout_prefix,
         }
     }
 
@@ -143,6 +158,7 @@ impl<'sess, 'ctx: 'sess> Session<'ctx> {
                 source: src,
                 revision: None,
                 version: None,
+                sparse: None,
             }))
     }
 
@@ -169,6 +185,7 @@ impl<'sess, 'ctx: 'sess> Session<'ctx> {
                         .version
                         .as_ref()
                         .map(|s| semver::Version::parse(s).unwrap()),
+                    sparse: pkg.sparse.clone(),
                 }),
             );
             graph_names.insert(id, &pkg.dependencies);
@@ -210,9 +227,11 @@ impl<'sess, 'ctx: 'sess> Session<'ctx> {
             for name in self.manifest.dependencies.keys() {
                 if !(names.contains_key(name)) {
                     return Err(Error::new(format!(
-                        "`Bender.yml` contains dependency `{}` but `Bender.lock` does not.\n\
+                        "`{}` contains dependency `{}` but `{}` does not.\n\
                         \tYou may need to run `bender update`.",
-                        name
+                        self.manifest_path.file_name().unwrap().to_string_lossy(),
+                        name,
+                        self.lock_path.display()
                     )));
                 }
             }
@@ -327,6 +346,36 @@ impl<'sess, 'ctx: 'sess> Session<'ctx> {
         }
     }
 
+    /// Access the configured package registry.
+    pub fn registry(&self) -> Result<Registry<'ctx>> {
+        match self.config.registry {
+            Some(ref index) => Ok(Registry::new(index)),
+            None => Err(Error::new(
+                "Dependencies without `git` or `path` are resolved from a registry, but no `registry` is configured.",
+            )),
+        }
+    }
+
+    /// Check that the name of a dependency matches the name of its package.
+    ///
+    /// A mismatch breaks the propagation of `export_include_dirs`. It is
+    /// reported as a warning, or as an error if `strict_names` is enabled.
+    pub fn check_package_name(&self, dep_name: &str, manifest: &Manifest) -> Result<()> {
+        if dep_name == manifest.package.name {
+            return Ok(());
+        }
+        if self.config.strict_names {
+            Err(Error::new(format!(
+                "Dependency `{}` refers to package `{}`. Rename the dependency to match the package name.",
+                dep_name, manifest.package.name
+            )))
+        } else {
+            warnln!("Dependency name and package name do not match for {:?} / {:?}, this can cause unwanted behavior",
+                dep_name, manifest.package.name);
+            Ok(())
+        }
+    }
+
     /// Internalize a string.
     ///
     /// This allocates the string in the arena and returns a reference to it
@@ -365,6 +414,46 @@ impl<'sess, 'ctx: 'sess> Session<'ctx> {
         self.arenas.dependency_entry.alloc(entry)
     }
 
+    /// The directory holding the manifests of path dependencies extracted
+    /// from git dependencies during resolution.
+    pub fn tmp_manifest_dir(&self) -> PathBuf {
+        self.root.join(".bender").join("tmp")
+    }
+
+    /// Remove the manifests left behind in the tmp directory by a previous
+    /// resolution, such that stale copies cannot shadow the current ones.
+    pub fn prune_tmp_manifests(&self) -> Result<()> {
+        let tmp_dir = self.tmp_manifest_dir();
+        let entries = match std::fs::read_dir(&tmp_dir) {
+            Ok(entries) => entries,
+            Err(ref e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(cause) => {
+                return Err(Error::chain(
+                    format!("Cannot read directory {:?}.", tmp_dir),
+                    cause,
+                ))
+            }
+        };
+        for entry in entries {
+            let path = entry
+                .map_err(|cause| {
+                    Error::chain(format!("Cannot read directory {:?}.", tmp_dir), cause)
+                })?
+                .path();
+            let is_manifest = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.ends_with("_manifest.yml"));
+            if is_manifest && path.is_file() {
+                debugln!("sess: pruning stale manifest {:?}", path);
+                std::fs::remove_file(&path).map_err(|cause| {
+                    Error::chain(format!("Cannot remove stale manifest {:?}.", path), cause)
+                })?;
+            }
+        }
+        Ok(())
+    }
+
     /// Access the package dependency graph.
     pub fn graph(&self) -> Arc<IndexMap<DependencyRef, IndexSet<DependencyRef>>> {
         self.graph.lock().unwrap().clone()
@@ -376,6 +465,9 @@ impl<'sess, 'ctx: 'sess> Session<'ctx> {
     }
 
     /// Load the sources in a manifest into a source group.
+    ///
+    /// The contents of external file lists are added as subgroups after the
+    /// source files, which fails if a file list cannot be read.
     pub fn load_sources(
         &self,
         sources: &'ctx config::Sources,
@@ -383,7 +475,7 @@ impl<'sess, 'ctx: 'sess> Session<'ctx> {
         dependencies: IndexSet<String>,
         dependency_export_includes: IndexMap<String, IndexSet<&'ctx Path>>,
         version: Option<Version>,
-    ) -> SourceGroup<'ctx> {
+    ) -> Result<SourceGroup<'ctx>> {
         let include_dirs: IndexSet<&Path> =
             IndexSet::from_iter(sources.include_dirs.iter().map(|d| self.intern_path(d)));
         let defines = sources
@@ -396,33 +488,91 @@ impl<'sess, 'ctx: 'sess> Session<'ctx> {
                 )
             })
             .collect();
-        let files = sources
+        let headers = sources
+            .headers
+            .iter()
+            .map(|h| self.intern_path(h))
+            .collect();
+        let target_defines = sources
+            .target_defines
+            .iter()
+            .map(|(spec, defines)| {
+                (
+                    spec.clone(),
+                    defines
+                        .iter()
+                        .map(|(k, v)| {
+                            (
+                                self.intern_string(k),
+                                v.as_ref().map(|v| self.intern_string(v)),
+                            )
+                        })
+                        .collect(),
+                )
+            })
+            .collect();
+        let mut files = sources
             .files
             .iter()
             .map(|file| match *file {
-                config::SourceFile::File(ref path) => (path as &Path).into(),
-                config::SourceFile::Group(ref group) => self
+                config::SourceFile::File(ref path) => Ok((path as &Path).into()),
+                config::SourceFile::Group(ref group) => Ok(self
                     .load_sources(
                         group.as_ref(),
                         None,
                         dependencies.clone(),
                         dependency_export_includes.clone(),
                         version.clone(),
-                    )
-                    .into(),
+                    )?
+                    .into()),
             })
-            .collect();
-        SourceGroup {
+            .collect::<Result<Vec<SourceFile>>>()?;
+        for path in &sources.external_flists {
+            let flist = config::parse_flist(path)?;
+            files.push(
+                SourceGroup {
+                    package: None,
+                    independent: false,
+                    target: TargetSpec::Wildcard,
+                    include_dirs: flist
+                        .include_dirs
+                        .into_iter()
+                        .map(|d| self.intern_path(d))
+                        .collect(),
+                    export_incdirs: dependency_export_includes.clone(),
+                    defines: flist
+                        .defines
+                        .into_iter()
+                        .map(|(k, v)| (self.intern_string(k), v.map(|v| self.intern_string(v))))
+                        .collect(),
+                    target_defines: IndexMap::new(),
+                    headers: IndexSet::new(),
+                    library: None,
+                    files: flist
+                        .files
+                        .into_iter()
+                        .map(|f| SourceFile::File(self.intern_path(f)))
+                        .collect(),
+                    dependencies: dependencies.clone(),
+                    version: version.clone(),
+                }
+                .into(),
+            );
+        }
+        Ok(SourceGroup {
             package,
             independent: false,
             target: sources.target.clone(),
             include_dirs: include_dirs.clone(),
             export_incdirs: dependency_export_includes.clone(),
             defines,
+            target_defines,
+            headers,
+            library: sources.library.as_deref().map(|l| self.intern_string(l)),
             files,
             dependencies,
             version,
-        }
+        })
     }
 }
 
@@ -435,6 +585,9 @@ pub struct SessionIo<'sess, 'ctx: 'sess> {
     /// The underlying session.
     pub sess: &'sess Session<'ctx>,
     git_versions: Mutex<IndexMap<PathBuf, GitVersions<'ctx>>>,
+    /// The versions of each dependency, and whether they were obtained after
+    /// forcing a fetch of the remote.
+    dependency_versions: Mutex<IndexMap<DependencyRef, (bool, DependencyVersions<'ctx>)>>,
 }
 
 impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
@@ -443,6 +596,7 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
         SessionIo {
             sess,
             git_versions: Mutex::new(IndexMap::new()),
+            dependency_versions: Mutex::new(IndexMap::new()),
         }
     }
 
@@ -452,20 +606,47 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
         dep_id: DependencyRef,
         force_fetch: bool,
     ) -> Result<DependencyVersions<'ctx>> {
+        // Check if the versions are already in the cache. Versions obtained
+        // after a forced fetch also satisfy requests without one, but not the
+        // other way around.
+        if let Some((fetched, cached)) = self.dependency_versions.lock().unwrap().get(&dep_id) {
+            if *fetched || !force_fetch {
+                return Ok(cached.clone());
+            }
+        }
+
         self.sess.stats.num_calls_dependency_versions.increment();
         let dep = self.sess.dependency(dep_id);
-        match dep.source {
+        let versions = match dep.source {
             DependencySource::Registry => {
-                unimplemented!("determine available versions of registry dependency");
+                let versions = self.sess.registry()?.versions(&dep.name).await?;
+                Ok(DependencyVersions::Registry(RegistryVersions {
+                    versions: versions
+                        .into_iter()
+                        .map(|v| {
+                            let s = self.sess.intern_string(v.to_string());
+                            (v, s)
+                        })
+                        .collect(),
+                }))
             }
             DependencySource::Path(_) => Ok(DependencyVersions::Path),
             DependencySource::Git(ref url) => {
                 let db = self.git_database(&dep.name, url, force_fetch, None).await?;
+                // The versions of the database before the fetch are stale.
+                if force_fetch {
+                    self.git_versions.lock().unwrap().swap_remove(db.path);
+                }
                 self.git_versions_func(db)
                     .await
                     .map(DependencyVersions::Git)
             }
-        }
+        }?;
+        self.dependency_versions
+            .lock()
+            .unwrap()
+            .insert(dep_id, (force_fetch, versions.clone()));
+        Ok(versions)
     }
 
     /// Access the git database for a dependency.
@@ -485,11 +666,7 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
         //       whole process faster for later calls.
         self.sess.stats.num_calls_git_database.increment();
 
-        // Determine the name of the database as the given name and the first
-        // 8 bytes (16 hex characters) of the URL's BLAKE2 hash.
-        use blake2::{Blake2b512, Digest};
-        let hash = &format!("{:016x}", Blake2b512::digest(url.as_bytes()))[..16];
-        let db_name = format!("{}-{}", name, hash);
+        let db_name = git_db_name(name, url);
 
         // Determine the location of the git database and create it if its does
         // not yet exist.
@@ -510,7 +687,9 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
                 ))
             }
         };
-        let git = Git::new(db_dir, &self.sess.config.git);
+        let git = Git::new(db_dir, &self.sess.config.git)
+            .with_config(self.sess.config.git_config_for(url));
+        let remote = git.with_timeout(self.sess.config.git_timeout_secs);
         let name2 = String::from(name);
         let url = String::from(url);
         let url2 = url.clone();
@@ -519,10 +698,11 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
         // Either initialize the repository or update it if needed.
         if !db_dir.join("config").exists() {
             if self.sess.local_only {
-                return Err(Error::new(
-                    "Bender --local argument set, unable to initialize git dependency. \n\
+                return Err(Error::new(format!(
+                    "Bender --local argument set, unable to initialize git dependency `{}`. \n\
                     \tPlease update without --local, or provide a path to the missing dependency.",
-                ));
+                    name
+                )));
             }
             // Initialize.
             self.sess.stats.num_database_init.increment();
@@ -533,10 +713,10 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
             })
             .and_then(|_| git.spawn_with(|c| c.arg("init").arg("--bare")))
             .and_then(|_| git.spawn_with(|c| c.arg("remote").arg("add").arg("origin").arg(url)))
-            .and_then(|_| git.fetch("origin"))
+            .and_then(|_| remote.fetch("origin"))
             .and_then(|_| async {
                 if let Some(reference) = fetch_ref {
-                    git.fetch_ref("origin", reference).await
+                    remote.fetch_ref("origin", reference).await
                 } else {
                     Ok(())
                 }
@@ -566,10 +746,10 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
                 stageln!("Fetching", "{} ({})", name2, url2);
                 Ok(())
             })
-            .and_then(|_| git.fetch("origin"))
+            .and_then(|_| remote.fetch("origin"))
             .and_then(|_| async {
                 if let Some(reference) = fetch_ref {
-                    git.fetch_ref("origin", reference).await
+                    remote.fetch_ref("origin", reference).await
                 } else {
                     Ok(())
                 }
@@ -664,8 +844,7 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
                     versions.sort_by(|a, b| b.cmp(a));
 
                     // Merge tags and branches.
-                    let refs: IndexMap<&str, &str> =
-                        branches.into_iter().chain(tags.into_iter()).collect();
+                    let refs: IndexMap<&str, &str> = branches.into_iter().chain(tags).collect();
 
                     let mut git_versions = self.git_versions.lock().unwrap().clone();
 
@@ -692,8 +871,36 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
         }
     }
 
+    /// The path of the cached manifest of a git dependency at a revision.
+    fn manifest_cache_path(&'io self, url: &str, rev: &str) -> PathBuf {
+        self.sess
+            .config
+            .database
+            .join("git")
+            .join("manifests")
+            .join(format!("{}.json", git_db_name(rev, url)))
+    }
+
+    /// Get the path of the file recording the revision of a git dependency at
+    /// which its prebuild steps last ran.
+    fn prebuild_stamp_path(&'io self, name: &str) -> PathBuf {
+        self.sess
+            .config
+            .database
+            .join("prebuild")
+            .join(format!("{}.rev", name))
+    }
+
     /// Get the path of a dependency
     pub fn get_package_path(&'io self, dep_id: DependencyRef) -> PathBuf {
+        self.get_package_path_in(dep_id, self.sess.checkout_prefix.as_deref())
+    }
+
+    /// Get the path of a dependency, optionally checked out below `prefix`
+    /// instead of the configured checkout directory.
+    ///
+    /// Path dependencies are never relocated.
+    pub fn get_package_path_in(&'io self, dep_id: DependencyRef, prefix: Option<&Path>) -> PathBuf {
         let dep = self.sess.dependency(dep_id);
 
         // Determine the name of the checkout as the given name and the first
@@ -706,7 +913,14 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
             use blake2::{Blake2b512, Digest};
             let mut hasher = Blake2b512::new();
             match dep.source {
-                DependencySource::Registry => unimplemented!(),
+                DependencySource::Registry => hasher.update(
+                    self.sess
+                        .config
+                        .registry
+                        .as_deref()
+                        .unwrap_or("")
+                        .as_bytes(),
+                ),
                 DependencySource::Git(ref url) => hasher.update(url.as_bytes()),
                 DependencySource::Path(ref path) => {
                     // Determine and canonicalize the dependency path, and
@@ -724,9 +938,12 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
         };
         let checkout_name = format!("{}-{}", dep.name, hash);
 
-        // Determine the location of the git checkout. If the workspace has an
-        // explicit checkout directory, use that and do not append any hash to
-        // the dependency name.
+        // Determine the location of the git checkout. An explicit prefix takes
+        // precedence. If the workspace has an explicit checkout directory, use
+        // that and do not append any hash to the dependency name.
+        if let Some(prefix) = prefix {
+            return prefix.join(checkout_name);
+        }
         match self.sess.manifest.workspace.checkout_dir {
             Some(ref cd) => cd.join(&dep.name),
             None => self
@@ -750,7 +967,12 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
         let dep = self.sess.dependency(dep_id);
 
         match dep.source {
-            DependencySource::Registry => unimplemented!(),
+            DependencySource::Registry => {
+                return Err(Error::new(format!(
+                    "Registry dependency `{}` cannot be checked out. The registry only provides manifests for dependency resolution.",
+                    dep.name
+                )))
+            }
             DependencySource::Git(..) => {}
             DependencySource::Path(..) => {
                 let path = self
@@ -763,14 +985,14 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
         let checkout_dir = self.sess.intern_path(self.get_package_path(dep_id));
 
         match dep.source {
-            DependencySource::Path(..) => unreachable!(),
-            DependencySource::Registry => unimplemented!(),
+            DependencySource::Path(..) | DependencySource::Registry => unreachable!(),
             DependencySource::Git(ref url) => self
                 .checkout_git(
                     self.sess.intern_string(&dep.name),
                     checkout_dir,
                     self.sess.intern_string(url),
                     self.sess.intern_string(dep.revision.as_ref().unwrap()),
+                    dep.sparse.as_deref(),
                 )
                 .await
                 .and_then(move |path| {
@@ -795,6 +1017,7 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
         path: &'ctx Path,
         url: &'ctx str,
         revision: &'ctx str,
+        sparse: Option<&'ctx [String]>,
     ) -> Result<&'ctx Path> {
         // First check if we have to get rid of the current checkout. This is
         // the case if it either does not exist or the checked out revision does
@@ -804,7 +1027,9 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
                 if exists {
                     // Never scrap checkouts the user asked for explicitly in
                     // the workspace configuration.
-                    if self.sess.manifest.workspace.checkout_dir.is_some() {
+                    if self.sess.manifest.workspace.checkout_dir.is_some()
+                        && self.sess.checkout_prefix.is_none()
+                    {
                         return Ok(false);
                     }
 
@@ -851,6 +1076,14 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
         if !path.exists() {
             stageln!("Checkout", "{} ({})", name, url);
 
+            // A fresh checkout lacks the outputs of previous prebuild steps.
+            let stamp = self.prebuild_stamp_path(name);
+            if stamp.exists() {
+                std::fs::remove_file(&stamp).map_err(|cause| {
+                    Error::chain(format!("Failed to remove {:?}.", stamp), cause)
+                })?;
+            }
+
             // First generate a tag to be cloned in the database. This is
             // necessary since `git clone` does not accept commits, but only
             // branches or tags for shallow clones.
@@ -865,19 +1098,84 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
                     )
 		})
                 .await?;
-            git.spawn_with(move |c| {
-                c.arg("clone")
-                    .arg(git.path)
-                    .arg(path)
-                    .arg("--recursive")
-                    .arg("--branch")
-                    .arg(tag_name_1)
-            })
-            .await?;
+            git.with_timeout(self.sess.config.git_timeout_secs)
+                .spawn_with(move |c| {
+                    c.arg("clone")
+                        .arg(git.path)
+                        .arg(path)
+                        .arg("--recursive")
+                        .arg("--branch")
+                        .arg(tag_name_1);
+                    // Only check out the top-level files until the sparse
+                    // checkout is set up below.
+                    if sparse.is_some() {
+                        c.arg("--sparse");
+                    }
+                    c
+                })
+                .await?;
+        }
+
+        // Never touch checkouts the user asked for explicitly in the workspace
+        // configuration.
+        if self.sess.manifest.workspace.checkout_dir.is_none()
+            || self.sess.checkout_prefix.is_some()
+        {
+            self.sparse_checkout(path, sparse).await?;
         }
         Ok(path)
     }
 
+    /// Ensure that the sparse checkout of a git checkout matches the paths
+    /// recorded in the lockfile, or that the checkout is complete if there are
+    /// none.
+    async fn sparse_checkout(
+        &'io self,
+        path: &'ctx Path,
+        sparse: Option<&'ctx [String]>,
+    ) -> Result<()> {
+        let git = Git::new(path, &self.sess.config.git);
+        let current: Option<IndexSet<String>> = if path
+            .join(".git")
+            .join("info")
+            .join("sparse-checkout")
+            .exists()
+        {
+            git.spawn_with(|c| c.arg("sparse-checkout").arg("list"))
+                .await
+                .ok()
+                .map(|list| list.lines().map(String::from).collect())
+        } else {
+            None
+        };
+        match (sparse, current) {
+            (Some(paths), current) => {
+                let paths: IndexSet<String> = paths
+                    .iter()
+                    .map(|p| p.trim_end_matches('/').to_string())
+                    .collect();
+                // The sets compare equal regardless of their order.
+                if current.as_ref() != Some(&paths) {
+                    debugln!("checkout_git: sparse checkout {:?} of {:?}", paths, path);
+                    git.spawn_with(|c| {
+                        c.arg("sparse-checkout")
+                            .arg("set")
+                            .arg("--cone")
+                            .args(&paths)
+                    })
+                    .await?;
+                }
+            }
+            (None, Some(_)) => {
+                debugln!("checkout_git: disable sparse checkout of {:?}", path);
+                git.spawn_with(|c| c.arg("sparse-checkout").arg("disable"))
+                    .await?;
+            }
+            (None, None) => (),
+        }
+        Ok(())
+    }
+
     /// Checkout only git dependency's path sub-dependency Bender.yml files
     #[async_recursion(?Send)]
     async fn sub_dependency_fixing(
@@ -913,7 +1211,7 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
 
                     let sub_dep_path = reference_path.join(path).clone();
 
-                    let tmp_path = self.sess.root.join(".bender").join("tmp");
+                    let tmp_path = self.sess.tmp_manifest_dir();
 
                     if let Some(full_sub_data) = sub_data.clone() {
                         if !tmp_path.exists() {
@@ -1012,35 +1310,25 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
                 if manifest_path.exists() {
                     match read_manifest(&manifest_path) {
                         Ok(m) => {
-                            if dep.name != m.package.name {
-                                warnln!("Dependency name and package name do not match for {:?} / {:?}, this can cause unwanted behavior",
-                                    dep.name, m.package.name); // TODO: This should be an error
-                            }
+                            self.sess.check_package_name(&dep.name, &m)?;
                             Ok(Some(self.sess.intern_manifest(m)))
                         }
                         Err(e) => Err(e),
                     }
                 } else if self
                     .sess
-                    .root
-                    .join(".bender")
-                    .join("tmp")
+                    .tmp_manifest_dir()
                     .join(format!("{}_manifest.yml", dep.name))
                     .exists()
                 {
                     match read_manifest(
                         &self
                             .sess
-                            .root
-                            .join(".bender")
-                            .join("tmp")
+                            .tmp_manifest_dir()
                             .join(format!("{}_manifest.yml", dep.name)),
                     ) {
                         Ok(m) => {
-                            if dep.name != m.package.name {
-                                warnln!("Dependency name and package name do not match for {:?} / {:?}, this can cause unwanted behavior",
-                                    dep.name, m.package.name); // TODO: This should be an error
-                            }
+                            self.sess.check_package_name(&dep.name, &m)?;
                             Ok(Some(self.sess.intern_manifest(m)))
                         }
                         Err(e) => Err(e),
@@ -1050,31 +1338,66 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
                     Ok(None)
                 }
             }
-            (&DepSrc::Registry, DepVer::Registry(_hash)) => {
-                unimplemented!("load manifest of registry dependency");
+            (&DepSrc::Registry, DepVer::Registry(version)) => {
+                let data = self.sess.registry()?.manifest(&dep.name, version).await?;
+                let partial: config::PartialManifest =
+                    serde_yaml::from_str(&data).map_err(|cause| {
+                        Error::chain(
+                            format!(
+                                "Syntax error in manifest of dependency `{}` at version `{}`.",
+                                dep.name, version
+                            ),
+                            cause,
+                        )
+                    })?;
+                let full = partial.validate().map_err(|cause| {
+                    Error::chain(
+                        format!(
+                            "Error in manifest of dependency `{}` at version `{}`.",
+                            dep.name, version
+                        ),
+                        cause,
+                    )
+                })?;
+                self.sess.check_package_name(&dep.name, &full)?;
+                let manifest = Some(self.sess.intern_manifest(full));
+                self.sess
+                    .cache
+                    .dependency_manifest_version
+                    .lock()
+                    .unwrap()
+                    .insert(cache_key, manifest);
+                Ok(manifest)
             }
             (DepSrc::Git(url), DepVer::Git(rev)) => {
                 let dep_name = self.sess.intern_string(dep.name.as_str());
-                // TODO MICHAERO: May need proper chaining using and_then
-                let db = self.git_database(&dep.name, url, false, None).await?;
-                let entries = db.list_files(rev, Some("Bender.yml")).await?;
-                let data = match entries.into_iter().next() {
-                    None => Ok(None),
-                    Some(entry) => db.cat_file(entry.hash).await.map(Some),
-                }?;
-                let manifest: Result<_> = match data {
-                    Some(data) => {
-                        let partial: config::PartialManifest = serde_yaml::from_str(&data)
-                            .map_err(|cause| {
+                // Revisions are immutable, so a cached manifest never goes stale.
+                let cache_path = self.manifest_cache_path(url, rev);
+                let (partial, cache_data) = match read_manifest_cache(&cache_path) {
+                    Some(partial) => (partial, None),
+                    None => {
+                        let partial: Option<config::PartialManifest> = match self
+                            .dependency_manifest_data(dep_id, version)
+                            .await?
+                        {
+                            Some(data) => Some(serde_yaml::from_str(&data).map_err(|cause| {
                                 Error::chain(
                                     format!(
                                         "Syntax error in manifest of dependency `{}` at \
-                                             revision `{}`.",
+                                                 revision `{}`.",
                                         dep_name, rev
                                     ),
                                     cause,
                                 )
-                            })?;
+                            })?),
+                            None => None,
+                        };
+                        let cache_data = serde_json::to_string(&partial).ok();
+                        (partial, cache_data)
+                    }
+                };
+                let manifest: Result<_> = match partial {
+                    Some(partial) => {
                         let mut full = partial.validate().map_err(|cause| {
                             Error::chain(
                                 format!(
@@ -1085,44 +1408,46 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
                                 cause,
                             )
                         })?;
+                        if let Some(ref data) = cache_data {
+                            write_manifest_cache(&cache_path, data);
+                        }
 
                         // Add base path to path dependencies within git repositories
-                        self.sub_dependency_fixing(
-                            &mut full.dependencies,
-                            full.package.name.clone(),
-                            &self.get_package_path(dep_id),
-                            &self.get_package_path(dep_id),
-                            db,
-                            rev,
-                        )
-                        .await?;
+                        if full.dependencies.values().any(|dep| {
+                            matches!(dep, config::Dependency::Path(path) if !path.starts_with("/"))
+                        }) {
+                            let db = self.git_database(&dep.name, url, false, None).await?;
+                            self.sub_dependency_fixing(
+                                &mut full.dependencies,
+                                full.package.name.clone(),
+                                &self.get_package_path(dep_id),
+                                &self.get_package_path(dep_id),
+                                db,
+                                rev,
+                            )
+                            .await?;
+                        }
 
                         Ok(Some(self.sess.intern_manifest(full)))
                     }
                     None => {
+                        if let Some(ref data) = cache_data {
+                            write_manifest_cache(&cache_path, data);
+                        }
                         warnln!("Manifest not found for {:?}", dep.name);
                         Ok(None)
                     }
                 };
                 let manifest = manifest?;
+                if let Some(m) = manifest {
+                    self.sess.check_package_name(&dep.name, m)?;
+                }
                 self.sess
                     .cache
                     .dependency_manifest_version
                     .lock()
                     .unwrap()
                     .insert(cache_key, manifest);
-                if dep.name
-                    != match manifest {
-                        Some(x) => &x.package.name,
-                        None => "dead",
-                    }
-                {
-                    warnln!("Dependency name and package name do not match for {:?} / {:?}, this can cause unwanted behavior",
-                            dep.name, match manifest {
-                                Some(x) => &x.package.name,
-                                None => "dead"
-                            }); // TODO (micprog): This should be an error
-                }
                 Ok(manifest)
             }
             _ => panic!(
@@ -1132,6 +1457,48 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
         }
     }
 
+    /// Read the unparsed manifest of a dependency at a specific version.
+    ///
+    /// Returns `None` if the dependency has no manifest at that version.
+    pub async fn dependency_manifest_data(
+        &'io self,
+        dep_id: DependencyRef,
+        version: DependencyVersion<'ctx>,
+    ) -> Result<Option<String>> {
+        let dep = self.sess.dependency(dep_id);
+        match (&dep.source, version) {
+            (DependencySource::Path(path), DependencyVersion::Path) => {
+                let manifest_path = path.join("Bender.yml");
+                if !manifest_path.exists() {
+                    return Ok(None);
+                }
+                std::fs::read_to_string(&manifest_path)
+                    .map(Some)
+                    .map_err(|cause| {
+                        Error::chain(format!("Cannot read manifest {:?}.", manifest_path), cause)
+                    })
+            }
+            (DependencySource::Registry, DependencyVersion::Registry(version)) => self
+                .sess
+                .registry()?
+                .manifest(&dep.name, version)
+                .await
+                .map(Some),
+            (DependencySource::Git(url), DependencyVersion::Git(rev)) => {
+                let db = self.git_database(&dep.name, url, false, None).await?;
+                let entries = db.list_files(rev, Some("Bender.yml")).await?;
+                match entries.into_iter().next() {
+                    None => Ok(None),
+                    Some(entry) => db.cat_file(entry.hash).await.map(Some),
+                }
+            }
+            _ => panic!(
+                "incompatible source {:?} and version {:?}",
+                dep.source, version
+            ),
+        }
+    }
+
     /// Load the manifest for a dependency.
     ///
     /// Loads and returns the manifest for a dependency at the resolved version.
@@ -1151,6 +1518,22 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
             return Ok(cached);
         }
 
+        // Registry dependencies are not checked out, but provide the manifest
+        // of the locked version directly.
+        let dep = self.sess.dependency(dep_id);
+        if dep.source == DependencySource::Registry {
+            let manifest = self
+                .dependency_manifest_version(dep_id, dep.version())
+                .await?;
+            self.sess
+                .cache
+                .dependency_manifest
+                .lock()
+                .unwrap()
+                .insert(dep_id, manifest);
+            return Ok(manifest);
+        }
+
         // Otherwise ensure that there is a checkout of the dependency and read
         // the manifest there.
         self.sess.stats.num_calls_dependency_manifest.increment();
@@ -1159,10 +1542,13 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
             .and_then(move |path| {
                 let manifest_path = path.join("Bender.yml");
                 if manifest_path.exists() {
-                    match read_manifest(&manifest_path) {
-                        Ok(m) => Ok(Some(self.sess.intern_manifest(m))),
-                        Err(e) => Err(e),
+                    let m = read_manifest(&manifest_path)?;
+                    // The resolver already warns about mismatching names. In
+                    // strict mode, also reject them for locked packages.
+                    if self.sess.config.strict_names {
+                        self.sess.check_package_name(&dep.name, &m)?;
                     }
+                    Ok(Some(self.sess.intern_manifest(m)))
                 } else {
                     Ok(None)
                 }
@@ -1188,15 +1574,21 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
             return Ok((*cached).clone());
         }
 
-        // Load the manifests of all packages.
+        // Load the manifests of all packages, which checks them out.
+        let packages = self.sess.packages();
+        let progress = Progress::new("Checkout", packages.iter().flatten().count());
+        let progress = &progress;
         let ranks = join_all(
-            self.sess
-                .packages()
+            packages
                 .iter()
                 .map(move |pkgs| async move {
                     join_all(
                         pkgs.iter()
-                            .map(move |&pkg| async move { self.dependency_manifest(pkg).await })
+                            .map(move |&pkg| async move {
+                                let manifest = self.dependency_manifest(pkg).await;
+                                progress.inc();
+                                manifest
+                            })
                             .collect::<Vec<_>>(),
                     )
                     .await
@@ -1210,11 +1602,23 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
             .map(|pkgs| pkgs.into_iter().collect::<Result<Vec<_>>>())
             .collect::<Result<Vec<_>>>()?;
 
+        use std::iter::once;
+
+        // Run the prebuild steps in topological order, such that generated
+        // files exist before the sources are gathered.
+        for manifest in ranks
+            .iter()
+            .flatten()
+            .flatten()
+            .copied()
+            .chain(once(self.sess.manifest))
+        {
+            self.prebuild(manifest).await?;
+        }
+
         // Extract the sources of each package and concatenate them into a long
         // manifest.
 
-        use std::iter::once;
-
         // Create IndexMap of the export_include_dirs for each package
         let mut all_export_include_dirs: IndexMap<String, IndexSet<&Path>> = IndexMap::new();
         let tmp_export_include_dirs: Vec<IndexMap<String, _>> = ranks
@@ -1242,6 +1646,10 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
             "export_include_dirs for each package: {:?}",
             all_export_include_dirs
         );
+        check_export_include_dirs(&all_export_include_dirs, self.sess.config.strict)?;
+        if self.sess.config.check_include_collisions {
+            check_include_collisions(&all_export_include_dirs);
+        }
 
         let files = ranks
             .into_iter()
@@ -1255,10 +1663,12 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
                             // Collect include dirs from export_include_dirs of package and direct dependencies
                             let mut export_include_dirs: IndexMap<String, IndexSet<&Path>> =
                                 IndexMap::new();
+                            // The package itself also uses its private include dirs.
                             export_include_dirs.insert(
                                 m.package.name.clone(),
                                 m.export_include_dirs
                                     .iter()
+                                    .chain(&m.private_include_dirs)
                                     .map(PathBuf::as_path)
                                     .collect(),
                             );
@@ -1286,26 +1696,29 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
                                         Err(_) => None,
                                     },
                                 )
-                                .into()
+                                .map(Into::into)
                         })
                     })
-                    .collect();
+                    .collect::<Result<_>>()?;
 
                 // Create a source group for this rank.
-                SourceGroup {
+                Ok(SourceGroup {
                     package: None,
                     independent: true,
                     target: TargetSpec::Wildcard,
                     include_dirs: IndexSet::new(),
                     export_incdirs: IndexMap::new(),
                     defines: IndexMap::new(),
+                    target_defines: IndexMap::new(),
+                    headers: IndexSet::new(),
+                    library: None,
                     files,
                     dependencies: IndexSet::new(),
                     version: None,
                 }
-                .into()
+                .into())
             })
-            .collect();
+            .collect::<Result<_>>()?;
 
         // Create a source group covering all ranks, i.e. the root source group.
         let sources = SourceGroup {
@@ -1315,6 +1728,9 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
             include_dirs: IndexSet::new(),
             export_incdirs: IndexMap::new(),
             defines: IndexMap::new(),
+            target_defines: IndexMap::new(),
+            headers: IndexSet::new(),
+            library: None,
             files,
             dependencies: IndexSet::new(),
             version: None,
@@ -1325,6 +1741,81 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
         Ok(sources)
     }
 
+    /// Run the prebuild steps of a package.
+    ///
+    /// The steps of a git dependency run once per revision, which is recorded
+    /// in bender's database, keyed by the package name. The steps of the root package
+    /// and of path dependencies run whenever the sources are gathered.
+    async fn prebuild(&'io self, manifest: &'ctx Manifest) -> Result<()> {
+        if manifest.prebuild.is_empty() {
+            return Ok(());
+        }
+        let name = &manifest.package.name;
+        let stamp = match self.sess.dependency_with_name(name) {
+            Ok(dep_id) => {
+                let dep = self.sess.dependency(dep_id);
+                match (&dep.source, &dep.revision) {
+                    (DependencySource::Git(..), Some(rev)) => {
+                        Some((self.prebuild_stamp_path(name), rev.as_str()))
+                    }
+                    _ => None,
+                }
+            }
+            Err(_) => None,
+        };
+        if let Some((ref path, rev)) = stamp {
+            if std::fs::read_to_string(path).is_ok_and(|s| s.trim() == rev) {
+                debugln!("sess: prebuild of `{}` up-to-date at `{}`", name, rev);
+                return Ok(());
+            }
+        }
+
+        for step in &manifest.prebuild {
+            stageln!("Prebuild", "{} ({})", name, step.command);
+            let mut cmd = if cfg!(windows) {
+                let mut cmd = tokio::process::Command::new("cmd");
+                cmd.arg("/C");
+                cmd
+            } else {
+                let mut cmd = tokio::process::Command::new("sh");
+                cmd.arg("-c");
+                cmd
+            };
+            cmd.arg(&step.command).current_dir(&step.dir);
+            let status = cmd.status().await.map_err(|cause| {
+                Error::chain(
+                    format!(
+                        "Failed to run prebuild step `{}` of package `{}` in {:?}.",
+                        step.command, name, step.dir
+                    ),
+                    cause,
+                )
+            })?;
+            if !status.success() {
+                // Shells exit with 127 if the command does not exist.
+                let hint = match status.code() {
+                    Some(127) => " The generator was not found.",
+                    _ => "",
+                };
+                return Err(Error::new(format!(
+                    "Prebuild step `{}` of package `{}` failed with {}.{}",
+                    step.command, name, status, hint
+                )));
+            }
+        }
+
+        if let Some((ref path, rev)) = stamp {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).map_err(|cause| {
+                    Error::chain(format!("Failed to create directory {:?}.", parent), cause)
+                })?;
+            }
+            std::fs::write(path, rev)
+                .map_err(|cause| Error::chain(format!("Failed to write {:?}.", path), cause))?;
+        }
+        Ok(())
+    }
+
     /// Load the plugins declared by any of the dependencies.
     pub async fn plugins(&'io self) -> Result<&'ctx Plugins> {
         // Check if we already have the list of plugins.
@@ -1378,15 +1869,17 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
                     name.clone(),
                     Plugin {
                         name: name.clone(),
-                        package,
-                        path: plugin.clone(),
+                        package: Some(package),
+                        path: plugin.path.clone(),
+                        interpreter: plugin.interpreter.clone(),
+                        args: plugin.args.clone(),
                     },
                 );
                 if let Some(existing) = existing {
                     return Err(Error::new(format!(
                         "Plugin `{}` declared by multiple packages (`{}` and `{}`).",
                         name,
-                        self.sess.dependency_name(existing.package),
+                        existing.package_name(self.sess),
                         self.sess.dependency_name(package),
                     )));
                 }
@@ -1399,15 +1892,17 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
                 name.clone(),
                 Plugin {
                     name: name.clone(),
-                    package: DependencyRef(0), // FIXME: unclean implementation
-                    path: plugin.clone(),
+                    package: None,
+                    path: plugin.path.clone(),
+                    interpreter: plugin.interpreter.clone(),
+                    args: plugin.args.clone(),
                 },
             );
             if let Some(existing) = existing {
                 return Err(Error::new(format!(
                     "Plugin `{}` declared by multiple packages (`{}` and `{}`).",
                     name,
-                    self.sess.dependency_name(existing.package),
+                    existing.package_name(self.sess),
                     "root",
                 )));
             }
@@ -1418,6 +1913,123 @@ impl<'io, 'sess: 'io, 'ctx: 'sess> SessionIo<'sess, 'ctx> {
     }
 }
 
+/// Determine the name of the git database of a dependency.
+///
+/// The name is composed of the given name and the first 8 bytes (16 hex
+/// characters) of the URL's BLAKE2 hash.
+pub fn git_db_name(name: &str, url: &str) -> String {
+    use blake2::{Blake2b512, Digest};
+    let hash = &format!("{:016x}", Blake2b512::digest(url.as_bytes()))[..16];
+    format!("{}-{}", name, hash)
+}
+
+/// Read a manifest from the on-disk manifest cache.
+///
+/// Returns `None` if the manifest is not cached, or if the cache entry cannot
+/// be read, in which case the manifest is loaded from the git database.
+fn read_manifest_cache(path: &Path) -> Option<Option<config::PartialManifest>> {
+    let data = std::fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&data) {
+        Ok(partial) => {
+            debugln!("sess: manifest cache hit {:?}", path);
+            Some(partial)
+        }
+        Err(cause) => {
+            debugln!("sess: ignoring manifest cache {:?}: {}", path, cause);
+            None
+        }
+    }
+}
+
+/// Write a manifest to the on-disk manifest cache.
+///
+/// Failures are not fatal, since the cache only serves to speed up later runs.
+fn write_manifest_cache(path: &Path, data: &str) {
+    // Write to a temporary file first, such that concurrent runs never read a
+    // partially written entry.
+    let tmp_path = path.with_extension(format!("json.{}", std::process::id()));
+    let result = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&tmp_path, data))
+        .and_then(|_| std::fs::rename(&tmp_path, path));
+    if let Err(cause) = result {
+        debugln!("sess: cannot write manifest cache {:?}: {}", path, cause);
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+}
+
+/// Check that the exported include directories of all packages exist.
+///
+/// Missing directories are reported as warnings, or as an error in strict mode.
+fn check_export_include_dirs(
+    export_include_dirs: &IndexMap<String, IndexSet<&Path>>,
+    strict: bool,
+) -> Result<()> {
+    let missing: Vec<_> = export_include_dirs
+        .iter()
+        .flat_map(|(pkg, dirs)| {
+            dirs.iter()
+                .filter(|dir| !dir.exists())
+                .map(move |dir| (pkg, dir))
+        })
+        .collect();
+    if strict && !missing.is_empty() {
+        let mut msg = String::from("Exported include directories do not exist:");
+        for (pkg, dir) in missing {
+            msg.push_str(&format!("\n- package `{}`: {:?}", pkg, dir));
+        }
+        return Err(Error::new(msg));
+    }
+    for (pkg, dir) in missing {
+        warnln!(
+            "Package `{}` exports include directory {:?}, which does not exist.",
+            pkg,
+            dir
+        );
+    }
+    Ok(())
+}
+
+/// Warn about headers that are provided by the exported include directories of
+/// multiple packages.
+///
+/// Headers are compared by their path relative to the include directory, as
+/// this is what an `` `include `` directive resolves against.
+fn check_include_collisions(export_include_dirs: &IndexMap<String, IndexSet<&Path>>) {
+    let mut providers: IndexMap<PathBuf, &str> = IndexMap::new();
+    for (pkg, dirs) in export_include_dirs {
+        for dir in dirs {
+            for entry in walkdir::WalkDir::new(dir)
+                .follow_links(true)
+                .sort_by_file_name()
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().is_file())
+            {
+                let header = match entry.path().strip_prefix(dir) {
+                    Ok(header) => header.to_path_buf(),
+                    Err(_) => continue,
+                };
+                match providers.get(&header) {
+                    Some(&other) if other != pkg => {
+                        warnln!(
+                            "Header {:?} is provided by the exported include directories of both `{}` and `{}`. Which one is included depends on the order of the include directories.",
+                            header,
+                            other,
+                            pkg
+                        );
+                    }
+                    Some(_) => (),
+                    None => {
+                        providers.insert(header, pkg);
+                    }
+                }
+            }
+        }
+    }
+}
+
 /// An arena container where all incremental, temporary things are allocated.
 pub struct SessionArenas {
     /// An arena to allocate paths in.
@@ -1487,13 +2099,17 @@ pub struct DependencyEntry {
     pub revision: Option<String>,
     /// The picked version.
     pub version: Option<semver::Version>,
+    /// The paths to check out with a sparse checkout, if any.
+    pub sparse: Option<Vec<String>>,
 }
 
 impl DependencyEntry {
     /// Obtain the dependency version for this entry.
-    pub fn version(&self) -> DependencyVersion {
+    pub fn version(&self) -> DependencyVersion<'_> {
         match self.source {
-            DependencySource::Registry => unimplemented!(),
+            DependencySource::Registry => {
+                DependencyVersion::Registry(self.revision.as_ref().unwrap())
+            }
             DependencySource::Path(_) => DependencyVersion::Path,
             DependencySource::Git(_) => DependencyVersion::Git(self.revision.as_ref().unwrap()),
         }
@@ -1584,14 +2200,17 @@ pub enum DependencyVersions<'ctx> {
     /// Path dependencies have no versions, but are exactly as present on disk.
     Path,
     /// Registry dependency versions.
-    Registry(RegistryVersions),
+    Registry(RegistryVersions<'ctx>),
     /// Git dependency versions.
     Git(GitVersions<'ctx>),
 }
 
 /// All available versions of a registry dependency.
 #[derive(Clone, Debug)]
-pub struct RegistryVersions;
+pub struct RegistryVersions<'ctx> {
+    /// The versions available for this dependency, newest one first.
+    pub versions: Vec<(semver::Version, &'ctx str)>,
+}
 
 /// All available versions a git dependency has.
 #[derive(Clone, Debug)]
@@ -1612,7 +2231,7 @@ pub struct GitVersions<'ctx> {
 pub enum DependencyVersion<'ctx> {
     /// A path dependency has no version.
     Path,
-    /// The exact hash of a registry dependency.
+    /// The exact version of a registry dependency.
     Registry(&'ctx str),
     /// The exact revision of a git dependency.
     Git(&'ctx str),
@@ -1734,8 +2353,44 @@ pub type Plugins = IndexMap<String, Plugin>;
 pub struct Plugin {
     /// The name of the plugin.
     pub name: String,
-    /// Which package declared the plugin.
-    pub package: DependencyRef,
-    /// What binary implements the plugin.
+    /// Which package declared the plugin, or `None` for the root package.
+    pub package: Option<DependencyRef>,
+    /// What binary or script implements the plugin.
     pub path: PathBuf,
+    /// The interpreter which runs the script, if any.
+    pub interpreter: Option<String>,
+    /// The arguments passed before the ones given on the command line.
+    pub args: Vec<String>,
+}
+
+impl Plugin {
+    /// The name of the package which declared the plugin.
+    pub fn package_name<'ctx>(&self, sess: &Session<'ctx>) -> &'ctx str {
+        match self.package {
+            Some(dep) => sess.dependency_name(dep),
+            None => &sess.manifest.package.name,
+        }
+    }
+
+    /// Check whether the plugin's binary can be executed.
+    ///
+    /// Returns a description of the problem if it cannot.
+    pub fn problem(&self) -> Option<&'static str> {
+        let metadata = match self.path.metadata() {
+            Ok(m) if m.is_file() => m,
+            Ok(_) => return Some("not a file"),
+            Err(_) => return Some("missing"),
+        };
+        // A script run by an interpreter need not be executable itself.
+        #[cfg(unix)]
+        if self.interpreter.is_none() {
+            use std::os::unix::fs::PermissionsExt;
+            if metadata.permissions().mode() & 0o111 == 0 {
+                return Some("not executable");
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = metadata;
+        None
+    }
 }
@@ -33,6 +33,12 @@ pub struct SourceGroup<'ctx> {
     pub export_incdirs: IndexMap<String, IndexSet<&'ctx Path>>,
     /// The preprocessor definitions.
     pub defines: IndexMap<&'ctx str, Option<&'ctx str>>,
+    /// The preprocessor definitions that only apply to certain targets.
+    pub target_defines: IndexMap<TargetSpec, IndexMap<&'ctx str, Option<&'ctx str>>>,
+    /// The header files included by the files in this group.
+    pub headers: IndexSet<&'ctx Path>,
+    /// The library to compile the VHDL files in this group into.
+    pub library: Option<&'ctx str>,
     /// The files in this group.
     pub files: Vec<SourceFile<'ctx>>,
     /// Package dependencies of this source group
@@ -61,6 +67,9 @@ impl<'ctx> SourceGroup<'ctx> {
                     if group.files.len() == 1
                         && group.include_dirs.is_empty()
                         && group.defines.is_empty()
+                        && group.target_defines.is_empty()
+                        && group.headers.is_empty()
+                        && group.library.is_none()
                         && group.target.is_wildcard()
                         && group.package.is_none()
                     {
@@ -76,6 +85,42 @@ impl<'ctx> SourceGroup<'ctx> {
         SourceGroup { files, ..self }
     }
 
+    /// Check the preprocessor definitions of sibling groups for conflicts.
+    ///
+    /// Defines and include directories are kept in insertion-ordered maps,
+    /// such that duplicate defines collapse with the last definition of a key
+    /// winning, and include directories are deduplicated in order of first
+    /// occurrence. Since some scripts merge the defines of all groups, a
+    /// warning is issued for every define that sibling groups set to
+    /// conflicting values. Call this after filtering the targets, such that
+    /// groups which are never active together are not compared.
+    pub fn warn_conflicting_defines(&self) {
+        let mut seen: IndexMap<&'ctx str, (Option<&'ctx str>, Option<&'ctx str>)> = IndexMap::new();
+        for file in &self.files {
+            if let SourceFile::Group(group) = file {
+                group.warn_conflicting_defines();
+                for (&name, &value) in &group.defines {
+                    match seen.insert(name, (value, group.package)) {
+                        Some((prev, pkg)) if prev != value => {
+                            warnln!(
+                                    "Define `{}` is set to conflicting values in sibling source groups{}: `{}` and `{}`.",
+                                    name,
+                                    match (pkg, group.package) {
+                                        (Some(a), Some(b)) if a != b => format!(" of `{}` and `{}`", a, b),
+                                        (Some(a), _) | (_, Some(a)) => format!(" of `{}`", a),
+                                        _ => String::new(),
+                                    },
+                                    format_define(name, prev),
+                                    format_define(name, value)
+                                );
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        }
+    }
+
     /// Filter the sources, keeping only the ones that apply to a target.
     pub fn filter_targets(&self, targets: &TargetSet) -> Option<SourceGroup<'ctx>> {
         if !self.target.matches(targets) {
@@ -91,6 +136,13 @@ impl<'ctx> SourceGroup<'ctx> {
                 ref other => Some(other.clone()),
             })
             .collect();
+        // Fold in the defines of the matching targets.
+        let mut defines = self.defines.clone();
+        for (spec, target_defines) in &self.target_defines {
+            if spec.matches(targets) {
+                defines.extend(target_defines.iter().map(|(k, v)| (*k, *v)));
+            }
+        }
         Some(
             SourceGroup {
                 package: self.package,
@@ -98,7 +150,10 @@ impl<'ctx> SourceGroup<'ctx> {
                 target: self.target.clone(),
                 include_dirs: self.include_dirs.clone(),
                 export_incdirs: self.export_incdirs.clone(),
-                defines: self.defines.clone(),
+                defines,
+                target_defines: IndexMap::new(),
+                headers: self.headers.clone(),
+                library: self.library,
                 files,
                 dependencies: self.dependencies.clone(),
                 version: self.version.clone(),
@@ -107,6 +162,40 @@ impl<'ctx> SourceGroup<'ctx> {
         )
     }
 
+    /// Remove the target restrictions of the group and its subgroups.
+    ///
+    /// The target-specific defines of all targets are folded in, such that the
+    /// group carries the union of all defines.
+    pub fn ignore_targets(&self) -> SourceGroup<'ctx> {
+        let files = self
+            .files
+            .iter()
+            .map(|file| match *file {
+                SourceFile::Group(ref group) => SourceFile::Group(Box::new(group.ignore_targets())),
+                ref other => other.clone(),
+            })
+            .collect();
+        let mut defines = self.defines.clone();
+        for target_defines in self.target_defines.values() {
+            defines.extend(target_defines.iter().map(|(k, v)| (*k, *v)));
+        }
+        SourceGroup {
+            package: self.package,
+            independent: self.independent,
+            target: TargetSpec::Wildcard,
+            include_dirs: self.include_dirs.clone(),
+            export_incdirs: self.export_incdirs.clone(),
+            defines,
+            target_defines: IndexMap::new(),
+            headers: self.headers.clone(),
+            library: self.library,
+            files,
+            dependencies: self.dependencies.clone(),
+            version: self.version.clone(),
+        }
+        .simplify()
+    }
+
     /// Recursively get dependency names.
     fn get_deps(
         &self,
@@ -132,12 +221,17 @@ impl<'ctx> SourceGroup<'ctx> {
     }
 
     /// Get list of packages based on constraints.
+    ///
+    /// If `with_deps` is set, the transitive dependencies of the packages are
+    /// taken from the dependency graph instead of the source groups, such that
+    /// dependencies of packages without sources are included as well.
     pub fn get_package_list(
         &self,
         sess: &Session,
         packages: &IndexSet<String>,
         excludes: &IndexSet<String>,
         no_deps: bool,
+        with_deps: bool,
     ) -> IndexSet<String> {
         let mut result = IndexSet::new();
 
@@ -149,7 +243,27 @@ impl<'ctx> SourceGroup<'ctx> {
 
         result = &result - excludes;
 
-        if !no_deps {
+        if with_deps {
+            let graph = sess.graph();
+            let mut i = 0;
+            while let Some(name) = result.get_index(i).cloned() {
+                let deps: Vec<String> = if name == sess.manifest.package.name {
+                    sess.manifest.dependencies.keys().cloned().collect()
+                } else {
+                    sess.dependency_with_name(&name)
+                        .ok()
+                        .and_then(|id| graph.get(&id))
+                        .map(|deps| {
+                            deps.iter()
+                                .map(|&dep| sess.dependency_name(dep).to_string())
+                                .collect()
+                        })
+                        .unwrap_or_default()
+                };
+                result.extend(deps.into_iter().filter(|dep| !excludes.contains(dep)));
+                i += 1;
+            }
+        } else if !no_deps {
             let mut curr_length = 0;
             while curr_length < result.len() {
                 curr_length = result.len();
@@ -186,6 +300,9 @@ impl<'ctx> SourceGroup<'ctx> {
                 include_dirs: self.include_dirs.clone(),
                 export_incdirs,
                 defines: self.defines.clone(),
+                target_defines: self.target_defines.clone(),
+                headers: self.headers.clone(),
+                library: self.library,
                 files,
                 dependencies: self.dependencies.clone(),
                 version: self.version.clone(),
@@ -207,6 +324,21 @@ impl<'ctx> SourceGroup<'ctx> {
         incdirs.into_iter().collect()
     }
 
+    /// Find the flattened source group containing a file.
+    ///
+    /// The returned group carries the effective include directories, defines,
+    /// and target of the file. Paths are compared after canonicalization.
+    pub fn find_file(self, path: &Path) -> Option<SourceGroup<'ctx>> {
+        let canonical = |p: &Path| std::fs::canonicalize(p).unwrap_or_else(|_| p.to_path_buf());
+        let path = canonical(path);
+        self.flatten().into_iter().find(|group| {
+            group.files.iter().any(|file| match file {
+                SourceFile::File(p) => canonical(p) == path,
+                SourceFile::Group(_) => false,
+            })
+        })
+    }
+
     /// Flatten nested source groups.
     ///
     /// Removes all levels of hierarchy and produces a canonical list of source
@@ -249,18 +381,18 @@ impl<'ctx> SourceGroup<'ctx> {
                             .collect(),
                     );
                     grp.include_dirs = IndexSet::<&Path>::from_iter(
-                        self.include_dirs
-                            .iter()
-                            .cloned()
-                            .chain(grp.include_dirs.into_iter()),
+                        self.include_dirs.iter().cloned().chain(grp.include_dirs),
                     )
                     .into_iter()
                     .collect();
+                    grp.headers = self.headers.iter().cloned().chain(grp.headers).collect();
+                    grp.library = grp.library.or(self.library);
+                    // Defines of the subgroup take precedence over inherited ones.
                     grp.defines = self
                         .defines
                         .iter()
                         .map(|(k, v)| (*k, *v))
-                        .chain(grp.defines.into_iter())
+                        .chain(grp.defines)
                         .collect();
                     grp.flatten_into(into);
                 }
@@ -270,6 +402,14 @@ impl<'ctx> SourceGroup<'ctx> {
     }
 }
 
+/// Format a define as it would be passed on the command line.
+fn format_define(name: &str, value: Option<&str>) -> String {
+    match value {
+        Some(value) => format!("{}={}", name, value),
+        None => name.to_string(),
+    }
+}
+
 /// A source file.
 ///
 /// This can either be an individual file, or a subgroup of files.
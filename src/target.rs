@@ -20,7 +20,7 @@ use serde::ser::{Serialize, Serializer};
 use crate::error::*;
 
 /// A target specification.
-#[derive(Clone, Ord, PartialOrd, Eq, PartialEq)]
+#[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub enum TargetSpec {
     /// Matches all targets.
     Wildcard,
@@ -77,12 +77,17 @@ impl FromStr for TargetSpec {
             partial: None,
             next,
         };
-        parse(&mut lexer).map_err(|cause| {
-            Error::chain(
-                format!("Syntax error in target specification `{}`.", s),
-                cause,
-            )
-        })
+        parse(&mut lexer)
+            .and_then(|spec| match lexer.next() {
+                None => Ok(spec),
+                wrong => parse_wrong(wrong),
+            })
+            .map_err(|cause| {
+                Error::chain(
+                    format!("Syntax error in target specification `{}`.", s),
+                    cause,
+                )
+            })
     }
 }
 
@@ -122,6 +127,18 @@ impl TargetSpec {
     pub fn is_wildcard(&self) -> bool {
         matches!(*self, TargetSpec::Wildcard)
     }
+
+    /// The names of the targets referenced in this specification.
+    pub fn names(&self) -> BTreeSet<&str> {
+        match *self {
+            TargetSpec::Wildcard => BTreeSet::new(),
+            TargetSpec::Name(ref name) => BTreeSet::from([name.as_str()]),
+            TargetSpec::All(ref specs) | TargetSpec::Any(ref specs) => {
+                specs.iter().flat_map(|s| s.names()).collect()
+            }
+            TargetSpec::Not(ref spec) => spec.names(),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -273,6 +290,7 @@ fn parse_wrong<R>(wrong: Option<Result<TargetToken>>) -> Result<R> {
 ///
 /// Target specifications can be matched against a target set. A target set is
 /// basically just a collection of strings.
+#[derive(Clone)]
 pub struct TargetSet(IndexSet<String>);
 
 impl TargetSet {
@@ -188,6 +188,23 @@ pub fn read_file(path: &Path) -> std::io::Result<String> {
     Ok(contents)
 }
 
+/// Read a list of entries from a file, one per line.
+///
+/// Leading and trailing whitespace is trimmed. Blank lines and lines starting
+/// with `#` are ignored.
+pub fn read_list_file(path: &Path) -> std::io::Result<Vec<String>> {
+    let file = File::open(path)?;
+    let mut entries = Vec::new();
+    for line in std::io::BufReader::new(file).lines() {
+        let line = line?;
+        let line = line.trim();
+        if !line.is_empty() && !line.starts_with('#') {
+            entries.push(line.to_string());
+        }
+    }
+    Ok(entries)
+}
+
 /// Write an entire string to a file.
 pub fn write_file(path: &Path, contents: &str) -> std::io::Result<()> {
     let mut file = File::create(path)?;
@@ -0,0 +1,54 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo
+mkdir bar
+
+cd "$DIR"/foo
+git init
+echo "
+package:
+  name: foo
+" > Bender.yml
+git add Bender.yml
+git -c user.name=test -c user.email=test@example.com commit -m "v1.0.0"
+git tag v1.0.0
+for v in 1.0.1 1.1.0 2.0.0; do
+	echo "# $v" >> Bender.yml
+	git -c user.name=test -c user.email=test@example.com commit -am "v$v"
+	git tag v$v
+done
+
+cd "$DIR"/bar
+echo "
+package:
+  name: bar
+
+dependencies:
+  # pinned on purpose
+  foo: { git: \"file://$DIR/foo\", version: \"=1.0.0\" }
+" > Bender.yml
+$BENDER update
+grep -q 'version: 1.0.0' Bender.lock
+
+# Patch level stays within 1.0.x.
+$BENDER bump foo --level patch
+grep -q 'version: "=1.0.1"' Bender.yml
+
+# Minor level stays within 1.x and re-resolves.
+$BENDER update
+$BENDER bump foo --update
+grep -q 'version: "=1.1.0"' Bender.yml
+grep -q '# pinned on purpose' Bender.yml
+if ! grep -q 'version: 1.1.0' Bender.lock; then
+	cat Bender.lock
+	echo "lockfile not updated" >&2
+	exit 1
+fi
@@ -0,0 +1,66 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo
+mkdir bar
+mkdir top
+
+cd "$DIR"/foo
+git init
+echo "
+package:
+  name: foo
+
+sources:
+  - foo.sv
+" > Bender.yml
+touch foo.sv
+git add Bender.yml foo.sv
+git -c user.name=test -c user.email=test@example.com commit -m "v0.1.0"
+git tag v0.1.0
+
+echo "
+package:
+  name: bar
+" > "$DIR"/bar/Bender.yml
+
+cd "$DIR"/top
+echo "
+package:
+  name: top
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: \"0.1\" }
+  bar: { path: \"../bar\" }
+" > Bender.yml
+$BENDER update
+CHECKOUT="$($BENDER path foo)"
+
+# The git dependencies are checked out below the prefix, with the same name.
+$BENDER checkout --prefix ../staged
+if [ ! -f "$DIR/staged/$(basename "$CHECKOUT")/foo.sv" ]; then
+    ls -R "$DIR/staged" || true
+    echo "dependency not checked out below the prefix" >&2
+    exit 1
+fi
+
+# Path dependencies are not relocated.
+if [ -e "$DIR/staged/bar" ] || [ "$(ls "$DIR/staged")" != "$(basename "$CHECKOUT")" ]; then
+    ls "$DIR/staged"
+    echo "path dependency relocated" >&2
+    exit 2
+fi
+
+# The regular checkout is not affected.
+if [ "$($BENDER path foo)" != "$CHECKOUT" ]; then
+    $BENDER path foo
+    echo "regular checkout moved" >&2
+    exit 3
+fi
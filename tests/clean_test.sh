@@ -0,0 +1,60 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+for pkg in foo bar; do
+	mkdir $pkg
+	cd "$DIR"/$pkg
+	git init
+	echo "
+package:
+  name: $pkg
+" > Bender.yml
+	git add Bender.yml
+	git -c user.name=test -c user.email=test@example.com commit -m "v0.1.0"
+	git tag v0.1.0
+	cd "$DIR"
+done
+
+mkdir top
+cd "$DIR"/top
+echo "
+package:
+  name: top
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: \"0.1\" }
+  bar: { git: \"file://$DIR/bar\", version: \"0.1\" }
+" > Bender.yml
+$BENDER update
+[ "$(ls .bender/git/checkouts | wc -l)" -eq 2 ]
+[ "$(ls .bender/git/db | wc -l)" -eq 2 ]
+
+# Drop `bar`, leaving its checkout and database stale.
+sed -i '/bar:/d' Bender.yml
+$BENDER update
+
+# A flag is required.
+if $BENDER clean 2> /dev/null; then
+	echo "clean without flags should fail" >&2
+	exit 1
+fi
+
+$BENDER clean --checkouts
+ls .bender/git/checkouts | grep -q '^foo-'
+[ "$(ls .bender/git/checkouts | wc -l)" -eq 1 ]
+[ "$(ls .bender/git/db | wc -l)" -eq 2 ]
+
+$BENDER clean --all 2> stderr
+ls .bender/git/db | grep -q '^foo-'
+[ "$(ls .bender/git/db | wc -l)" -eq 1 ]
+grep -q "Cleaned.*1 path, reclaiming" stderr
+
+# The remaining dependency is still usable.
+$BENDER path foo | grep -q '/.bender/git/checkouts/foo-'
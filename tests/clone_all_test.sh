@@ -0,0 +1,68 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo
+mkdir baz
+mkdir -p top/qux
+
+for pkg in foo baz; do
+	cd "$DIR"/$pkg
+	git init
+	echo "
+package:
+  name: $pkg
+" > Bender.yml
+	git add Bender.yml
+	git -c user.name=test -c user.email=test@example.com commit -m "v0.1.0"
+	git tag v0.1.0
+done
+
+cd "$DIR"/top
+echo "
+package:
+  name: qux
+" > qux/Bender.yml
+echo "
+package:
+  name: top
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: \"0.1\" }
+  baz: { git: \"file://$DIR/baz\", version: \"0.1\" }
+  qux: { path: \"qux\" }
+" > Bender.yml
+$BENDER update
+
+# All git dependencies are cloned and overridden, path dependencies are kept.
+$BENDER clone --all &> log
+cat log
+grep -q 'Cloned.*2 dependencies into working_dir, skipped 0' log
+[ -d working_dir/foo/.git ]
+[ -d working_dir/baz/.git ]
+[ ! -e working_dir/qux ]
+grep -q 'foo: { path: "working_dir/foo" }' Bender.local
+grep -q 'baz: { path: "working_dir/baz" }' Bender.local
+if grep -q 'qux' Bender.local; then
+	cat Bender.local
+	echo "path dependency should not be cloned" >&2
+	exit 1
+fi
+$BENDER path foo | grep -q 'working_dir/foo$'
+
+# Dependencies that already have a path override are skipped.
+$BENDER clone --all &> log
+grep -q 'skipped 2' log
+
+# A package name and `--all` are mutually exclusive.
+if $BENDER clone --all foo &> log; then
+	cat log
+	echo "--all should conflict with a package name" >&2
+	exit 2
+fi
@@ -0,0 +1,58 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo
+mkdir bar
+
+cd "$DIR"/foo
+git init
+echo "
+package:
+  name: foo
+" > Bender.yml
+git add Bender.yml
+git -c user.name=test -c user.email=test@example.com commit -m "v0.1.0"
+git tag v0.1.0
+
+# The dependency name mismatches the package name to trigger a warning.
+cd "$DIR"/bar
+echo "
+package:
+  name: bar
+
+dependencies:
+  foo_alias: { git: \"file://$DIR/foo\", version: \"0.1\" }
+" > Bender.yml
+
+ESC=$(printf '\033')
+
+$BENDER --color never update &> log
+grep -q 'warning:' log
+grep -q 'Checkout' log
+if grep -q "$ESC" log; then
+	cat -v log
+	echo "--color never should not emit ANSI codes" >&2
+	exit 1
+fi
+
+$BENDER --color always update --fetch &> log
+if ! grep -q "$ESC" log; then
+	cat log
+	echo "--color always should emit ANSI codes" >&2
+	exit 2
+fi
+
+# Without a terminal, `auto` does not color.
+$BENDER update --fetch &> log
+if grep -q "$ESC" log; then
+	cat -v log
+	echo "--color auto should not color redirected output" >&2
+	exit 3
+fi
@@ -0,0 +1,89 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo
+mkdir bar
+
+cd "$DIR"/foo
+git init
+echo "
+package:
+  name: foo
+
+sources:
+  - target: any(fpga, not(asic))
+    files: []
+" > Bender.yml
+git add Bender.yml
+git -c user.name=test -c user.email=test@example.com commit -m "v0.1.0"
+git tag v0.1.0
+
+cd "$DIR"/bar
+echo "
+package:
+  name: bar
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: \"0.1\" }
+
+sources:
+  - target: rtl
+    files: []
+" > Bender.yml
+$BENDER update
+
+# The candidates are taken from the lockfile and the checked-out manifests.
+$BENDER __complete packages > out
+printf 'bar\nfoo\n' | diff - out
+$BENDER __complete targets > out
+printf 'asic\nfpga\nrtl\n' | diff - out
+
+# The hidden helper is not offered as a subcommand.
+$BENDER completion bash > completion.bash
+if grep -q '__complete' completion.bash; then
+	echo "static completion should not call the helper" >&2
+	exit 1
+fi
+
+# The dynamic bash completion calls the helper for packages and targets.
+$BENDER completion bash --dynamic > completion.bash
+bash -n completion.bash
+COMPREPLY=$(bash -c '
+	bender() { cargo run -q -- "$@"; }
+	source completion.bash
+	COMP_WORDS=(bender script vsim -p f)
+	COMP_CWORD=4
+	_bender_dynamic
+	echo "${COMPREPLY[@]}"
+')
+if [ "$COMPREPLY" != "foo" ]; then
+	echo "unexpected package completion: $COMPREPLY" >&2
+	exit 2
+fi
+COMPREPLY=$(bash -c '
+	bender() { cargo run -q -- "$@"; }
+	source completion.bash
+	COMP_WORDS=(bender sources --target "")
+	COMP_CWORD=3
+	_bender_dynamic
+	echo "${COMPREPLY[@]}"
+')
+if [ "$COMPREPLY" != "asic fpga rtl" ]; then
+	echo "unexpected target completion: $COMPREPLY" >&2
+	exit 3
+fi
+
+$BENDER completion fish --dynamic | grep -q '__complete targets'
+$BENDER completion zsh --dynamic | grep -q '__complete packages'
+if $BENDER completion elvish --dynamic &> log; then
+	cat log
+	echo "dynamic completion should be rejected for elvish" >&2
+	exit 4
+fi
@@ -0,0 +1,50 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo
+mkdir top
+
+echo "
+package:
+  name: foo
+" > foo/Bender.yml
+
+cd "$DIR"/top
+echo "
+package:
+  name: top
+" > Bender.yml
+echo "
+database: $DIR/db
+git: my-git
+overrides:
+  foo: { path: \"$DIR/foo\" }
+" > Bender.local
+
+# Values are printed without quotes, after merging the configuration files.
+if [ "$($BENDER config --get database)" != "$DIR/db" ] || [ "$($BENDER config --get git)" != "my-git" ]; then
+	$BENDER config
+	echo "configuration value should be printed as it is" >&2
+	exit 1
+fi
+if [ "$($BENDER config --get overrides.foo)" != "$DIR/foo" ]; then
+	$BENDER config
+	echo "nested configuration value should be printed" >&2
+	exit 2
+fi
+$BENDER config --get strict > out
+grep -qx false out
+
+# Unknown keys are rejected.
+if $BENDER config --get overrides.bar > out 2> log || ! grep -q 'Unknown configuration key `overrides.bar`' log; then
+	cat out log
+	echo "unknown key should fail" >&2
+	exit 3
+fi
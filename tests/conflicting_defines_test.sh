@@ -0,0 +1,73 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir -p inc
+touch a.sv b.sv c.sv
+echo "
+package:
+  name: top
+
+sources:
+  - include_dirs: [inc, inc]
+    defines:
+      BAR: ~
+    files:
+      - defines:
+          FOO: 1
+        files: [a.sv]
+      - defines:
+          FOO: 2
+        files: [b.sv]
+      - defines:
+          BAR: ~
+        files: [c.sv]
+" > Bender.yml
+
+$BENDER script flist-plus > out 2> log
+cat log
+
+# Conflicting sibling defines are reported, identical ones are not.
+grep -q 'Define `FOO` is set to conflicting values' log
+if grep -q 'Define `BAR`' log; then
+	echo "identical defines must not be reported" >&2
+	exit 1
+fi
+
+# The last definition wins and each define and include dir appears once.
+[ "$(grep -c '+define+FOO' out)" -eq 1 ]
+grep -q '+define+FOO=2' out
+[ "$(grep -c '+incdir+' out)" -eq 1 ]
+
+# Groups of targets that are never active together do not conflict.
+echo "
+package:
+  name: top
+
+sources:
+  - target: simulation
+    defines:
+      FOO: 1
+    files: [a.sv]
+  - target: synthesis
+    defines:
+      FOO: 2
+    files: [b.sv]
+" > Bender.yml
+
+for cmd in "script vsim" "script flist-plus -t simulation -t synthesis" "sources"; do
+	$BENDER $cmd > out 2> log
+	if [ "$cmd" = "script flist-plus -t simulation -t synthesis" ]; then
+		grep -q 'Define `FOO` is set to conflicting values' log
+	elif grep -q 'Define `FOO`' log; then
+		cat log
+		echo "defines of exclusive targets must not be reported for \`$cmd\`" >&2
+		exit 2
+	fi
+done
@@ -0,0 +1,49 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+touch inherited.sv local.sv
+echo "
+package:
+  name: top
+
+sources:
+  - defines:
+      LAYER: package
+      TARGET_VSIM: package
+    files:
+      - defines:
+          OTHER: ~
+        files: [inherited.sv]
+      - defines:
+          LAYER: group
+        files: [local.sv]
+" > Bender.yml
+
+# Print the defines emitted for the group containing a file.
+defines_of() {
+	awk -v file="$1" '
+		/vlog / { n = 0 }
+		/\+define\+/ { defs[n++] = $1 }
+		index($0, file) { for (i = 0; i < n; i++) print defs[i]; exit }
+	' "$2"
+}
+
+$BENDER script vsim > plain
+$BENDER script vsim -D LAYER=cli > cli
+
+# Inherited package defines beat the target defines.
+defines_of inherited.sv plain | grep -qx '"+define+TARGET_VSIM=package"'
+defines_of inherited.sv plain | grep -qx '"+define+LAYER=package"'
+# Group-local defines beat inherited ones.
+defines_of local.sv plain | grep -qx '"+define+LAYER=group"'
+# Command line defines beat everything.
+defines_of local.sv cli | grep -qx '"+define+LAYER=cli"'
+defines_of inherited.sv cli | grep -qx '"+define+LAYER=cli"'
+[ "$(defines_of local.sv cli | grep -c LAYER)" -eq 1 ]
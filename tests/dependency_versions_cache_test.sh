@@ -0,0 +1,70 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo
+mkdir a
+mkdir b
+mkdir top
+
+cd "$DIR"/foo
+git init
+echo "
+package:
+  name: foo
+" > Bender.yml
+git add Bender.yml
+git -c user.name=test -c user.email=test@example.com commit -m "v0.1.0"
+git tag v0.1.0
+
+for pkg in a b; do
+	echo "
+package:
+  name: $pkg
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: \"0.1\" }
+" > "$DIR"/$pkg/Bender.yml
+done
+
+cd "$DIR"/top
+echo "
+package:
+  name: top
+
+dependencies:
+  a: { path: \"../a\" }
+  b: { path: \"../b\" }
+  foo: { git: \"file://$DIR/foo\", version: \"0.1\" }
+" > Bender.yml
+
+# The versions of a dependency required by several packages are only
+# determined once.
+$BENDER --debug update 2> log
+if [ "$(grep -c 'git_versions get new' log)" -ne 1 ]; then
+	cat log
+	echo "git versions should be determined once per dependency" >&2
+	exit 1
+fi
+
+# Versions obtained by a forced fetch supersede the ones determined before.
+cd "$DIR"/foo
+touch other.sv
+git add other.sv
+git -c user.name=test -c user.email=test@example.com commit -m "v0.1.1"
+git tag v0.1.1
+cd "$DIR"/top
+sed -i 's/version: "0.1" }$/version: "0.1.1" }/' Bender.yml
+touch -d "2000-01-01" Bender.yml
+$BENDER update
+if ! grep -q "version: 0.1.1" Bender.lock; then
+	cat Bender.lock
+	echo "forced fetch should provide the new version" >&2
+	exit 2
+fi
@@ -0,0 +1,64 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo
+mkdir bar
+
+cd "$DIR"/foo
+git init
+echo "
+package:
+  name: foo
+" > Bender.yml
+git add Bender.yml
+git -c user.name=test -c user.email=test@example.com commit -m "v0.1.0"
+git tag v0.1.0
+OLD_REV=$(git rev-parse HEAD)
+git -c user.name=test -c user.email=test@example.com commit --allow-empty -m "v0.2.0"
+git tag v0.2.0
+NEW_REV=$(git rev-parse HEAD)
+
+cd "$DIR"/bar
+echo "
+package:
+  name: bar
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: \"0.1\" }
+" > Bender.yml
+$BENDER update
+cp Bender.lock old.lock
+
+# Identical lockfiles have no differences.
+$BENDER diff-lock old.lock > out
+grep -qx 'No differences.' out
+
+# Bump the dependency and compare against the working lockfile.
+sed -i 's/version: "0.1"/version: "0.2"/' Bender.yml
+$BENDER update
+$BENDER diff-lock old.lock > out
+cat out
+grep -qxF -- "~ foo version 0.1.0 -> 0.2.0" out
+grep -qxF -- "~ foo revision ${OLD_REV:0:7} -> ${NEW_REV:0:7}" out
+
+# A missing lockfile is an error.
+if $BENDER diff-lock missing.lock &> log; then
+	cat log
+	echo "missing lockfile should fail" >&2
+	exit 1
+fi
+grep -q 'Cannot open lockfile' log
+
+# Removed and added packages are listed as such.
+echo "packages: {}" > empty.lock
+$BENDER diff-lock Bender.lock empty.lock > out
+grep -qxF -- "- foo 0.2.0 (${NEW_REV:0:7})" out
+$BENDER diff-lock empty.lock > out
+grep -qxF -- "+ foo 0.2.0 (${NEW_REV:0:7})" out
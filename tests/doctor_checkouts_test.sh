@@ -0,0 +1,64 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo
+mkdir top
+
+cd "$DIR"/foo
+git init
+echo "
+package:
+  name: foo
+" > Bender.yml
+git add Bender.yml
+git -c user.name=test -c user.email=test@example.com commit -m "v0.1.0"
+git tag v0.1.0
+touch other.sv
+git add other.sv
+git -c user.name=test -c user.email=test@example.com commit -m "v0.2.0"
+git tag v0.2.0
+
+cd "$DIR"/top
+echo "
+package:
+  name: top
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: \"0.1\" }
+" > Bender.yml
+$BENDER update
+
+# A checkout at the locked revision is fine.
+$BENDER doctor > out
+if grep -q 'Checkouts' out; then
+    cat out
+    echo "checkout at the locked revision reported" >&2
+    exit 1
+fi
+
+# A checkout moved to another revision is reported.
+CHECKOUT="$($BENDER path foo)"
+git -C "$CHECKOUT" fetch -q origin tag v0.2.0
+git -C "$CHECKOUT" checkout -q v0.2.0
+$BENDER doctor > out
+if ! grep -q 'Checkout of `foo`' out || ! grep -q 'bender checkout' out; then
+    cat out
+    echo "checkout at the wrong revision not reported" >&2
+    exit 2
+fi
+
+# Checking out restores the locked revision.
+$BENDER checkout
+$BENDER doctor > out
+if grep -q 'Checkouts' out; then
+    cat out
+    echo "checkout not restored" >&2
+    exit 3
+fi
@@ -0,0 +1,66 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo top
+echo "
+package:
+  name: foo
+" > foo/Bender.yml
+
+cd top
+echo "
+package:
+  name: top
+
+dependencies:
+  foo: { path: \"../foo\" }
+" > Bender.yml
+
+# Without a lockfile, only a warning is reported.
+$BENDER doctor > report
+grep -q "There is no \`Bender.lock\`" report
+
+$BENDER update
+$BENDER doctor > report
+grep -qx "No issues found." report
+
+# A name mismatch is a warning.
+sed -i 's/name: foo/name: fooo/' ../foo/Bender.yml
+$BENDER doctor > report
+grep -q "Dependency \`foo\` refers to package \`fooo\`" report
+
+# Missing path dependencies and stale lockfile entries are reported.
+cp Bender.lock Bender.lock.orig
+sed -i 's|\.\./foo|../bar|' Bender.yml Bender.lock
+printf '  stale:\n    revision: null\n    version: null\n    source:\n      Path: ../foo\n    dependencies: []\n' >> Bender.lock
+if $BENDER doctor > report; then
+	cat report
+	echo "doctor should fail for a missing path dependency" >&2
+	exit 1
+fi
+grep -q "Path dependency \`foo\` points to .*bar.*, which does not exist." report
+grep -q "\`Bender.lock\` contains \`stale\`, which no package depends on." report
+
+# Cyclic dependencies are detected.
+sed -i 's|\.\./bar|../foo|' Bender.yml
+cp Bender.lock.orig Bender.lock
+sed -i 's/name: fooo/name: foo/' ../foo/Bender.yml
+echo "
+dependencies:
+  top: { path: \"../top\" }
+" >> ../foo/Bender.yml
+sed -i 's/dependencies: \[\]/dependencies:\n    - top/' Bender.lock
+printf '  top:\n    revision: null\n    version: null\n    source:\n      Path: .\n    dependencies:\n    - foo\n' >> Bender.lock
+if $BENDER doctor > report; then
+	cat report
+	echo "doctor should fail for a cyclic dependency" >&2
+	exit 1
+fi
+grep -q "cyclical dependency" report
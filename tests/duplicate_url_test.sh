@@ -0,0 +1,56 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo
+mkdir bar
+
+cd "$DIR"/foo
+git init
+echo "
+package:
+  name: foo
+" > Bender.yml
+git add Bender.yml
+git -c user.name=test -c user.email=test@example.com commit -m "v0.1.0"
+git tag v0.1.0
+
+cd "$DIR"/bar
+echo "
+package:
+  name: bar
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: \"0.1\" }
+  foo_alias: { git: \"file://$DIR/foo\", version: \"0.1\" }
+" > Bender.yml
+
+# By default, the duplicate is reported as a warning.
+$BENDER update &> log
+if ! grep -q 'same git repository' log; then
+	cat log
+	echo "missing warning for duplicate git url" >&2
+	exit 1
+fi
+grep -q '`foo`, `foo_alias`' log
+grep -q 'foo_alias' Bender.lock
+
+# In strict mode, the duplicate is an error.
+rm Bender.lock
+echo "
+strict: true
+strict_names: false
+" > Bender.local
+if $BENDER update &> log; then
+	cat log
+	echo "duplicate git url should fail in strict mode" >&2
+	exit 2
+fi
+grep -q 'same git repository' log
+[ ! -e Bender.lock ]
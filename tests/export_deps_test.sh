@@ -0,0 +1,57 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo
+mkdir baz
+mkdir top
+
+cd "$DIR"/foo
+git init
+echo "
+package:
+  name: foo
+" > Bender.yml
+git add Bender.yml
+git -c user.name=test -c user.email=test@example.com commit -m "v0.1.0"
+git tag v0.1.0
+REV=$(git rev-parse HEAD)
+
+cd "$DIR"/baz
+echo "
+package:
+  name: baz
+" > Bender.yml
+
+cd "$DIR"/top
+echo "
+package:
+  name: top
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: \"0.1\" }
+  baz: { path: \"../baz\" }
+" > Bender.yml
+$BENDER update
+
+$BENDER export-deps --format csv > out
+echo "name,source,location,revision,version
+baz,path,../baz,,
+foo,git,file://$DIR/foo,$REV,0.1.0" > expected
+if ! diff -u expected out; then
+    echo "unexpected CSV export" >&2
+    exit 1
+fi
+
+$BENDER export-deps --format json > out
+if ! grep -qF "\"revision\": \"$REV\"" out; then
+    cat out
+    echo "unexpected JSON export" >&2
+    exit 2
+fi
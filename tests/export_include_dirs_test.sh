@@ -0,0 +1,50 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+# The dependency exports a misspelled include directory.
+mkdir -p dep/include
+touch dep/dep.sv
+echo "
+package:
+  name: dep
+
+export_include_dirs:
+  - include
+  - includ
+
+sources:
+  - dep.sv
+" > dep/Bender.yml
+
+mkdir top
+echo "
+package:
+  name: top
+
+dependencies:
+  dep: { path: \"../dep\" }
+" > top/Bender.yml
+cd top
+$BENDER update
+
+# By default, the missing directory is a warning.
+$BENDER sources > /dev/null 2> stderr
+grep -q 'Package `dep` exports include directory ".*/dep/includ", which does not exist.' stderr
+if grep -q 'dep/include"' stderr; then
+	echo "existing include directory reported as missing" >&2
+	exit 1
+fi
+
+# In strict mode, it is an error.
+if $BENDER sources --strict > /dev/null 2> stderr; then
+	echo "sources succeeded despite missing export directory" >&2
+	exit 1
+fi
+grep -q 'package `dep`: ".*/dep/includ"' stderr
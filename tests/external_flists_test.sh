@@ -0,0 +1,69 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir -p src vendor/sub/rtl vendor/sub/include vendor/rtl
+touch src/top.sv vendor/rtl/ip.sv vendor/sub/rtl/core.sv
+echo "
+package:
+  name: top
+
+sources:
+  - target: simulation
+    external_flists:
+      - vendor/ip.f
+    files:
+      - src/top.sv
+" > Bender.yml
+echo "
++define+IP
+-F sub/core.f
+rtl/ip.sv
+" > vendor/ip.f
+echo "
++incdir+include
++define+CORE=1
+rtl/core.sv
+" > vendor/sub/core.f
+
+# Nested file lists are resolved relative to the including file list.
+$BENDER script flist-plus --no-relative-path -t simulation > out.f
+if [ "$(grep -v '^+' out.f | grep . | sed "s|$DIR/||" | tr '\n' ' ')" != "src/top.sv vendor/sub/rtl/core.sv vendor/rtl/ip.sv " ]; then
+    cat out.f
+    echo "files of the nested file lists missing or misplaced" >&2
+    exit 1
+fi
+for pattern in "+incdir+$DIR/vendor/sub/include" '+define+IP' '+define+CORE=1'; do
+    if ! grep -qxF -- "$pattern" out.f; then
+        cat out.f
+        echo "$pattern missing" >&2
+        exit 2
+    fi
+done
+
+# The file lists belong to the group and follow its target.
+$BENDER script flist-plus --no-relative-path > out.f
+if grep -q 'ip.sv' out.f; then
+    cat out.f
+    echo "file list included without the group's target" >&2
+    exit 3
+fi
+
+# Cyclic includes are rejected with the include chain.
+echo "-f ../ip.f" >> vendor/sub/core.f
+if $BENDER script flist-plus -t simulation &> log; then
+    cat log
+    echo "cyclic file lists must be rejected" >&2
+    exit 4
+fi
+if ! grep -q 'includes itself: .*ip.f" -> .*core.f" -> .*ip.f"' log; then
+    cat log
+    echo "cycle not reported with the include chain" >&2
+    exit 5
+fi
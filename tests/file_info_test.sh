@@ -0,0 +1,39 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir include src
+touch src/top.sv src/other.sv
+echo "
+package:
+  name: top
+
+sources:
+  - target: rtl
+    include_dirs:
+      - include
+    defines:
+      FOO: 1
+    files:
+      - src/top.sv
+" > Bender.yml
+
+$BENDER file-info src/top.sv > info.json
+cat info.json
+grep -q '"package": "top"' info.json
+grep -q '"target": ".*rtl.*"' info.json
+grep -q "\"$DIR/include\"" info.json
+grep -q '"FOO": "1"' info.json
+
+# Files outside of the resolved sources are rejected.
+if $BENDER file-info src/other.sv &> log; then
+	cat log
+	echo "should fail" >&2
+	exit 1
+fi
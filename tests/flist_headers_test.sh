@@ -0,0 +1,58 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"/dep/include "$DIR"/top/inc
+cd "$DIR"
+
+# The dependency declares its headers but no `export_include_dirs`.
+touch dep/d.sv dep/include/d.svh
+echo "
+package:
+  name: dep
+
+sources:
+  - headers: [include/d.svh]
+    files: [d.sv]
+" > dep/Bender.yml
+
+touch top/a.sv top/inc/a.svh
+echo "
+package:
+  name: top
+
+dependencies:
+  dep: { path: \"../dep\" }
+
+sources:
+  - headers: [inc/a.svh]
+    files: [a.sv]
+" > top/Bender.yml
+
+cd top
+$BENDER update
+
+# Headers are listed as comments, but not compiled.
+$BENDER script flist-plus --relative-path --comment-style // > flist
+grep -qx "+incdir+$DIR/dep/include" flist
+grep -qx '// header: inc/a.svh' flist
+if grep -qx 'inc/a.svh' flist; then
+	echo "headers must not be compiled" >&2
+	exit 1
+fi
+
+# Without a comment style, the headers are left out.
+$BENDER script flist-plus > flist
+if grep -q ' header:' flist; then
+	cat flist
+	echo "headers must not be listed without comments" >&2
+	exit 2
+fi
+$BENDER script flist-plus --comment-style '#' > flist
+grep -qx "# header: $DIR/dep/include/d.svh" flist
+
+# The headers are exposed to custom templates.
+$BENDER script template_json | grep -q '"all_headers"'
@@ -0,0 +1,64 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir src
+touch src/top.sv
+echo "
+sources:
+  - src/top.sv
+dependencies:
+  zeta: { path: \"../zeta\" }
+  alpha: { git: \"https://example.com/alpha.git\", rev: \"abc123\" }
+  mid: \"1.2.3\"
+custom_field: kept
+package:
+  name: top
+" > Bender.yml
+
+# An unformatted manifest fails the check without being modified.
+cp Bender.yml before.yml
+if $BENDER fmt --check &> log; then
+	cat log
+	echo "check must fail for an unformatted manifest" >&2
+	exit 1
+fi
+if ! cmp -s Bender.yml before.yml; then
+	echo "check must not modify the manifest" >&2
+	exit 2
+fi
+
+# Formatting brings the keys into canonical order and sorts the dependencies.
+$BENDER fmt
+if [ "$(grep -n '^[a-z_]*:' Bender.yml | cut -d: -f2 | tr '\n' ' ')" != "package dependencies sources custom_field " ]; then
+	cat Bender.yml
+	echo "top-level keys not in canonical order" >&2
+	exit 3
+fi
+if [ "$(grep -o '^  [a-z]*:' Bender.yml | tr -d ' :' | tr '\n' ' ')" != "name alpha mid zeta " ]; then
+	cat Bender.yml
+	echo "dependencies not sorted" >&2
+	exit 4
+fi
+
+# The dependency shorthands are kept.
+if ! grep -q 'mid: 1.2.3' Bender.yml || ! grep -q 'rev: abc123' Bender.yml; then
+	cat Bender.yml
+	echo "dependency specification changed" >&2
+	exit 5
+fi
+
+# The formatted manifest passes the check and is stable.
+cp Bender.yml formatted.yml
+$BENDER fmt --check
+$BENDER fmt
+if ! cmp -s Bender.yml formatted.yml; then
+	echo "formatting is not idempotent" >&2
+	exit 6
+fi
@@ -0,0 +1,48 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo
+mkdir bar
+
+cd "$DIR"/foo
+git init
+echo "
+package:
+  name: foo
+" > Bender.yml
+git add Bender.yml
+git -c user.name=test -c user.email=test@example.com commit -m "init"
+git tag v0.1.0
+
+# The dependency lives at an unreachable URL, which is only redirected to the
+# local repository by the extra git configuration for its prefix.
+cd "$DIR"/bar
+echo "
+package:
+  name: bar
+
+dependencies:
+  foo: { git: \"https://example.invalid/foo\", version: 0.1.0 }
+" > Bender.yml
+echo "
+git_config:
+  https://example.invalid/:
+    - url.file://$DIR/.insteadOf=https://example.invalid/
+  https://example.invalid/other/:
+    - url.file:///nonexistent/.insteadOf=https://example.invalid/
+" > Bender.local
+
+$BENDER update
+$BENDER checkout
+
+if [ ! -f "$($BENDER path foo)"/Bender.yml ]; then
+    echo "foo was not checked out through the configured URL" >&2
+    exit 1
+fi
@@ -0,0 +1,54 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo
+mkdir bar
+
+cd "$DIR"/foo
+git init
+echo "
+package:
+  name: foo
+" > Bender.yml
+git add Bender.yml
+git -c user.name=test -c user.email=test@example.com commit -m "init"
+git tag v0.1.0
+
+# A git wrapper that hangs on every fetch, simulating an unresponsive server.
+echo '#!/bin/bash
+if [ "$1" = "fetch" ]; then
+	sleep 60
+fi
+exec git "$@"' > "$DIR"/slow-git.sh
+chmod +x "$DIR"/slow-git.sh
+
+cd "$DIR"/bar
+echo "
+package:
+  name: bar
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: 0.1.0 }
+" > Bender.yml
+echo "
+git: $DIR/slow-git.sh
+" > Bender.local
+
+if $BENDER --git-timeout 1 update &> log; then
+	cat log
+	echo "should fail" >&2
+	exit 1
+fi
+
+if ! grep -E 'timed out after 1 seconds' log; then
+	cat log
+	echo "should fail with a timeout" >&2
+	exit 2
+fi
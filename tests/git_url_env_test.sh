@@ -0,0 +1,52 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo
+mkdir bar
+
+cd "$DIR"/foo
+git init
+echo "
+package:
+  name: foo
+" > Bender.yml
+git add Bender.yml
+git -c user.name=test -c user.email=test@example.com commit -m "v0.1.0"
+git tag v0.1.0
+
+cd "$DIR"/bar
+echo '
+package:
+  name: bar
+
+dependencies:
+  foo: { git: "file://${BENDER_TEST_GIT_HOST}/foo", version: "0.1" }
+' > Bender.yml
+
+# The variable is substituted before the dependency is fetched and locked.
+BENDER_TEST_GIT_HOST="$DIR" $BENDER update &> log || (cat log; exit 1)
+if ! grep -qF "file://$DIR/foo" Bender.lock; then
+	cat Bender.lock
+	echo "git url not substituted in lockfile" >&2
+	exit 2
+fi
+
+# An unset variable is reported rather than producing a broken URL.
+rm Bender.lock
+if env -u BENDER_TEST_GIT_HOST $BENDER update &> log; then
+	cat log
+	echo "unset variable in git url should fail" >&2
+	exit 3
+fi
+if ! grep -q 'BENDER_TEST_GIT_HOST' log; then
+	cat log
+	echo "error does not name the unset variable" >&2
+	exit 4
+fi
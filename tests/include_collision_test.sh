@@ -0,0 +1,57 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+# Two packages export a header of the same name.
+for pkg in a b; do
+	mkdir -p $pkg/include/common
+	touch $pkg/$pkg.sv $pkg/include/common/defs.svh $pkg/include/$pkg.svh
+	echo "
+package:
+  name: $pkg
+
+export_include_dirs:
+  - include
+
+sources:
+  - $pkg.sv
+" > $pkg/Bender.yml
+done
+
+mkdir top
+touch top/top.sv
+echo "
+package:
+  name: top
+
+dependencies:
+  a: { path: \"../a\" }
+  b: { path: \"../b\" }
+
+sources:
+  - top.sv
+" > top/Bender.yml
+
+cd top
+$BENDER update
+
+# The scan is disabled by default.
+$BENDER sources > /dev/null 2> stderr
+if grep -q "common/defs.svh" stderr; then
+	echo "unexpected collision warning without check_include_collisions" >&2
+	exit 1
+fi
+
+echo "check_include_collisions: true" > Bender.local
+$BENDER sources > /dev/null 2> stderr
+grep -q 'Header "common/defs.svh" is provided by the exported include directories of both `a` and `b`' stderr
+if grep -q '"a.svh"\|"b.svh"' stderr; then
+	echo "unexpected collision warning for distinct headers" >&2
+	exit 1
+fi
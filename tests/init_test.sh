@@ -0,0 +1,82 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir ip
+mkdir wrapper
+
+cd "$DIR"/ip
+git init
+echo "
+package:
+  name: ip
+" > Bender.yml
+git add Bender.yml
+git -c user.name=test -c user.email=test@example.com commit -m "v0.1.0"
+git tag v0.1.0
+
+# Scaffold a package wrapping the existing IP.
+cd "$DIR"/wrapper
+$BENDER init --name my_wrapper --authors "Jane Doe <jane@example.com>" \
+	--git "file://$DIR/ip.git" --version 0.1
+cat Bender.yml
+grep -qF 'name: "my_wrapper"' Bender.yml
+grep -qF -- '- "Jane Doe <jane@example.com>"' Bender.yml
+grep -qF "ip: { git: \"file://$DIR/ip.git\", version: \"0.1\" }" Bender.yml
+
+# An existing manifest is only overwritten with `--force`.
+if $BENDER init --git "file://$DIR/ip" --rev v0.1.0 &> log; then
+	cat log
+	echo "existing Bender.yml should not be overwritten" >&2
+	exit 1
+fi
+grep -q 'already exists' log
+$BENDER init --force --git "file://$DIR/ip" --rev v0.1.0
+grep -qF "ip: { git: \"file://$DIR/ip\", rev: \"v0.1.0\" }" Bender.yml
+$BENDER update
+grep -q 'ip:' Bender.lock
+
+# Invalid requirements are rejected without touching the manifest.
+cp Bender.yml expected.yml
+if $BENDER init --force --git "file://$DIR/ip" --version "not a version" &> log; then
+	cat log
+	echo "invalid version should fail" >&2
+	exit 2
+fi
+diff expected.yml Bender.yml
+if $BENDER init --force --git "file://$DIR/ip" &> log; then
+	cat log
+	echo "git dependency without rev or version should fail" >&2
+	exit 3
+fi
+
+# Generate the sources from an existing tree.
+mkdir -p "$DIR"/legacy/rtl/core "$DIR"/legacy/rtl/include "$DIR"/legacy/.git/rtl "$DIR"/legacy/tb
+cd "$DIR"/legacy
+touch rtl/core/alu.sv rtl/core/regfile.sv rtl/include/defs.svh rtl/top.vhd tb/tb.sv tb/README.md .git/rtl/skip.sv
+$BENDER init --from-sources . &> log
+cat Bender.yml
+grep -q 'Review them' log
+sed -n '/^sources:/,$p' Bender.yml > sources.yml
+cat > expected.yml <<EOS
+sources:
+  include_dirs:
+    - "rtl/include"
+  files:
+    - files:
+        - "rtl/core/alu.sv"
+        - "rtl/core/regfile.sv"
+    - files:
+        - "rtl/top.vhd"
+    - files:
+        - "tb/tb.sv"
+EOS
+diff expected.yml sources.yml
+$BENDER sources -f > out
+grep -q "$DIR/legacy/rtl/core/alu.sv" out
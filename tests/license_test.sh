@@ -0,0 +1,63 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo
+mkdir bar
+mkdir top
+
+echo "
+package:
+  name: foo
+  license: Apache-2.0 WITH SHL-2.1
+" > foo/Bender.yml
+
+echo "
+package:
+  name: bar
+  license: GPL-3.0-only
+" > bar/Bender.yml
+
+cd "$DIR"/top
+echo "
+package:
+  name: top
+  license: LicenseRef-Proprietary
+
+dependencies:
+  foo: { path: \"../foo\" }
+" > Bender.yml
+
+# Compatible licenses pass the check.
+$BENDER license --check-compatibility > out
+grep -q 'foo.*Apache-2.0 WITH SHL-2.1' out
+grep -q 'top.*LicenseRef-Proprietary' out
+
+$BENDER license --format json > out.json
+python3 -c "import json; assert json.load(open('out.json'))['Apache-2.0 WITH SHL-2.1'] == ['foo']"
+
+# A GPL dependency in a proprietary package is flagged.
+echo "  bar: { path: \"../bar\" }" >> Bender.yml
+$BENDER update
+if $BENDER license --check-compatibility > out 2> log; then
+	cat out log
+	echo "incompatible license should fail" >&2
+	exit 1
+fi
+grep -q 'bar.*GPL-3.0-only.*incompatible' out
+grep -q 'Found 1 license' log
+if grep -q 'foo.*incompatible' out; then
+	cat out
+	echo "compatible license flagged" >&2
+	exit 2
+fi
+
+# Without the check, the licenses are only listed.
+$BENDER license > out
+grep -q 'bar.*GPL-3.0-only' out
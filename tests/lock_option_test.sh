@@ -0,0 +1,73 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir dep_a dep_b top artifacts
+echo "
+package:
+  name: dep_a
+" > dep_a/Bender.yml
+echo "
+package:
+  name: dep_b
+" > dep_b/Bender.yml
+echo "
+package:
+  name: top
+
+dependencies:
+  dep_a: { path: \"../dep_a\" }
+" > top/Bender.yml
+echo "
+package:
+  name: top
+
+dependencies:
+  dep_b: { path: \"../dep_b\" }
+" > top/variant.yml
+
+# The lockfile is written to the given path instead of next to the manifest.
+cd "$DIR"/top
+$BENDER --lock ../artifacts/top.lock update > out 2> log
+if [ ! -f ../artifacts/top.lock ] || [ -e Bender.lock ]; then
+	cat out log
+	echo "lockfile should be written to the given path" >&2
+	exit 1
+fi
+
+# The given lockfile is read by other commands, and reported by its path if
+# it does not match the manifest.
+sed -i 's/dep_a/dep_c/g' ../artifacts/top.lock
+$BENDER packages --lock ../artifacts/top.lock > out 2> log || true
+if ! grep -q "$DIR/artifacts/top.lock\` does not" log; then
+	cat out log
+	echo "the given lockfile should be read" >&2
+	exit 2
+fi
+
+# Together with `--manifest`, the given lockfile takes precedence.
+cd "$DIR"
+$BENDER --manifest top/variant.yml --lock artifacts/variant.lock update > out 2> log
+if ! grep -q "dep_b" artifacts/variant.lock || [ -e top/variant.lock ]; then
+	cat out log
+	echo "lockfile should be written to the given path with --manifest" >&2
+	exit 3
+fi
+
+# A lockfile in a missing directory is rejected.
+if $BENDER --manifest top/variant.yml --lock missing/variant.lock update > out 2> log; then
+	cat out log
+	echo "lockfile in a missing directory should be rejected" >&2
+	exit 4
+fi
+if ! grep -q "does not exist" log; then
+	cat log
+	echo "missing directory should be reported" >&2
+	exit 5
+fi
@@ -0,0 +1,55 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo
+mkdir bar
+
+cd "$DIR"/foo
+git init
+echo "
+package:
+  name: foo
+" > Bender.yml
+git add Bender.yml
+git -c user.name=test -c user.email=test@example.com commit -m "v0.1.0"
+git tag v0.1.0
+
+cd "$DIR"/bar
+echo "
+package:
+  name: bar
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: \"0.1\" }
+" > Bender.yml
+
+# Without a git database, a local-only lock must fail gracefully.
+if $BENDER --local lock &> log; then
+	cat log
+	echo "local lock without git database should fail" >&2
+	exit 1
+fi
+grep -q 'foo' log
+[ ! -e Bender.lock ]
+
+# Locking resolves the dependencies without checking them out.
+$BENDER lock
+grep -q 'version: 0.1.0' Bender.lock
+if [ -e .bender/git/checkouts ]; then
+	ls .bender/git/checkouts
+	echo "lock checked out dependencies" >&2
+	exit 2
+fi
+
+# With the git database present, a local-only lock succeeds.
+rm Bender.lock
+$BENDER --local lock
+grep -q 'version: 0.1.0' Bender.lock
+[ ! -e .bender/git/checkouts ]
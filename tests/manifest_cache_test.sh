@@ -0,0 +1,84 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo
+mkdir bar
+mkdir baz
+
+cd "$DIR"/baz
+git init
+echo "
+package:
+  name: baz
+" > Bender.yml
+git add .
+git -c user.name=test -c user.email=test@example.com commit -m "v0.1.0"
+git tag v0.1.0
+
+cd "$DIR"/foo
+git init
+mkdir -p src include
+touch src/foo.sv include/foo.svh
+echo "
+package:
+  name: foo
+
+dependencies:
+  baz: { git: \"file://$DIR/baz\", version: \"0.1\" }
+
+export_include_dirs:
+  - include
+  - { dir: src, public: false }
+
+sources:
+  - defines:
+      FOO: 1
+    files:
+      - src/foo.sv
+" > Bender.yml
+git add .
+git -c user.name=test -c user.email=test@example.com commit -m "v0.1.0"
+git tag v0.1.0
+
+cd "$DIR"/bar
+echo "
+package:
+  name: bar
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: \"0.1\" }
+" > Bender.yml
+$BENDER update
+cp Bender.lock expected.lock
+
+# The manifests of the resolved revisions are cached in the database.
+if [ "$(ls .bender/git/manifests | wc -l)" != 2 ]; then
+	ls -R .bender/git
+	echo "manifests of foo and baz not cached" >&2
+	exit 1
+fi
+
+# Resolving again uses the cached manifests, with the same result.
+$BENDER --debug update &> log
+if ! grep -q 'manifest cache hit' log; then
+	cat log
+	echo "cached manifests not used" >&2
+	exit 2
+fi
+diff expected.lock Bender.lock
+
+# A corrupt cache entry is ignored and replaced.
+for f in .bender/git/manifests/*; do echo "garbage" > "$f"; done
+$BENDER update
+diff expected.lock Bender.lock
+if grep -q 'garbage' .bender/git/manifests/*; then
+	echo "corrupt cache entry not replaced" >&2
+	exit 3
+fi
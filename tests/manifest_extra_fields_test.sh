@@ -0,0 +1,70 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo
+mkdir bar
+
+cd "$DIR"/foo
+git init
+echo "
+package:
+  name: foo
+  maintainer: someone
+
+ci_metadata:
+  nightly: true
+" > Bender.yml
+git add .
+git -c user.name=test -c user.email=test@example.com commit -m "v0.1.0"
+git tag v0.1.0
+
+cd "$DIR"/bar
+echo "
+package:
+  name: bar
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: \"0.1\", owner: team }
+
+ci_metadata:
+  nightly: false
+" > Bender.yml
+
+# Unknown fields of the package's own manifest are reported, but accepted.
+$BENDER update &> log
+for field in ci_metadata dependencies.foo.owner; do
+    if ! grep -q "Unknown field \`$field\`" log; then
+        cat log
+        echo "missing warning about unknown field $field" >&2
+        exit 1
+    fi
+done
+if grep -q 'maintainer' log; then
+    cat log
+    echo "unknown fields of dependencies must not be reported" >&2
+    exit 2
+fi
+
+# The unknown fields of a dependency survive the manifest cache.
+if ! grep -q '"ci_metadata":{"nightly":true}' .bender/git/manifests/*.json \
+    || ! grep -q '"maintainer":"someone"' .bender/git/manifests/*.json; then
+    cat .bender/git/manifests/*.json
+    echo "unknown fields dropped from the manifest cache" >&2
+    exit 3
+fi
+
+# The warning can be silenced in the configuration.
+echo "allow_extra_fields: true" > Bender.local
+$BENDER checkout &> log
+if grep -q 'Unknown field' log; then
+    cat log
+    echo "warning not silenced by allow_extra_fields" >&2
+    exit 4
+fi
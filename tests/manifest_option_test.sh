@@ -0,0 +1,72 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir dep_a dep_b top
+echo "
+package:
+  name: dep_a
+" > dep_a/Bender.yml
+echo "
+package:
+  name: dep_b
+" > dep_b/Bender.yml
+
+# Two variants of the package live in the same directory.
+echo "
+package:
+  name: top
+
+dependencies:
+  dep_a: { path: \"../dep_a\" }
+" > top/Bender.yml
+echo "
+package:
+  name: top
+
+dependencies:
+  dep_b: { path: \"../dep_b\" }
+" > top/variant.yml
+
+# The default manifest is used without `--manifest`.
+cd "$DIR"/top
+$BENDER update > /dev/null 2>&1
+if [ ! -f Bender.lock ] || ! grep -q "dep_a" Bender.lock; then
+	echo "default lockfile should be written" >&2
+	exit 1
+fi
+cp Bender.lock Bender.lock.orig
+
+# The alternate manifest is used directly, with a lockfile named after it.
+cd "$DIR"
+$BENDER --manifest top/variant.yml update > out 2> log
+if [ ! -f top/variant.lock ] || ! grep -q "dep_b" top/variant.lock; then
+	cat out log
+	echo "lockfile should be named after the manifest" >&2
+	exit 2
+fi
+if ! cmp -s top/Bender.lock top/Bender.lock.orig; then
+	echo "default lockfile should not be modified" >&2
+	exit 3
+fi
+
+# The option may also follow the subcommand.
+$BENDER packages --manifest top/variant.yml > out 2> log
+if ! grep -q "dep_b" out || grep -q "dep_a" out; then
+	cat out log
+	echo "packages of the alternate manifest should be listed" >&2
+	exit 4
+fi
+
+# A missing manifest is an error.
+if $BENDER --manifest top/missing.yml packages > out 2> log; then
+	cat out log
+	echo "missing manifest should be rejected" >&2
+	exit 5
+fi
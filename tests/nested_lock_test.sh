@@ -0,0 +1,69 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo sub top
+
+cd "$DIR"/foo
+git init
+echo "
+package:
+  name: foo
+" > Bender.yml
+git add Bender.yml
+git -c user.name=test -c user.email=test@example.com commit -m "v0.1.0"
+git tag v0.1.0
+echo "# change" >> Bender.yml
+git -c user.name=test -c user.email=test@example.com commit -am "v0.2.0"
+git tag v0.2.0
+
+# A path dependency whose own lockfile pins foo to 0.1.0.
+cd "$DIR"/sub
+echo "
+package:
+  name: sub
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: \"=0.1.0\" }
+" > Bender.yml
+$BENDER update
+sed -i 's/=0.1.0/>=0.1.0/' Bender.yml
+grep -q 'version: 0.1.0' Bender.lock
+
+cd "$DIR"/top
+echo "
+package:
+  name: top
+
+dependencies:
+  sub: { path: \"../sub\" }
+" > Bender.yml
+
+# By default, the nested lockfile is ignored.
+$BENDER update
+grep -q 'version: 0.2.0' Bender.lock
+
+# Honoring the nested lockfile pins foo to its locked revision, which
+# downgrades it.
+echo "respect_nested_lock: true" > Bender.local
+$BENDER update --allow-downgrade
+if ! grep -q 'version: 0.1.0' Bender.lock; then
+	cat Bender.lock
+	echo "nested lockfile not respected" >&2
+	exit 1
+fi
+
+# Conflicts with the root constraints are reported.
+echo "  foo: { git: \"file://$DIR/foo\", version: \"=0.2.0\" }" >> Bender.yml
+if $BENDER update &> log; then
+	cat log
+	echo "should fail" >&2
+	exit 2
+fi
+grep -q 'package `sub` requires' log
@@ -0,0 +1,79 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir old_dep new_dep top
+echo "
+package:
+  name: old_dep
+" > old_dep/Bender.yml
+echo "
+package:
+  name: new_dep
+" > new_dep/Bender.yml
+
+# The first revision depends on `old_dep`.
+cd "$DIR"/top
+git init -q
+echo "
+package:
+  name: top
+
+dependencies:
+  old_dep: { path: \"../old_dep\" }
+" > Bender.yml
+$BENDER update > /dev/null 2>&1
+git add Bender.yml Bender.lock
+git -c user.name=test -c user.email=test@example.com commit -q -m "old"
+git tag old
+
+# The current revision depends on `new_dep` instead.
+echo "
+package:
+  name: top
+
+dependencies:
+  new_dep: { path: \"../new_dep\" }
+" > Bender.yml
+$BENDER update > /dev/null 2>&1
+git add Bender.yml Bender.lock
+git -c user.name=test -c user.email=test@example.com commit -q -m "new"
+cp Bender.lock Bender.lock.orig
+
+# The packages of the old revision are listed without checking it out.
+$BENDER packages --rev old > out 2> log
+if ! grep -q "old_dep" out || grep -q "new_dep" out; then
+	cat out log
+	echo "packages of the given revision should be listed" >&2
+	exit 1
+fi
+if ! cmp -s Bender.lock Bender.lock.orig || ! grep -q "new_dep" Bender.yml; then
+	echo "working tree should not be modified" >&2
+	exit 2
+fi
+
+# Without `--rev`, the working tree is used.
+$BENDER packages > out 2> log
+if ! grep -q "new_dep" out || grep -q "old_dep" out; then
+	cat out log
+	echo "packages of the working tree should be listed" >&2
+	exit 3
+fi
+
+# An unknown revision is an error.
+if $BENDER packages --rev nonexistent > out 2> log; then
+	cat out log
+	echo "unknown revision should be rejected" >&2
+	exit 4
+fi
+if ! grep -q "Cannot read \`Bender.yml\` at revision \`nonexistent\`" log; then
+	cat log
+	echo "unknown revision should be reported" >&2
+	exit 5
+fi
@@ -0,0 +1,68 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+# A diamond: top depends on a and b, which both depend on c, which depends on d.
+mkdir a b c d top
+echo "
+package:
+  name: d
+" > d/Bender.yml
+echo "
+package:
+  name: c
+
+dependencies:
+  d: { path: \"$DIR/d\" }
+" > c/Bender.yml
+for pkg in a b; do
+    echo "
+package:
+  name: $pkg
+
+dependencies:
+  c: { path: \"$DIR/c\" }
+" > $pkg/Bender.yml
+done
+echo "
+package:
+  name: top
+
+dependencies:
+  a: { path: \"$DIR/a\" }
+  b: { path: \"$DIR/b\" }
+" > top/Bender.yml
+
+cd "$DIR"/top
+$BENDER update
+
+# Without --transitive, only the direct parent is listed.
+$BENDER parents d > out
+if ! grep -qE '^ +c +requires' out || grep -qE '^ +(a|b|top) ' out; then
+    cat out
+    echo "unexpected direct parents" >&2
+    exit 1
+fi
+
+# With --transitive, every ancestor is listed exactly once.
+$BENDER parents d --transitive > out
+for pkg in a b top; do
+    if [ "$(grep -cE "^ +$pkg +indirect" out)" != 1 ]; then
+        cat out
+        echo "$pkg not listed once as an indirect parent" >&2
+        exit 2
+    fi
+done
+
+$BENDER parents d --transitive --format json > out
+if [ "$(grep -c '"name"' out)" != 4 ] || ! grep -qF '"direct": false' out; then
+    cat out
+    echo "unexpected JSON parents" >&2
+    exit 3
+fi
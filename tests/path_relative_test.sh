@@ -0,0 +1,60 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+# A git dependency, a path dependency inside the root, and one outside.
+mkdir gitdep
+cd gitdep
+git init -q
+echo "
+package:
+  name: gitdep
+" > Bender.yml
+git add Bender.yml
+git -c user.name=test -c user.email=test@example.com commit -q -m "v1.0.0"
+git tag v1.0.0
+
+mkdir -p "$DIR"/outer "$DIR"/top/ips/inner
+echo "
+package:
+  name: outer
+" > "$DIR"/outer/Bender.yml
+echo "
+package:
+  name: inner
+" > "$DIR"/top/ips/inner/Bender.yml
+
+cd "$DIR"/top
+echo "
+package:
+  name: top
+
+dependencies:
+  gitdep: { git: \"file://$DIR/gitdep\", version: \"1.0.0\" }
+  inner: { path: \"ips/inner\" }
+  outer: { path: \"../outer\" }
+" > Bender.yml
+$BENDER update
+
+# Paths inside the root are relative, others stay absolute with a note.
+$BENDER path --relative gitdep inner outer > out.txt 2> stderr
+diff out.txt - <<EOT
+.bender/git/checkouts/$(basename "$($BENDER path gitdep 2>/dev/null)")
+ips/inner
+$DIR/outer
+EOT
+grep -q 'Package `outer` is outside the package root' stderr
+
+# The JSON output maps the package names to their paths.
+$BENDER path --relative --format json inner outer > out.json
+python3 - "$DIR" <<'EOT'
+import json, sys
+paths = json.load(open("out.json"))
+assert paths == {"inner": "ips/inner", "outer": sys.argv[1] + "/outer"}, paths
+EOT
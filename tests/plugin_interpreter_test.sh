@@ -0,0 +1,65 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+echo "
+package:
+  name: top
+
+sources:
+  - top.sv
+
+plugins:
+  show:
+    path: scripts/show.sh
+    interpreter: sh
+    args: [--fixed]
+" > Bender.yml
+touch top.sv
+mkdir scripts
+cat > scripts/show.sh <<'SCRIPT'
+echo "args: $*"
+echo "root: $BENDER_ROOT"
+echo "manifest: $BENDER_MANIFEST"
+cp "$BENDER_SOURCES_JSON" sources.json
+echo "$BENDER_SOURCES_JSON" > sources_path
+SCRIPT
+
+# The script is run by the interpreter with the fixed and forwarded arguments,
+# even though it is not executable itself.
+$BENDER show --forwarded > out 2> log
+if ! grep -q "^args: --fixed --forwarded$" out; then
+	cat out log
+	echo "plugin should receive the fixed and forwarded arguments" >&2
+	exit 1
+fi
+if ! grep -q "^root: $DIR$" out || ! grep -q "^manifest: $DIR/Bender.yml$" out; then
+	cat out log
+	echo "plugin should receive the root and manifest" >&2
+	exit 2
+fi
+
+# The resolved sources are provided in a temporary file.
+if ! grep -q "$DIR/top.sv" sources.json; then
+	cat sources.json
+	echo "plugin should receive the resolved sources" >&2
+	exit 3
+fi
+if [ -e "$(cat sources_path)" ]; then
+	echo "temporary sources file should be removed" >&2
+	exit 4
+fi
+
+# The plugin is listed with its interpreter and not flagged.
+$BENDER plugins > out 2> log
+if ! grep -q "^show *top *sh $DIR/scripts/show.sh$" out; then
+	cat out log
+	echo "plugin should be listed with its interpreter" >&2
+	exit 5
+fi
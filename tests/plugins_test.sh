@@ -0,0 +1,61 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir tools
+mkdir top
+
+echo "
+package:
+  name: tools
+
+plugins:
+  hello: hello.sh
+  broken: broken.sh
+" > tools/Bender.yml
+printf '#!/bin/sh\necho hello\n' > tools/hello.sh
+chmod +x tools/hello.sh
+
+cd "$DIR"/top
+echo "
+package:
+  name: top
+
+dependencies:
+  tools: { path: \"../tools\" }
+" > Bender.yml
+
+# The declared plugins are listed with their package and binary.
+$BENDER plugins > out 2> log
+if ! grep -q "hello *tools *$DIR/tools/hello.sh$" out; then
+	cat out log
+	echo "plugin should be listed" >&2
+	exit 1
+fi
+
+# A plugin whose binary does not exist is flagged.
+if ! grep -q "broken *tools *$DIR/tools/broken.sh *missing" out || ! grep -q "Plugin \`broken\`" log; then
+	cat out log
+	echo "missing plugin binary should be flagged" >&2
+	exit 2
+fi
+
+# A plugin whose binary is not executable is flagged.
+printf '#!/bin/sh\n' > ../tools/broken.sh
+$BENDER plugins > out 2> log
+if ! grep -q "broken .*not executable" out; then
+	cat out log
+	echo "non-executable plugin binary should be flagged" >&2
+	exit 3
+fi
+if grep -q "hello .*\(missing\|not executable\)" out; then
+	cat out log
+	echo "working plugin should not be flagged" >&2
+	exit 4
+fi
@@ -0,0 +1,113 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir gen
+mkdir a
+mkdir b
+mkdir top
+
+# A git dependency generating one of its sources.
+cd "$DIR"/gen
+git init
+echo "
+package:
+  name: gen
+
+sources:
+  - rtl/regs.sv
+
+prebuild:
+  - command: \"echo 'module regs; endmodule' > regs.sv && echo gen >> $DIR/runs.log\"
+    dir: rtl
+" > Bender.yml
+mkdir rtl
+touch rtl/.keep
+git add Bender.yml rtl/.keep
+git -c user.name=test -c user.email=test@example.com commit -m "v0.1.0"
+git tag v0.1.0
+
+# Path dependencies recording the order of their steps.
+for pkg in a b; do
+	echo "
+package:
+  name: $pkg
+
+prebuild:
+  - command: \"echo $pkg >> $DIR/order.log\"
+" > "$DIR"/$pkg/Bender.yml
+done
+echo "
+dependencies:
+  b: { path: \"../b\" }
+" >> "$DIR"/a/Bender.yml
+
+cd "$DIR"/top
+echo "
+package:
+  name: top
+
+dependencies:
+  a: { path: \"../a\" }
+  gen: { git: \"file://$DIR/gen\", version: \"0.1\" }
+
+prebuild:
+  - command: \"echo top >> $DIR/order.log\"
+" > Bender.yml
+
+# The generated files exist, and the steps run in topological order.
+$BENDER update
+$BENDER sources -f > out
+GEN="$($BENDER path gen)"
+if ! grep -q "$GEN/rtl/regs.sv" out || ! grep -q "module regs" "$GEN/rtl/regs.sv"; then
+	cat out
+	echo "generated source should exist" >&2
+	exit 1
+fi
+if [ "$(head -3 "$DIR"/order.log | tr '\n' ' ')" != "b a top " ]; then
+	cat "$DIR"/order.log
+	echo "prebuild steps should run in topological order" >&2
+	exit 2
+fi
+
+# The steps of a git dependency only run once per revision.
+$BENDER sources -f > out
+if [ "$(wc -l < "$DIR"/runs.log)" -ne 1 ]; then
+	cat "$DIR"/runs.log
+	echo "prebuild of git dependency should run once" >&2
+	exit 3
+fi
+if git -C "$GEN" status --porcelain --ignored | grep -v "rtl/regs.sv"; then
+	echo "prebuild should not leave state in the checkout" >&2
+	exit 5
+fi
+
+# A fresh checkout runs the steps again.
+rm -rf "$GEN"
+$BENDER sources -f > out
+if [ "$(wc -l < "$DIR"/runs.log)" -ne 2 ]; then
+	cat "$DIR"/runs.log
+	echo "prebuild of git dependency should run again after a fresh checkout" >&2
+	exit 6
+fi
+
+# A missing generator is reported.
+echo "
+package:
+  name: top
+
+prebuild:
+  - command: bender-missing-generator
+" > Bender.yml
+$BENDER update --no-checkout
+if $BENDER sources > out 2> log || ! grep -q "Prebuild step \`bender-missing-generator\` of package \`top\` failed" log || ! grep -q "not found" log; then
+	cat out log
+	echo "missing generator should fail" >&2
+	exit 4
+fi
@@ -0,0 +1,53 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir -p foo/include foo/internal top
+
+echo "
+package:
+  name: foo
+
+export_include_dirs:
+  - include
+  - { dir: internal, public: false }
+
+sources:
+  - foo.sv
+" > foo/Bender.yml
+touch foo/foo.sv
+
+cd "$DIR"/top
+echo "
+package:
+  name: top
+
+dependencies:
+  foo: { path: \"../foo\" }
+
+sources:
+  - top.sv
+" > Bender.yml
+touch top.sv
+$BENDER update
+
+$BENDER script vsim > vsim.tcl
+python3 - "$DIR" <<'PY'
+import sys
+root = sys.argv[1]
+blocks = open("vsim.tcl").read().split("vlog")[1:]
+foo = next(b for b in blocks if "foo.sv" in b)
+top = next(b for b in blocks if "top.sv" in b)
+# The package uses both its public and private include dirs.
+assert root + "/foo/include" in foo, foo
+assert root + "/foo/internal" in foo, foo
+# Dependents only see the public ones.
+assert root + "/foo/include" in top, top
+assert root + "/foo/internal" not in top, top
+PY
@@ -0,0 +1,49 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo
+mkdir bar
+
+cd "$DIR"/foo
+git init
+echo "
+package:
+  name: foo
+" > Bender.yml
+git add Bender.yml
+git -c user.name=test -c user.email=test@example.com commit -m "v0.1.0"
+git tag v0.1.0
+
+cd "$DIR"/bar
+echo "
+package:
+  name: bar
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: \"0.1\" }
+" > Bender.yml
+
+# Progress and stage messages are emitted as JSON lines, also without a terminal.
+$BENDER --progress-json update 2> log
+if ! grep '^{.*"event":"progress"' log | grep -q '"label":"Checkout".*"total":1}$'; then
+    cat log
+    echo "progress event missing" >&2
+    exit 1
+fi
+if ! grep '^{.*"event":"stage"' log | grep -q '"stage":"Checkout"'; then
+    cat log
+    echo "stage event missing" >&2
+    exit 2
+fi
+if grep -q '^ *Checkout foo' log; then
+    cat log
+    echo "human-readable stage output mixed into the JSON events" >&2
+    exit 3
+fi
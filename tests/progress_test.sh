@@ -0,0 +1,52 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo
+mkdir bar
+
+cd "$DIR"/foo
+git init
+echo "
+package:
+  name: foo
+" > Bender.yml
+git add Bender.yml
+git -c user.name=test -c user.email=test@example.com commit -m "v0.1.0"
+git tag v0.1.0
+
+cd "$DIR"/bar
+echo "
+package:
+  name: bar
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: \"0.1\" }
+" > Bender.yml
+
+# Without a terminal, only the regular line output is printed.
+$BENDER update &> log
+grep -q 'Checkout' log
+if grep -q '\] 1/1' log; then
+    cat -v log
+    echo "progress bar drawn without a terminal" >&2
+    exit 1
+fi
+
+# In a terminal, the progress bar is drawn and finalized.
+if ! command -v script > /dev/null; then
+    exit 0
+fi
+rm -rf .bender Bender.lock
+script -qec "$BENDER update" /dev/null < /dev/null > log
+if ! grep -q 'Checkout \[=*\] 1/1' log; then
+    cat -v log
+    echo "progress bar not drawn in a terminal" >&2
+    exit 2
+fi
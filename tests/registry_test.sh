@@ -0,0 +1,81 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+# A local registry index providing `foo`, which depends on `bar`.
+mkdir -p index top
+echo '{"versions": ["1.0.0", "1.2.0", "2.0.0"]}' > index/foo.json
+echo '{"versions": ["0.1.0", "0.1.3", "0.2.0"]}' > index/bar.json
+for version in 1.0.0 1.2.0 2.0.0; do
+	mkdir -p index/foo/$version
+	echo "
+package:
+  name: foo
+
+dependencies:
+  bar: \"0.1\"
+" > index/foo/$version/Bender.yml
+done
+for version in 0.1.0 0.1.3 0.2.0; do
+	mkdir -p index/bar/$version
+	echo "
+package:
+  name: bar
+" > index/bar/$version/Bender.yml
+done
+
+cd top
+echo "
+package:
+  name: top
+
+dependencies:
+  foo: \"1\"
+" > Bender.yml
+
+# Registry dependencies require a configured registry.
+if $BENDER update 2> stderr; then
+	echo "update without registry should fail" >&2
+	exit 1
+fi
+grep -q "no \`registry\` is configured" stderr
+
+echo "registry: ../index" > Bender.local
+$BENDER update
+cat Bender.lock
+grep -A4 '^  foo:' Bender.lock | grep -q 'version: 1.2.0'
+grep -A4 '^  bar:' Bender.lock | grep -q 'version: 0.1.3'
+grep -q "Registry: $DIR/top/../index" Bender.lock
+
+# The lockfile is honored on subsequent resolutions.
+echo '{"versions": ["1.0.0", "1.2.0", "1.3.0", "2.0.0"]}' > ../index/foo.json
+cp -r ../index/foo/1.2.0 ../index/foo/1.3.0
+$BENDER update --locked-only
+grep -A4 '^  foo:' Bender.lock | grep -q 'version: 1.2.0'
+$BENDER update
+grep -A4 '^  foo:' Bender.lock | grep -q 'version: 1.3.0'
+
+# Vendoring from a registry is rejected with an error.
+mkdir "$DIR"/vendoring
+cd "$DIR"/vendoring
+echo "
+package:
+  name: vendoring
+
+vendor_package:
+  - name: foo
+    target_dir: vendor/foo
+    upstream: { version: \"1.2\" }
+" > Bender.yml
+if $BENDER vendor init > out 2> log; then
+	cat out log
+	echo "vendoring from a registry should fail" >&2
+	exit 1
+fi
+grep -q "Cannot vendor package \`foo\` from a registry" log
@@ -0,0 +1,65 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo
+cd foo
+git init -q
+echo "
+package:
+  name: foo
+" > Bender.yml
+git add Bender.yml
+git -c user.name=test -c user.email=test@example.com commit -q -m "v1.0.0"
+git tag v1.0.0
+for v in 1.2.0 1.3.0 2.0.0; do
+	echo "# $v" >> Bender.yml
+	git -c user.name=test -c user.email=test@example.com commit -q -am "v$v"
+	git tag v$v
+done
+
+# `bar` is stricter on `foo` than the root package.
+mkdir "$DIR"/bar
+echo "
+package:
+  name: bar
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: \">=1.0.0, <1.3.0\" }
+" > "$DIR"/bar/Bender.yml
+
+mkdir "$DIR"/top
+cd "$DIR"/top
+echo "
+package:
+  name: top
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: \"1.2.0\" }
+  bar: { path: \"../bar\" }
+" > Bender.yml
+$BENDER update
+cp Bender.lock Bender.lock.orig
+
+# A version satisfying all requirements.
+$BENDER resolve --explain foo@1.2.0 > out.txt
+grep -q 'top requires `^1.2.0`: satisfied' out.txt
+grep -q 'bar requires `>=1.0.0, <1.3.0`: satisfied' out.txt
+
+# A version satisfying only some requirements.
+if $BENDER resolve --explain foo@1.3.0 > out.txt 2> stderr; then
+	echo "explain succeeded for a violating version" >&2
+	exit 1
+fi
+grep -q 'top requires `^1.2.0`: satisfied' out.txt
+grep -q 'bar requires `>=1.0.0, <1.3.0`: not satisfied' out.txt
+grep -q 'violates 1 of 2 requirement(s)' stderr
+
+# Nothing is modified.
+diff Bender.lock.orig Bender.lock
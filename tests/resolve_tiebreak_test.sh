@@ -0,0 +1,47 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo
+mkdir top
+
+# Two tags of equal precedence, differing only in their build metadata. The
+# commit carrying the preferred tag is older, such that it comes last in the
+# date order of the revisions.
+cd "$DIR"/foo
+git init
+echo "
+package:
+  name: foo
+" > Bender.yml
+git add Bender.yml
+GIT_COMMITTER_DATE="2020-01-01T00:00:00" git -c user.name=test -c user.email=test@example.com commit -m "first"
+git tag v1.0.0+10
+PREFERRED="$(git rev-parse HEAD)"
+touch other.sv
+git add other.sv
+GIT_COMMITTER_DATE="2021-01-01T00:00:00" git -c user.name=test -c user.email=test@example.com commit -m "second"
+git tag v1.0.0+9
+
+cd "$DIR"/top
+echo "
+package:
+  name: top
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: \"1.0.0\" }
+" > Bender.yml
+
+# The tag with the highest build metadata is picked.
+$BENDER update
+if ! grep -q "revision: $PREFERRED" Bender.lock; then
+	cat Bender.lock
+	echo "tag with the highest build metadata should be picked" >&2
+	exit 1
+fi
@@ -0,0 +1,37 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+touch a.sv
+echo "
+package:
+  name: top
+
+sources:
+  - a.sv
+" > Bender.yml
+
+$BENDER script vsim --abort-file "$DIR/failed" > vsim.tcl
+grep -qF "}]} {close [open {$DIR/failed} w]; return 1}" vsim.tcl
+
+$BENDER script vcs --abort-file "$DIR/failed" > vcs.sh
+grep -qF "trap 'touch \"$DIR/failed\"' ERR" vcs.sh
+
+# Without the option, the scripts are unchanged.
+$BENDER script vsim > vsim.tcl
+grep -qF "}]} {return 1}" vsim.tcl
+if grep -q "failed" vsim.tcl; then
+	echo "unexpected abort file directive" >&2
+	exit 1
+fi
+
+if $BENDER script vsim --abort-file "$DIR/failed" --no-abort-on-error &> log; then
+	echo "--abort-file must conflict with --no-abort-on-error" >&2
+	exit 2
+fi
@@ -0,0 +1,46 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir include
+touch a.sv b.sv c.sv d.vhd
+echo "
+package:
+  name: top
+
+sources:
+  - include_dirs: [include]
+    defines:
+      FOO: 1
+    files: [a.sv, b.sv, c.sv, d.vhd]
+" > Bender.yml
+
+# One command per chunk, with the placeholders substituted.
+$BENDER script flist --no-default-target --max-files-per-group 2 \
+	--chunk-command "analyze {defines} {incdirs} -- {files}" > out.txt
+diff out.txt - <<EOT
+analyze FOO=1 $DIR/include -- $DIR/a.sv $DIR/b.sv
+analyze FOO=1 $DIR/include -- $DIR/c.sv
+analyze FOO=1 $DIR/include -- $DIR/d.vhd
+EOT
+
+# Without a chunk size, every source group is one chunk.
+$BENDER script flist --no-default-target --chunk-command "analyze {files}" > out.txt
+diff out.txt - <<EOT
+analyze $DIR/a.sv $DIR/b.sv $DIR/c.sv
+analyze $DIR/d.vhd
+EOT
+
+# The chunk size also applies to the generated scripts.
+$BENDER script vsim --max-files-per-group 1 > out.tcl
+if [ "$(grep -c 'vlog -incr' out.tcl)" != 3 ]; then
+	cat out.tcl
+	echo "expected one vlog command per file" >&2
+	exit 1
+fi
@@ -0,0 +1,49 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+touch a.sv
+echo "
+package:
+  name: top
+
+sources:
+  - a.sv
+" > Bender.yml
+
+HEADER="This script was generated automatically by bender."
+
+for fmt in flist flist-plus; do
+	# No header by default and with `none`.
+	for style in "" "--comment-style none"; do
+		$BENDER script $fmt $style > out
+		if grep -q "$HEADER" out; then
+			echo "unexpected header for $fmt $style" >&2
+			exit 1
+		fi
+	done
+
+	$BENDER script $fmt --comment-style // > out
+	[ "$(head -n1 out)" = "// $HEADER" ]
+	grep -q "a.sv" out
+
+	$BENDER script $fmt --comment-style "#" > out
+	[ "$(head -n1 out)" = "# $HEADER" ]
+	grep -q "a.sv" out
+done
+
+# The per-package file lists of a recursive file list carry the header too.
+$BENDER script flist --comment-style // --recursive-flist flists > out
+[ "$(head -n1 out)" = "// $HEADER" ]
+[ "$(head -n1 flists/top.f)" = "// $HEADER" ]
+
+if $BENDER script vsim --comment-style // &> log; then
+	echo "--comment-style must be rejected for vsim" >&2
+	exit 2
+fi
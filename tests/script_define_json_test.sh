@@ -0,0 +1,47 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+touch a.sv
+echo "
+package:
+  name: top
+
+sources:
+  - a.sv
+" > Bender.yml
+
+echo '{
+  "WIDTH": 32,
+  "NAME": "core",
+  "ENABLE": true,
+  "RATIO": 0.5,
+  "EMPTY": null,
+  "CACHE": { "WAYS": 4, "LINE": { "BYTES": 64 } },
+  "OVERRIDE": 1
+}' > params.json
+
+$BENDER script flist-plus --define-from-json params.json -D OVERRIDE=2 > flist
+for define in WIDTH=32 NAME=core ENABLE=true RATIO=0.5 CACHE_WAYS=4 CACHE_LINE_BYTES=64 OVERRIDE=2; do
+	if ! grep -qx "+define+$define" flist; then
+		cat flist
+		echo "missing define $define" >&2
+		exit 1
+	fi
+done
+grep -qx '+define+EMPTY' flist
+
+# The defines also apply to each source group.
+$BENDER script vsim --define-from-json params.json | grep -q '+define+CACHE_LINE_BYTES=64'
+
+echo '[1, 2]' > list.json
+if $BENDER script flist-plus --define-from-json list.json 2> /dev/null; then
+	echo "non-object define file should be rejected" >&2
+	exit 1
+fi
@@ -0,0 +1,56 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir src include
+touch src/a.sv src/b.sv src/c.sv src/d.sv
+echo "
+package:
+  name: top
+
+sources:
+  - src/a.sv
+  - include_dirs:
+      - include
+    defines:
+      FOO: 1
+    files:
+      - src/b.sv
+      - src/c.sv
+  - src/d.sv
+" > Bender.yml
+
+printf "# changed files\nsrc/a.sv\n./src/c.sv\nsrc/missing.sv\n" > files.txt
+$BENDER script flist-plus --file-list-from files.txt > out.f 2> log
+
+# Only the listed files are emitted.
+if ! grep -q 'src/a.sv' out.f || ! grep -q 'src/c.sv' out.f; then
+	cat out.f
+	echo "listed files missing" >&2
+	exit 1
+fi
+if grep -q 'src/b.sv' out.f || grep -q 'src/d.sv' out.f; then
+	cat out.f
+	echo "unlisted files must be dropped" >&2
+	exit 2
+fi
+
+# The retained files keep the include directories and defines of their group.
+if ! grep -q '+incdir+.*include' out.f || ! grep -q '+define+FOO=1' out.f; then
+	cat out.f
+	echo "group metadata of retained files missing" >&2
+	exit 3
+fi
+
+# Listed files that are not among the sources are reported.
+if ! grep -q 'src/missing.sv' log; then
+	cat log
+	echo "missing warning about unknown file" >&2
+	exit 4
+fi
@@ -0,0 +1,52 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir -p src vendor/rtl vendor/include
+touch src/top.sv vendor/rtl/ip.sv vendor/rtl/ip_pkg.sv
+echo "
+package:
+  name: top
+
+sources:
+  - src/top.sv
+" > Bender.yml
+echo "// vendor file list
++incdir+include
++define+IP_WIDTH=32+IP_FAST
+-f pkg.f
+rtl/ip.sv  # the IP
+" > vendor/ip.f
+echo "rtl/ip_pkg.sv" > vendor/pkg.f
+
+$BENDER script flist-plus --no-relative-path --flist vendor/ip.f > out.f
+
+# The contents of the file list follow the package's sources, with paths
+# relative to the file list.
+if [ "$(grep -v '^+' out.f | grep . | sed "s|$DIR/||" | tr '\n' ' ')" != "src/top.sv vendor/rtl/ip_pkg.sv vendor/rtl/ip.sv " ]; then
+    cat out.f
+    echo "files of the file list missing or misplaced" >&2
+    exit 1
+fi
+for pattern in "+incdir+$DIR/vendor/include" '+define+IP_WIDTH=32' '+define+IP_FAST'; do
+    if ! grep -qxF -- "$pattern" out.f; then
+        cat out.f
+        echo "$pattern missing" >&2
+        exit 2
+    fi
+done
+
+# The include directories and defines apply to the files of the file list in
+# the tool scripts.
+$BENDER script vsim --flist vendor/ip.f > out.tcl
+if ! grep -B8 'ip.sv' out.tcl | grep -q 'IP_WIDTH=32'; then
+    cat out.tcl
+    echo "defines of the file list not applied to its files" >&2
+    exit 3
+fi
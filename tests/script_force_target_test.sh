@@ -0,0 +1,33 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir src
+touch src/top.sv src/forced.sv
+echo "
+package:
+  name: top
+
+sources:
+  - src/top.sv
+  - target: forced
+    files:
+      - src/forced.sv
+" > Bender.yml
+
+$BENDER script flist-plus --no-default-target --force-target forced > out.f
+cat out.f
+grep -qx '+define+TARGET_FORCED' out.f
+grep -q 'src/forced.sv' out.f
+
+$BENDER script flist-plus --no-default-target > out.f
+if grep -q 'TARGET_FORCED\|src/forced.sv' out.f; then
+	echo "target should only be active when forced" >&2
+	exit 1
+fi
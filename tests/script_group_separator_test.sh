@@ -0,0 +1,60 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+touch a.sv b.sv c.sv d.vhd
+echo "
+package:
+  name: top
+
+sources:
+  - files: [a.sv, b.sv]
+  - defines: { C: ~ }
+    files: [c.sv]
+  - defines: { D: ~ }
+    files: [d.vhd]
+" > Bender.yml
+
+# Without a separator, the file list is unchanged.
+$BENDER script flist --relative-path | grep -v '^$' > out.f
+diff out.f - <<EOT
+a.sv
+b.sv
+c.sv
+d.vhd
+EOT
+
+# The separator is emitted between groups, but not before the first one.
+for format in flist flist-plus; do
+	$BENDER script $format --relative-path --group-separator "# ---" > out.f
+	grep -v '^+\|^$' out.f | diff - <(cat <<EOT
+a.sv
+b.sv
+# ---
+c.sv
+# ---
+d.vhd
+EOT
+)
+done
+
+# Custom templates can use the separator too.
+echo '{% for group in file_groups %}{% if not loop.first %}{{ group_separator }}{% endif %}{{ group | length }}{% endfor %}' > custom.tera
+$BENDER script template --template custom.tera --group-separator "|" > out.txt
+if [ "$(cat out.txt)" != "2|1|1" ]; then
+	cat out.txt
+	echo "unexpected template output" >&2
+	exit 1
+fi
+
+# Other formats reject the option.
+if $BENDER script vsim --group-separator "# ---" > /dev/null 2>&1; then
+	echo "vsim accepted --group-separator" >&2
+	exit 1
+fi
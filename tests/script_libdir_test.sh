@@ -0,0 +1,50 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+echo "
+package:
+  name: top
+
+sources:
+  - top.sv
+" > Bender.yml
+touch top.sv
+mkdir lib
+
+# The library directories are emitted with `-y`, followed by `+libext+`.
+$BENDER script flist-plus --relative-path --libdir lib --libdir /opt/cells > out
+if ! grep -q "^-y lib$" out || ! grep -q "^-y /opt/cells$" out || ! grep -q "^+libext+.v$" out; then
+	cat out
+	echo "flist-plus should list the library directories" >&2
+	exit 1
+fi
+
+# Several extensions are joined.
+$BENDER script vsim --libdir lib --libext .v --libext .sv > out
+if ! grep -q -- '-y "$ROOT/lib"' out || ! grep -q '"+libext+.v+.sv"' out; then
+	cat out
+	echo "vsim should pass the library directories to vlog" >&2
+	exit 2
+fi
+
+# Without library directories, nothing is emitted.
+$BENDER script vcs > out
+if grep -q -- "-y\|+libext+" out; then
+	cat out
+	echo "no library directories should be emitted by default" >&2
+	exit 3
+fi
+
+# Formats without library directories reject them.
+if $BENDER script flist --libdir lib > out 2> log; then
+	cat out log
+	echo "flist should reject library directories" >&2
+	exit 4
+fi
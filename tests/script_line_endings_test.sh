@@ -0,0 +1,56 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+echo "
+package:
+  name: top
+
+sources:
+  - include_dirs: [include]
+    defines: { FOO: 1 }
+    files:
+      - a.sv
+      - b.vhd
+" > Bender.yml
+touch a.sv b.vhd
+mkdir include
+
+# A template with Windows line endings mixed with Unix ones.
+printf 'first\r\nsecond\n{%% for file in all_files %%}{{ file }}\r\n{%% endfor %%}' > mixed.tera
+
+# By default, all lines end in LF.
+$BENDER script template --template mixed.tera > out
+if grep -q $'\r' out || [ "$(wc -l < out)" -ne 4 ]; then
+	od -c out
+	echo "output should only contain LF line endings" >&2
+	exit 1
+fi
+$BENDER script vsim > out
+if grep -q $'\r' out; then
+	od -c out
+	echo "vsim output should only contain LF line endings" >&2
+	exit 2
+fi
+
+# With crlf, every line ends in CRLF.
+for format in vsim flist; do
+	$BENDER script $format --normalize-line-endings crlf > out
+	if grep -v -q $'\r$' out || grep -q $'\r\r' out; then
+		od -c out
+		echo "$format output should only contain CRLF line endings" >&2
+		exit 3
+	fi
+done
+$BENDER script template --template mixed.tera --normalize-line-endings crlf > out
+if [ "$(grep -c $'\r$' out)" -ne 4 ] || grep -q $'\r\r' out; then
+	od -c out
+	echo "template output should only contain CRLF line endings" >&2
+	exit 4
+fi
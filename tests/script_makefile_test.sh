@@ -0,0 +1,40 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir inc
+touch a.sv b.sv c.vhd inc/a.svh inc/b.svh
+echo "
+package:
+  name: top
+
+sources:
+  - headers: [inc/a.svh]
+    files:
+      - a.sv
+      - headers: [inc/b.svh]
+        files: [b.sv]
+  - c.vhd
+" > Bender.yml
+
+$BENDER script makefile > sources.mk
+grep -qx '    $(BENDER_ROOT)/c.vhd' sources.mk
+grep -qx '$(BENDER_ROOT)/a.sv: $(BENDER_ROOT)/inc/a.svh' sources.mk
+grep -qx '$(BENDER_ROOT)/b.sv: $(BENDER_ROOT)/inc/a.svh $(BENDER_ROOT)/inc/b.svh' sources.mk
+
+# The fragment can be included and rebuilds when a header changes.
+printf 'include sources.mk\nstamp: $(BENDER_PREREQUISITES)\n\ttouch $@\n' > Makefile
+make -s stamp
+make -q stamp
+sleep 1
+touch inc/b.svh
+if make -q stamp; then
+	echo "stamp should be out of date after touching a header" >&2
+	exit 1
+fi
@@ -0,0 +1,75 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo
+mkdir top
+
+cd "$DIR"/foo
+git init
+echo "
+package:
+  name: foo
+
+sources:
+  - foo.sv
+" > Bender.yml
+touch foo.sv
+git add Bender.yml foo.sv
+git -c user.name=test -c user.email=test@example.com commit -m "v0.1.0"
+git tag v0.1.0
+REV=$(git rev-parse HEAD)
+
+cd "$DIR"/top
+echo "
+package:
+  name: top
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: \"0.1\" }
+
+sources:
+  - top.sv
+" > Bender.yml
+touch top.sv
+$BENDER update
+
+for format in vsim vcs synopsys vivado yosys verilator; do
+	$BENDER script $format --include-manifest-metadata > $format.out
+	if ! grep -q "Package \`foo\` version 0.1.0 revision $REV from \`file://$DIR/foo\`" $format.out; then
+		cat $format.out
+		echo "missing metadata of dependency for $format" >&2
+		exit 1
+	fi
+	grep -q 'Package `top` at' $format.out
+
+	# Without the flag, no metadata is emitted.
+	$BENDER script $format > $format.plain
+	if grep -q 'Package `' $format.plain; then
+		cat $format.plain
+		echo "unexpected metadata for $format" >&2
+		exit 2
+	fi
+done
+
+# The comments use the syntax of the tool.
+grep -q '^# Package `foo`' vsim.out
+grep -q '^// Package `foo`' verilator.out
+
+# The metadata comment precedes the files of the package.
+foo_line=$(grep -n 'Package `foo`' vsim.out | cut -d: -f1)
+file_line=$(grep -n 'foo.sv' vsim.out | cut -d: -f1)
+[ "$foo_line" -lt "$file_line" ]
+
+# File lists have no comment syntax to emit the metadata with.
+if $BENDER script flist --include-manifest-metadata &> log; then
+	cat log
+	echo "flist with metadata should fail" >&2
+	exit 3
+fi
@@ -0,0 +1,46 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir src
+touch src/top.sv
+echo "
+package:
+  name: top
+
+sources:
+  - defines:
+      GROUP_DEFINE: ~
+    files:
+      - src/top.sv
+" > Bender.yml
+
+# By default, the active targets are defined.
+$BENDER script flist-plus -t foo -D USER_DEFINE > out.f
+grep -qx '+define+TARGET_FOO' out.f
+
+# With the flag, only the user and manifest defines remain.
+$BENDER script flist-plus -t foo -D USER_DEFINE --no-default-defines > out.f
+if grep -q 'TARGET_' out.f; then
+	cat out.f
+	echo "TARGET_* defines should be suppressed" >&2
+	exit 1
+fi
+grep -qx '+define+USER_DEFINE' out.f
+grep -qx '+define+GROUP_DEFINE' out.f
+
+# The same holds for the defines-only output of Vivado.
+$BENDER script vivado --only-defines -D USER_DEFINE --no-default-defines > out.tcl
+if grep -q 'TARGET_' out.tcl; then
+	cat out.tcl
+	echo "TARGET_* defines should be suppressed with --only-defines" >&2
+	exit 2
+fi
+grep -q 'USER_DEFINE' out.tcl
+grep -q 'GROUP_DEFINE' out.tcl
@@ -0,0 +1,67 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo top
+
+echo "
+package:
+  name: foo
+
+sources:
+  - foo.sv
+" > foo/Bender.yml
+touch foo/foo.sv
+
+cd "$DIR"/top
+echo "
+package:
+  name: top
+
+dependencies:
+  foo: { path: \"../foo\" }
+
+sources:
+  - target: asic
+    files:
+      - asic.sv
+  - target: not(asic)
+    target_defines:
+      simulation:
+        SIM: 1
+    files:
+      - fpga.sv
+" > Bender.yml
+touch asic.sv fpga.sv
+$BENDER update
+
+# Normally, only one of the mutually exclusive groups is emitted.
+$BENDER script flist > filtered
+if grep -q 'asic.sv' filtered; then
+	cat filtered
+	echo "target filter not applied" >&2
+	exit 1
+fi
+
+# Without the target filter, all files and defines are emitted.
+$BENDER script flist-plus --no-target-filter > all
+grep -q 'asic.sv' all
+grep -q 'fpga.sv' all
+grep -q 'foo.sv' all
+grep -q '+define+SIM=1' all
+
+# The package filters still apply.
+$BENDER script flist --no-target-filter -e foo > no_foo
+grep -q 'asic.sv' no_foo
+grep -q 'fpga.sv' no_foo
+if grep -q 'foo.sv' no_foo; then
+	cat no_foo
+	echo "excluded package emitted" >&2
+	exit 2
+fi
@@ -0,0 +1,42 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+touch a.sv
+echo "
+package:
+  name: top
+
+sources:
+  - a.sv
+" > Bender.yml
+
+# Every format is written to its conventional filename.
+$BENDER script flist --output-dir out > stdout.txt
+$BENDER script vsim --output-dir out >> stdout.txt
+if [ -s stdout.txt ]; then
+	cat stdout.txt
+	echo "script printed to stdout despite --output-dir" >&2
+	exit 1
+fi
+$BENDER script flist > flist.expected
+$BENDER script vsim > vsim.expected
+diff flist.expected out/compile.flist
+diff vsim.expected out/vsim.tcl
+
+# An explicit output file is written verbatim.
+$BENDER script flist -o explicit.f
+diff flist.expected explicit.f
+
+# Custom templates have no conventional filename.
+echo "{{ root }}" > custom.tera
+if $BENDER script template --template custom.tera --output-dir out 2>/dev/null; then
+	echo "template format accepted --output-dir" >&2
+	exit 1
+fi
@@ -0,0 +1,56 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir -p foo/rtl foo/include
+mkdir top
+
+cd "$DIR"/foo
+git init
+echo "
+package:
+  name: foo
+
+export_include_dirs:
+  - include
+
+sources:
+  - rtl/foo.sv
+" > Bender.yml
+touch rtl/foo.sv include/foo.svh
+git add Bender.yml rtl/foo.sv include/foo.svh
+git -c user.name=test -c user.email=test@example.com commit -m "v0.1.0"
+git tag v0.1.0
+
+cd "$DIR"/top
+echo "
+package:
+  name: top
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: \"0.1\" }
+
+sources:
+  - top.sv
+" > Bender.yml
+touch top.sv
+$BENDER update
+FOO="$($BENDER path foo)"
+
+# The emitted paths map to keys independent of the checkout location.
+$BENDER script flist --no-relative-path --path-map map.json > out
+python3 - "$DIR" "$FOO" <<'PY'
+import json, sys
+top, foo = sys.argv[1] + "/top", sys.argv[2]
+paths = json.load(open("map.json"))
+assert paths[foo + "/rtl/foo.sv"] == "foo/rtl/foo.sv", paths
+assert paths[foo + "/include"] == "foo/include", paths
+assert paths[top + "/top.sv"] == "top/top.sv", paths
+PY
+grep -q "$FOO/rtl/foo.sv" out
@@ -0,0 +1,51 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+touch a.sv b.sv
+echo "
+package:
+  name: top
+
+sources:
+  - defines:
+      FOO: 1
+    target_defines:
+      simulation:
+        SIM: ~
+    files:
+      - a.sv
+      - defines:
+          BAR: ~
+        files: [b.sv]
+" > Bender.yml
+
+# Global, per-group, target, and command line defines are all prefixed.
+for format in flist-plus vsim verilator; do
+	$BENDER script $format -t simulation --prefix-defines PFX -D CLI=2 > $format.out
+	for define in PFX_TARGET_SIMULATION PFX_FOO=1 PFX_SIM PFX_CLI=2; do
+		if ! grep -q "+define+$define\b" $format.out; then
+			cat $format.out
+			echo "missing define $define in $format" >&2
+			exit 1
+		fi
+	done
+	if grep -o '+define+[A-Z_]*' $format.out | grep -qv '+define+PFX_'; then
+		cat $format.out
+		echo "unprefixed define in $format" >&2
+		exit 1
+	fi
+done
+grep -q '+define+PFX_BAR' vsim.out
+
+$BENDER script template_json --prefix-defines PFX > out.json
+if grep -q '"TARGET_\|"FOO"\|"BAR"' out.json; then
+	echo "unprefixed define in template context" >&2
+	exit 1
+fi
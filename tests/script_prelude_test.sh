@@ -0,0 +1,50 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir src scripts
+touch src/top.sv
+echo "vlib my_lib" > scripts/prelude.tcl
+echo "
+package:
+  name: top
+
+script_prelude:
+  vsim: scripts/prelude.tcl
+
+sources:
+  - src/top.sv
+" > Bender.yml
+
+# The prelude follows the header, also when called from a subdirectory.
+cd src
+$BENDER script vsim > ../out.tcl
+cd ..
+if [ "$(sed -n 2p out.tcl)" != "vlib my_lib" ]; then
+	cat out.tcl
+	echo "prelude missing after the header" >&2
+	exit 1
+fi
+
+# Other formats are not affected.
+$BENDER script synopsys > out.tcl
+if grep -q 'my_lib' out.tcl; then
+	cat out.tcl
+	echo "prelude should only be added for vsim" >&2
+	exit 2
+fi
+
+# A missing prelude file is an error.
+rm scripts/prelude.tcl
+if $BENDER script vsim &> log; then
+	cat log
+	echo "missing prelude should fail" >&2
+	exit 3
+fi
+grep -q 'script prelude' log
@@ -0,0 +1,42 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir -p dep/src top/src
+touch dep/src/dep.sv top/src/top.sv
+echo "
+package:
+  name: dep
+
+sources:
+  - src/dep.sv
+" > dep/Bender.yml
+echo "
+package:
+  name: top
+
+dependencies:
+  dep: { path: \"$DIR/dep\" }
+
+sources:
+  - src/top.sv
+" > top/Bender.yml
+
+cd "$DIR"/top
+$BENDER script flist --no-relative-path --recursive-flist deps > top.f
+cat top.f
+
+printf -- "-f deps/dep.f\n-f deps/top.f\n" > expected.f
+if ! diff expected.f top.f; then
+	echo "top file list should reference each package's file list" >&2
+	exit 1
+fi
+grep -q "$DIR/dep/src/dep.sv" deps/dep.f
+grep -q "$DIR/top/src/top.sv" deps/top.f
+! grep -q "top.sv" deps/dep.f
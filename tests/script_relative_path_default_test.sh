@@ -0,0 +1,65 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+echo "
+package:
+  name: top
+
+sources:
+  - include_dirs:
+      - include
+    files:
+      - src/top.sv
+" > Bender.yml
+mkdir src include
+touch src/top.sv
+echo '{{ relativize_path }}' > flag.tera
+
+# File lists use relative paths by default.
+$BENDER script flist > out
+if ! grep -q "^src/top.sv$" out; then
+	cat out
+	echo "flist should use relative paths by default" >&2
+	exit 1
+fi
+$BENDER script flist-plus > out
+if ! grep -q "^+incdir+include$" out || ! grep -q "^src/top.sv$" out; then
+	cat out
+	echo "flist-plus should use relative paths by default" >&2
+	exit 6
+fi
+
+# Custom templates use absolute paths by default.
+$BENDER script template --template flag.tera > out
+if ! grep -q "^false$" out; then
+	cat out
+	echo "templates should use absolute paths by default" >&2
+	exit 2
+fi
+
+# The flags override the default of the format, the last one applies.
+$BENDER script flist --no-relative-path > out
+if ! grep -q "^$DIR/src/top.sv$" out; then
+	cat out
+	echo "--no-relative-path should use absolute paths" >&2
+	exit 3
+fi
+$BENDER script template --template flag.tera --relative-path > out
+if ! grep -q "^true$" out; then
+	cat out
+	echo "--relative-path should use relative paths" >&2
+	exit 4
+fi
+$BENDER script flist --no-relative-path --relative-path > out
+if ! grep -q "^src/top.sv$" out; then
+	cat out
+	echo "the last flag should apply" >&2
+	exit 5
+fi
@@ -0,0 +1,62 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir -p ext/src ext/include top/src
+touch ext/src/ext.sv ext/include/ext.svh top/src/top.sv
+echo "
+package:
+  name: ext
+
+export_include_dirs:
+  - include
+
+sources:
+  - src/ext.sv
+" > ext/Bender.yml
+
+cd "$DIR"/top
+echo "
+package:
+  name: top
+
+dependencies:
+  ext: { path: \"$DIR/ext\" }
+
+sources:
+  - src/top.sv
+" > Bender.yml
+
+# The first matching rule applies, even if a later one matches as well.
+$BENDER script flist-plus --no-relative-path \
+	--replace-path "$DIR/ext=\$EXT" \
+	--replace-path "$DIR=\$WORK" > out.f
+cat out.f
+grep -qxF '+incdir+$EXT/include' out.f
+grep -qxF '$EXT/src/ext.sv' out.f
+grep -qxF '$WORK/top/src/top.sv' out.f
+
+# Paths relativized to the root are not rewritten.
+$BENDER script vsim \
+	--replace-path "$DIR/ext=\$EXT" \
+	--replace-path "$DIR=\$WORK" > out.tcl
+grep -qF '"$EXT/src/ext.sv"' out.tcl
+grep -qF '"$ROOT/src/top.sv"' out.tcl
+if grep -q 'WORK' out.tcl; then
+	cat out.tcl
+	echo "relativized paths should not be rewritten" >&2
+	exit 1
+fi
+
+if $BENDER script flist --replace-path "no-separator" &> log; then
+	cat log
+	echo "rule without separator should fail" >&2
+	exit 2
+fi
+grep -q 'expected `FROM=TO`' log
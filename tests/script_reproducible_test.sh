@@ -0,0 +1,68 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+for pkg in alpha beta gamma; do
+	mkdir -p $pkg/include
+	touch $pkg/$pkg.sv
+	echo "
+package:
+  name: $pkg
+
+export_include_dirs:
+  - include
+
+sources:
+  - include_dirs: [include]
+    defines:
+      ${pkg^^}_ZETA: ~
+      ${pkg^^}_ALPHA: 1
+      ${pkg^^}_MID: \"x\"
+    files:
+      - $pkg.sv
+" > $pkg/Bender.yml
+done
+mkdir top
+touch top/top.sv
+echo "
+package:
+  name: top
+
+dependencies:
+  gamma: { path: \"../gamma\" }
+  alpha: { path: \"../alpha\" }
+  beta: { path: \"../beta\" }
+
+sources:
+  - defines:
+      Z: ~
+      A: ~
+    files:
+      - top.sv
+" > top/Bender.yml
+
+cd "$DIR"/top
+for run in 1 2; do
+	rm -rf Bender.lock .bender
+	$BENDER update
+	for fmt in flist-plus vsim vcs verilator template_json; do
+		$BENDER script $fmt > $fmt.$run
+	done
+done
+
+for fmt in flist-plus vsim vcs verilator template_json; do
+	if ! cmp $fmt.1 $fmt.2; then
+		diff $fmt.1 $fmt.2
+		echo "script $fmt differs between runs" >&2
+		exit 1
+	fi
+done
+
+# Defines keep the order in which they appear in the manifest.
+[ "$(grep -o '+define+ALPHA_[A-Z]*' flist-plus.1 | tr '\n' ' ')" = "+define+ALPHA_ZETA +define+ALPHA_ALPHA +define+ALPHA_MID " ]
@@ -0,0 +1,58 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir src include
+touch src/a.sv src/b.v src/c.vhd src/d.vhdl
+echo "
+package:
+  name: top
+
+sources:
+  - include_dirs:
+      - include
+    defines:
+      FOO: 1
+    files:
+      - src/a.sv
+      - src/c.vhd
+  - src/b.v
+  - src/d.vhdl
+" > Bender.yml
+
+$BENDER script flist-plus --split-by-language-dir out
+
+# The Verilog file list carries the Verilog files with their include
+# directories and defines.
+for pattern in '+incdir+.*include' '+define+FOO=1' 'src/a.sv' 'src/b.v'; do
+    if ! grep -q -- "$pattern" out/verilog.f; then
+        cat out/verilog.f
+        echo "verilog.f lacks $pattern" >&2
+        exit 1
+    fi
+done
+if grep -q 'vhd' out/verilog.f; then
+    cat out/verilog.f
+    echo "verilog.f contains VHDL files" >&2
+    exit 2
+fi
+
+# The VHDL file list only lists the VHDL files.
+if [ "$(grep . out/vhdl.f | sed 's|.*/||' | tr '\n' ' ')" != "c.vhd d.vhdl " ]; then
+    cat out/vhdl.f
+    echo "vhdl.f does not list exactly the VHDL files" >&2
+    exit 3
+fi
+
+# The option is only supported for file lists.
+if $BENDER script vsim --split-by-language-dir out &> log; then
+    cat log
+    echo "--split-by-language-dir must be rejected for vsim" >&2
+    exit 4
+fi
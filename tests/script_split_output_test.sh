@@ -0,0 +1,76 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir -p foo/include top
+
+echo "
+package:
+  name: foo
+
+export_include_dirs:
+  - include
+
+sources:
+  - defines:
+      FOO: 1
+    files:
+      - foo.sv
+" > foo/Bender.yml
+touch foo/foo.sv
+
+cd "$DIR"/top
+echo "
+package:
+  name: top
+
+dependencies:
+  foo: { path: \"../foo\" }
+
+sources:
+  - top.sv
+" > Bender.yml
+touch top.sv
+$BENDER update
+
+# One script per package, included in dependency order.
+$BENDER script vsim --split-output out
+[ -f out/foo.tcl ]
+[ -f out/top.tcl ]
+if ! grep -q "source \"$DIR/top/out/foo.tcl\"" out/vsim.tcl; then
+	cat out/vsim.tcl
+	echo "top-level script does not source package script" >&2
+	exit 1
+fi
+foo_line=$(grep -n 'foo.tcl' out/vsim.tcl | cut -d: -f1)
+top_line=$(grep -n 'top.tcl' out/vsim.tcl | cut -d: -f1)
+[ "$foo_line" -lt "$top_line" ]
+
+# The package scripts only carry the package's own sources and defines.
+grep -q 'foo.sv' out/foo.tcl
+grep -q '+define+FOO=1' out/foo.tcl
+if grep -q 'foo.sv\|+define+FOO' out/top.tcl; then
+	cat out/top.tcl
+	echo "package script contains sources of other packages" >&2
+	exit 2
+fi
+grep -q "+incdir+.*foo/include" out/top.tcl
+
+# File lists include the package file lists with `-f`.
+$BENDER script flist --split-output flist
+grep -qxF -- "-f $DIR/top/flist/foo.flist" flist/compile.flist
+grep -qxF -- "-f $DIR/top/flist/top.flist" flist/compile.flist
+grep -q 'foo.sv' flist/foo.flist
+
+# Formats that set defines globally cannot be split.
+if $BENDER script vivado --split-output vivado &> log; then
+	cat log
+	echo "vivado split output should fail" >&2
+	exit 3
+fi
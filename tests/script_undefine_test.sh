@@ -0,0 +1,63 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir -p dep/src top/src
+touch dep/src/dep.sv top/src/top.sv
+echo "
+package:
+  name: dep
+
+sources:
+  - defines:
+      FOO: 1
+      KEEP: 2
+    files:
+      - src/dep.sv
+" > dep/Bender.yml
+
+cd "$DIR"/top
+echo "
+package:
+  name: top
+
+dependencies:
+  dep: { path: \"../dep\" }
+
+sources:
+  - src/top.sv
+" > Bender.yml
+
+# A define inherited from a dependency is removed from every format.
+for format in flist-plus vsim synopsys; do
+    $BENDER script $format -U FOO > out
+    if grep -q 'FOO' out; then
+        cat out
+        echo "define of dependency not removed for $format" >&2
+        exit 1
+    fi
+    if ! grep -q 'KEEP' out; then
+        cat out
+        echo "other define removed for $format" >&2
+        exit 2
+    fi
+done
+
+# Undefines are applied last, also to defines given on the command line.
+$BENDER script flist-plus -D BAR=3 -D BAZ -U BAR --undefine TARGET_FLIST > out
+if grep -q 'BAR' out || grep -q 'TARGET_FLIST' out; then
+    cat out
+    echo "command line or target define not removed" >&2
+    exit 3
+fi
+if ! grep -q '+define+BAZ' out; then
+    cat out
+    echo "other command line define removed" >&2
+    exit 4
+fi
@@ -0,0 +1,47 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir src
+touch src/top.sv
+echo "
+package:
+  name: top
+
+sources:
+  - src/top.sv
+" > Bender.yml
+
+# Warnings are fatal by default.
+$BENDER script verilator > out.f
+if grep -qF -- "-Wno-fatal" out.f; then
+	cat out.f
+	echo "warnings should be fatal by default" >&2
+	exit 1
+fi
+
+# Without aborting on errors, warnings no longer stop verilator.
+$BENDER script verilator --no-abort-on-error --verilator-arg=-Wall --verilator-arg=--timing > out.f
+if [ "$(sed -n 1,3p out.f)" != "$(printf -- '-Wno-fatal\n-Wall\n--timing')" ]; then
+	cat out.f
+	echo "verilator arguments missing at the top of the file list" >&2
+	exit 2
+fi
+if ! grep -q 'src/top.sv' out.f; then
+	cat out.f
+	echo "source file missing" >&2
+	exit 3
+fi
+
+# The arguments are rejected for other formats.
+if $BENDER script vsim --verilator-arg=-Wall &> log; then
+	cat log
+	echo "--verilator-arg must be rejected for vsim" >&2
+	exit 4
+fi
@@ -0,0 +1,61 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+echo "
+package:
+  name: top
+
+sources:
+  - library: mylib
+    files:
+      - a.vhd
+      - b.sv
+  - c.vhd
+" > Bender.yml
+touch a.vhd b.sv c.vhd
+
+$BENDER script vsim > out
+if [ "$(grep -c -- '-work mylib' out)" != 1 ]; then
+    cat out
+    echo "vsim script does not compile the library's VHDL files into it" >&2
+    exit 1
+fi
+if ! grep -B2 -- '-work mylib' out | grep -q 'vcom'; then
+    cat out
+    echo "library not passed to vcom" >&2
+    exit 2
+fi
+
+$BENDER script vcs > out
+if [ "$(grep -c -- '-work mylib' out)" != 1 ]; then
+    cat out
+    echo "vcs script does not compile the library's VHDL files into it" >&2
+    exit 3
+fi
+
+$BENDER script flist-plus --comment-style // > out
+if [ "$(grep -c '^// library: mylib$' out)" != 1 ]; then
+    cat out
+    echo "flist-plus does not annotate the library" >&2
+    exit 4
+fi
+if ! grep -q '^// library: work$' out; then
+    cat out
+    echo "flist-plus does not annotate the default library" >&2
+    exit 5
+fi
+
+# Without a comment style, the libraries are not annotated.
+$BENDER script flist-plus > out
+if grep -q 'library:' out; then
+    cat out
+    echo "flist-plus must not annotate libraries without comments" >&2
+    exit 6
+fi
@@ -0,0 +1,67 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+echo "
+package:
+  name: top
+
+sources:
+  - library: mylib
+    files:
+      - a.vhd
+  - b.vhd
+  - c.sv
+" > Bender.yml
+touch a.vhd b.vhd c.sv
+
+# Without the option, no library directories are set up.
+$BENDER script vsim > out
+if grep -q '^vmap' out; then
+    cat out
+    echo "libraries mapped without --vhdl-relative-work" >&2
+    exit 1
+fi
+
+# Each VHDL library gets its own directory.
+for format in vsim riviera; do
+    $BENDER script $format --vhdl-relative-work libs > out
+    for lib in mylib work; do
+        if ! grep -qx "vlib \"libs/$lib\"" out || ! grep -qx "vmap $lib \"libs/$lib\"" out; then
+            cat out
+            echo "$format script does not set up library $lib" >&2
+            exit 2
+        fi
+    done
+done
+if grep -qx 'vlib work' out; then
+    cat out
+    echo "riviera script must not create a local work library" >&2
+    exit 3
+fi
+if ! grep -A1 'vcom' out | grep -q -- '-work mylib'; then
+    cat out
+    echo "riviera script does not compile into the library" >&2
+    exit 4
+fi
+
+$BENDER script synopsys --vhdl-relative-work libs > out
+if ! grep -qx 'define_design_lib mylib -path "libs/mylib"' out \
+    || ! grep -q 'analyze -format vhdl -library mylib' out; then
+    cat out
+    echo "synopsys script does not set up the library" >&2
+    exit 5
+fi
+
+# The option is rejected for other formats.
+if $BENDER script vcs --vhdl-relative-work libs &> log; then
+    cat log
+    echo "--vhdl-relative-work must be rejected for vcs" >&2
+    exit 6
+fi
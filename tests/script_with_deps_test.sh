@@ -0,0 +1,90 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+# top depends on mid and other, mid bundles leaf via glue, which has no sources.
+mkdir top mid glue leaf other
+echo "
+package:
+  name: leaf
+
+sources:
+  - leaf.sv
+" > leaf/Bender.yml
+echo "
+package:
+  name: glue
+
+dependencies:
+  leaf: { path: \"$DIR/leaf\" }
+" > glue/Bender.yml
+echo "
+package:
+  name: mid
+
+dependencies:
+  glue: { path: \"$DIR/glue\" }
+
+sources:
+  - mid.sv
+" > mid/Bender.yml
+echo "
+package:
+  name: other
+
+sources:
+  - other.sv
+" > other/Bender.yml
+echo "
+package:
+  name: top
+
+dependencies:
+  mid: { path: \"$DIR/mid\" }
+  other: { path: \"$DIR/other\" }
+
+sources:
+  - top.sv
+" > top/Bender.yml
+for pkg in top mid leaf other; do
+    touch $pkg/$pkg.sv
+done
+
+cd "$DIR"/top
+$BENDER update
+
+$BENDER script flist --package mid --with-deps > out
+for file in mid/mid.sv leaf/leaf.sv; do
+    if ! grep -q "$file" out; then
+        cat out
+        echo "$file missing from the subtree of mid" >&2
+        exit 1
+    fi
+done
+for file in top/top.sv other/other.sv; do
+    if grep -q "$file" out; then
+        cat out
+        echo "$file is not in the subtree of mid" >&2
+        exit 2
+    fi
+done
+
+# Excluded packages are not traversed.
+$BENDER script flist --package mid --with-deps --exclude glue > out
+if grep -q "leaf/leaf.sv" out; then
+    cat out
+    echo "dependencies of excluded package emitted" >&2
+    exit 3
+fi
+
+if $BENDER script flist --with-deps &> log; then
+    cat log
+    echo "--with-deps without --package should fail" >&2
+    exit 4
+fi
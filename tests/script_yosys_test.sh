@@ -0,0 +1,39 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir include src
+touch include/pkg.svh src/pkg.sv src/top.sv src/sim_only.sv
+echo "
+package:
+  name: top
+
+sources:
+  - include_dirs:
+      - include
+    defines:
+      FOO: 1
+    files:
+      - src/pkg.sv
+      - src/top.sv
+  - target: simulation
+    files:
+      - src/sim_only.sv
+" > Bender.yml
+
+$BENDER script yosys > out.ys
+
+echo "# This script was generated automatically by bender.
+read_verilog -sv -I$DIR/include -DFOO=1 -DTARGET_SYNTHESIS -DTARGET_YOSYS $DIR/src/pkg.sv
+read_verilog -sv -I$DIR/include -DFOO=1 -DTARGET_SYNTHESIS -DTARGET_YOSYS $DIR/src/top.sv" > expected.ys
+
+if ! diff expected.ys out.ys; then
+	echo "unexpected yosys script" >&2
+	exit 1
+fi
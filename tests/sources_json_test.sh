@@ -0,0 +1,52 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir inc
+touch a.sv b.sv c.sv
+echo "
+package:
+  name: top
+
+sources:
+  - include_dirs: [inc]
+    defines:
+      FOO: 1
+    files:
+      - a.sv
+      - target: any(simulation, fpga)
+        defines:
+          BAR: ~
+        files: [b.sv]
+      - c.sv
+" > Bender.yml
+
+$BENDER sources --format json -t simulation > out.json
+python3 - "$DIR" <<'PY'
+import json, sys
+d = sys.argv[1]
+top = json.load(open("out.json"))
+[pkg] = top["groups"]
+assert pkg["package"] == "top" and "files" not in pkg, pkg
+[group] = pkg["groups"]
+assert group["include_dirs"] == [d + "/inc"], group
+assert group["defines"] == {"FOO": "1"}, group
+a, b, c = group["groups"]
+assert a["files"] == [d + "/a.sv"] and c["files"] == [d + "/c.sv"], group
+assert b["target"] == "any(fpga, simulation)", b
+assert b["defines"] == {"BAR": None} and b["files"] == [d + "/b.sv"], b
+PY
+
+$BENDER sources --format json --relative-path --flatten > out.json
+python3 - <<'PY'
+import json
+groups = json.load(open("out.json"))
+assert [g["files"] for g in groups] == [["a.sv", "c.sv"]], groups
+assert groups[0]["include_dirs"] == ["inc"], groups
+PY
@@ -0,0 +1,54 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir inc sim
+touch a.sv b.sv c.sv
+echo "
+package:
+  name: top
+
+sources:
+  - include_dirs: [inc]
+    defines:
+      FOO: 1
+    files:
+      - a.sv
+      - target: simulation
+        include_dirs: [sim]
+        defines:
+          BAR: ~
+        files: [b.sv]
+  - c.sv
+" > Bender.yml
+
+$BENDER sources --flatten --per-file > out.json
+$BENDER sources --flatten --per-file > out2.json
+cmp out.json out2.json
+
+python3 - "$DIR" <<'PY'
+import json, sys
+d = sys.argv[1]
+files = json.load(open("out.json"))
+assert [f["file"] for f in files] == [d + "/a.sv", d + "/c.sv"], files
+assert files[0]["include_dirs"] == [d + "/inc"], files
+assert files[0]["defines"] == {"FOO": "1"}, files
+assert files[1]["defines"] == {}, files
+PY
+
+$BENDER sources --flatten --per-file -t simulation > out.json
+python3 - "$DIR" <<'PY'
+import json, sys
+d = sys.argv[1]
+files = json.load(open("out.json"))
+assert [f["file"] for f in files] == [d + "/a.sv", d + "/b.sv", d + "/c.sv"], files
+assert files[1]["include_dirs"] == [d + "/inc", d + "/sim"], files
+assert files[1]["defines"] == {"FOO": "1", "BAR": None}, files
+assert all(f["package"] == "top" for f in files), files
+PY
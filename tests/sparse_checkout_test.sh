@@ -0,0 +1,62 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo
+mkdir top
+
+# A monorepo with several IPs.
+cd "$DIR"/foo
+git init
+echo "
+package:
+  name: foo
+" > Bender.yml
+mkdir -p hw/ip/a hw/ip/b docs
+touch hw/ip/a/a.sv hw/ip/b/b.sv docs/index.md
+git add .
+git -c user.name=test -c user.email=test@example.com commit -m "v0.1.0"
+git tag v0.1.0
+
+cd "$DIR"/top
+echo "
+package:
+  name: top
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: \"0.1\", sparse: [\"hw/ip/a\"] }
+" > Bender.yml
+$BENDER update
+
+FOO="$($BENDER path foo)"
+if [ ! -f "$FOO"/Bender.yml ] || [ ! -f "$FOO"/hw/ip/a/a.sv ]; then
+    ls -R "$FOO"
+    echo "sparse paths missing from the checkout" >&2
+    exit 1
+fi
+if [ -e "$FOO"/hw/ip/b ] || [ -e "$FOO"/docs ]; then
+    ls -R "$FOO"
+    echo "paths outside the sparse set are checked out" >&2
+    exit 2
+fi
+if ! grep -qF -- "- hw/ip/a" Bender.lock; then
+    cat Bender.lock
+    echo "sparse set not recorded in the lockfile" >&2
+    exit 3
+fi
+
+# Dropping the sparse set restores the full checkout.
+sed -i 's/, sparse: \["hw\/ip\/a"\]//' Bender.yml
+$BENDER update
+if [ ! -f "$FOO"/hw/ip/b/b.sv ] || grep -q 'sparse:' Bender.lock; then
+    ls -R "$FOO"
+    cat Bender.lock
+    echo "full checkout not restored" >&2
+    exit 4
+fi
@@ -0,0 +1,51 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+# The dependency `dep` refers to the package `other`.
+mkdir dep
+touch dep/dep.sv
+echo "
+package:
+  name: other
+
+sources:
+  - dep.sv
+" > dep/Bender.yml
+
+mkdir top
+echo "
+package:
+  name: top
+
+dependencies:
+  dep: { path: \"../dep\" }
+" > top/Bender.yml
+cd top
+
+# By default, the mismatch is a warning.
+$BENDER update 2> stderr
+grep -q "Dependency name and package name do not match" stderr
+
+# With the flag, it is an error naming both the dependency and the package.
+rm Bender.lock
+if $BENDER update --strict-names 2> stderr; then
+	echo "update succeeded despite --strict-names" >&2
+	exit 1
+fi
+grep -q 'Dependency `dep` refers to package `other`' stderr
+
+# The config option also rejects the mismatch for locked packages.
+$BENDER update > /dev/null 2>&1
+echo "strict_names: true" > Bender.local
+if $BENDER sources 2> stderr; then
+	echo "sources succeeded despite strict_names" >&2
+	exit 1
+fi
+grep -q 'Dependency `dep` refers to package `other`' stderr
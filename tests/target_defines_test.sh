@@ -0,0 +1,41 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+touch a.sv
+echo "
+package:
+  name: top
+
+sources:
+  - defines:
+      COMMON: ~
+    target_defines:
+      synthesis:
+        SYNTHESIS: ~
+      any(simulation, vsim):
+        SIM: 1
+    files:
+      - a.sv
+" > Bender.yml
+
+$BENDER script flist-plus -t synthesis > synth
+grep -q '+define+COMMON' synth
+grep -q '+define+SYNTHESIS' synth
+if grep -q '+define+SIM' synth; then
+	echo "simulation define leaked into synthesis" >&2
+	exit 1
+fi
+
+$BENDER script vsim > sim
+grep -q '+define+SIM=1' sim
+if grep -q '+define+SYNTHESIS' sim; then
+	echo "synthesis define leaked into simulation" >&2
+	exit 2
+fi
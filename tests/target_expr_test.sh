@@ -0,0 +1,52 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+touch bare.sv rtl_asic.sv sim.sv nested.sv
+echo "
+package:
+  name: top
+
+sources:
+  - target: rtl
+    files: [bare.sv]
+  - target: all(rtl, not(fpga))
+    files: [rtl_asic.sv]
+  - target: any(vsim, vcs)
+    files: [sim.sv]
+  - target: any(all(rtl, not(any(fpga, gate))), (test))
+    files: [nested.sv]
+" > Bender.yml
+
+check() {
+	$BENDER sources -f $1 > out
+	for f in $2; do
+		grep -q "$f.sv" out || { echo "$f missing for $1" >&2; exit 1; }
+	done
+	for f in $3; do
+		if grep -q "$f.sv" out; then echo "$f unexpected for $1" >&2; exit 1; fi
+	done
+}
+
+check "-t rtl" "bare rtl_asic nested" "sim"
+check "-t rtl -t fpga" "bare" "rtl_asic sim nested"
+check "-t vcs -t test" "sim nested" "bare rtl_asic"
+check "-t rtl -t gate" "bare rtl_asic" "nested"
+
+# Malformed expressions are rejected.
+for expr in "all(rtl, fpga" "rtl fpga" "not(rtl, fpga)" "any()" "all(rtl) )"; do
+	sed -i "s/target: rtl$/target: $expr/" Bender.yml
+	if $BENDER sources &> log; then
+		cat log
+		echo "\`$expr\` should not parse" >&2
+		exit 2
+	fi
+	grep -q "Syntax error in target specification" log
+	sed -i "s/target: $expr$/target: rtl/" Bender.yml
+done
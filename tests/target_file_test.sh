@@ -0,0 +1,46 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+touch a.sv b.sv c.sv
+echo "
+package:
+  name: top
+
+sources:
+  - target: alpha
+    files: [a.sv]
+  - target: beta
+    files: [b.sv]
+  - target: gamma
+    files: [c.sv]
+" > Bender.yml
+printf "# targets of this flow\n\n  alpha  \n# gamma\n" > targets.txt
+
+$BENDER sources -f --target-file targets.txt -t beta > out
+grep -q a.sv out
+grep -q b.sv out
+if grep -q c.sv out; then
+	echo "commented target must be ignored" >&2
+	exit 1
+fi
+
+$BENDER script flist-plus --target-file targets.txt > out
+grep -q '+define+TARGET_ALPHA' out
+grep -q a.sv out
+if grep -q b.sv out; then
+	echo "unexpected target" >&2
+	exit 2
+fi
+
+if $BENDER script flist --target-file missing.txt &> log; then
+	echo "missing target file should fail" >&2
+	exit 3
+fi
+grep -q "Cannot read target file" log
@@ -0,0 +1,72 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+GIT="git -c user.name=test -c user.email=test@example.com"
+mkdir foo
+cd foo
+git init -q
+echo "
+package:
+  name: foo
+" > Bender.yml
+git add Bender.yml
+$GIT commit -q -m "v1.0.0"
+git tag v1.0.0
+RELEASE=$(git rev-parse HEAD)
+for branch in fpga-fix xilinx-fix; do
+	git checkout -q -b $branch v1.0.0
+	echo "# $branch" >> Bender.yml
+	$GIT commit -q -am "$branch"
+done
+FPGA=$(git rev-parse fpga-fix)
+XILINX=$(git rev-parse xilinx-fix)
+
+mkdir "$DIR"/top
+cd "$DIR"/top
+echo "
+package:
+  name: top
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: \"1.0.0\" }
+" > Bender.yml
+echo "
+overrides:
+  foo:
+    - { git: \"file://$DIR/foo\", rev: fpga-fix, target: fpga }
+    - { git: \"file://$DIR/foo\", rev: xilinx-fix, target: xilinx }
+" > Bender.local
+
+# Without targets, the target-specific overrides do not apply.
+$BENDER update --no-checkout
+grep -q "revision: $RELEASE" Bender.lock
+
+# A matching target applies its override.
+$BENDER update --no-checkout -t fpga
+grep -q "revision: $FPGA" Bender.lock
+
+# Among multiple matching overrides, the one declared last wins.
+$BENDER update --no-checkout -t fpga -t xilinx
+grep -q "revision: $XILINX" Bender.lock
+
+# The lock command applies the overrides of its targets.
+$BENDER lock -t fpga
+grep -q "revision: $FPGA" Bender.lock
+
+# Implicit resolution applies the overrides of the command's targets.
+rm Bender.lock
+$BENDER sources -t fpga > /dev/null
+grep -q "revision: $FPGA" Bender.lock
+
+# Commands without targets warn that the overrides do not apply.
+rm Bender.lock
+$BENDER packages > /dev/null 2> log
+grep -q "revision: $RELEASE" Bender.lock
+grep -q "overrides restricted to targets do not apply" log
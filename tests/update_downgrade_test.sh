@@ -0,0 +1,51 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo top
+cd "$DIR"/foo
+git init
+echo "
+package:
+  name: foo
+" > Bender.yml
+git add Bender.yml
+git -c user.name=test -c user.email=test@example.com commit -m "v0.1.0"
+git tag v0.1.0
+echo "# change" >> Bender.yml
+git -c user.name=test -c user.email=test@example.com commit -am "v0.2.0"
+git tag v0.2.0
+
+cd "$DIR"/top
+echo "
+package:
+  name: top
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: \">=0.1.0\" }
+" > Bender.yml
+$BENDER update
+grep -q 'version: 0.2.0' Bender.lock
+
+# Tightening the constraint forces a downgrade, which is refused.
+sed -i 's/>=0.1.0/=0.1.0/' Bender.yml
+if $BENDER update < /dev/null 2> stderr; then
+	echo "downgrade should be refused" >&2
+	exit 1
+fi
+grep -q '`foo` from 0.2.0 to 0.1.0' stderr
+grep -q 'version: 0.2.0' Bender.lock
+
+$BENDER update --allow-downgrade
+grep -q 'version: 0.1.0' Bender.lock
+
+# Upgrades are not affected.
+sed -i 's/=0.1.0/>=0.1.0/' Bender.yml
+$BENDER update < /dev/null
+grep -q 'version: 0.2.0' Bender.lock
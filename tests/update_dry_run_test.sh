@@ -0,0 +1,75 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo
+mkdir bar
+mkdir top
+
+cd "$DIR"/foo
+git init
+echo "
+package:
+  name: foo
+" > Bender.yml
+git add Bender.yml
+git -c user.name=test -c user.email=test@example.com commit -m "v0.1.0"
+git tag v0.1.0
+touch other.sv
+git add other.sv
+git -c user.name=test -c user.email=test@example.com commit -m "v0.2.0"
+git tag v0.2.0
+
+echo "
+package:
+  name: bar
+" > "$DIR"/bar/Bender.yml
+
+cd "$DIR"/top
+echo "
+package:
+  name: top
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: \"0.1\" }
+" > Bender.yml
+
+# Without a lockfile, all packages are added.
+$BENDER update --dry-run > out
+if ! grep -q '^+ foo 0.1.0' out || [ -e Bender.lock ]; then
+	cat out
+	echo "dry run without lockfile should only list the added packages" >&2
+	exit 1
+fi
+
+$BENDER update
+cp Bender.lock expected.lock
+CHECKOUT="$($BENDER path foo)"
+
+# Changed and added packages are listed, without touching the lockfile or
+# the checkouts.
+echo "
+package:
+  name: top
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: \"0.2\" }
+  bar: { path: \"../bar\" }
+" > Bender.yml
+$BENDER update --dry-run > out
+if ! grep -q '^~ foo version 0.1.0 -> 0.2.0' out || ! grep -q '^+ bar' out; then
+	cat out
+	echo "dry run should list the changed packages" >&2
+	exit 2
+fi
+diff expected.lock Bender.lock
+if [ -e "$CHECKOUT/other.sv" ]; then
+	echo "dry run should not check out the dependencies" >&2
+	exit 3
+fi
@@ -0,0 +1,54 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo
+mkdir bar
+
+cd "$DIR"/foo
+git init
+echo "
+package:
+  name: foo
+" > Bender.yml
+git add Bender.yml
+git -c user.name=test -c user.email=test@example.com commit -m "v0.1.0"
+git tag v0.1.0
+echo "# change" >> Bender.yml
+git -c user.name=test -c user.email=test@example.com commit -am "v0.2.0"
+git tag v0.2.0
+
+cd "$DIR"/bar
+echo "
+package:
+  name: bar
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: \"=0.1.0\" }
+" > Bender.yml
+$BENDER update
+grep -q 'version: 0.1.0' Bender.lock
+
+# Relaxing the constraint must not pull in the newer version.
+sed -i 's/=0.1.0/>=0.1.0/' Bender.yml
+$BENDER update --locked-only
+if ! grep -q 'version: 0.1.0' Bender.lock; then
+	cat Bender.lock
+	echo "locked-only update introduced a new version" >&2
+	exit 1
+fi
+
+# Requiring a version outside the lockfile must fail.
+sed -i 's/>=0.1.0/=0.2.0/' Bender.yml
+if $BENDER update --locked-only &> log; then
+	cat log
+	echo "should fail" >&2
+	exit 2
+fi
+grep -q 'version: 0.1.0' Bender.lock
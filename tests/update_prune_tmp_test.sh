@@ -0,0 +1,65 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+# A path dependency without a manifest of its own.
+mkdir foo
+echo "
+package:
+  name: top
+
+dependencies:
+  foo: { path: \"foo\" }
+" > Bender.yml
+
+# A stale manifest left behind by an earlier resolution, which pulls in a
+# dependency that no longer exists.
+write_stale() {
+    mkdir -p .bender/tmp
+    echo "
+package:
+  name: foo
+
+dependencies:
+  bar: { path: \"$DIR/bar\" }
+" > .bender/tmp/foo_manifest.yml
+}
+
+# The stale manifest is kept and shadows the current one when opting out.
+write_stale
+$BENDER update --no-prune-tmp
+if ! grep -q "bar" Bender.lock; then
+    cat Bender.lock
+    echo "stale tmp manifest was not used with --no-prune-tmp" >&2
+    exit 1
+fi
+if [ ! -f .bender/tmp/foo_manifest.yml ]; then
+    echo "--no-prune-tmp removed the tmp manifest" >&2
+    exit 2
+fi
+
+# By default, stale manifests are removed before resolving.
+$BENDER update
+if [ -e .bender/tmp/foo_manifest.yml ]; then
+    echo "stale tmp manifest was not removed by update" >&2
+    exit 3
+fi
+if grep -q "bar" Bender.lock; then
+    cat Bender.lock
+    echo "stale tmp manifest leaked into the lockfile" >&2
+    exit 4
+fi
+
+# The same holds for `lock`.
+write_stale
+$BENDER lock
+if [ -e .bender/tmp/foo_manifest.yml ]; then
+    echo "stale tmp manifest was not removed by lock" >&2
+    exit 5
+fi
@@ -0,0 +1,86 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+# Create a git package with the given name, dependencies, and version tags.
+make_pkg() {
+	local name=$1
+	local deps=$2
+	shift 2
+	mkdir "$DIR"/$name
+	cd "$DIR"/$name
+	git init -q
+	for version in "$@"; do
+		echo "
+package:
+  name: $name
+  # $version
+$deps
+" > Bender.yml
+		git add Bender.yml
+		git -c user.name=test -c user.email=test@example.com commit -q -m "v$version"
+		git tag v$version
+	done
+	cd "$DIR"
+}
+
+make_pkg c "" 0.1.0 0.2.0
+make_pkg a "dependencies:
+  c: { git: \"file://$DIR/c\", version: \"=0.1.0\" }" 0.1.0
+make_pkg b "dependencies:
+  c: { git: \"file://$DIR/c\", version: \"=0.2.0\" }" 0.1.0
+
+mkdir top
+cd "$DIR"/top
+echo "
+package:
+  name: top
+
+dependencies:
+  a: { git: \"file://$DIR/a\", version: \"0.1\" }
+  b: { git: \"file://$DIR/b\", version: \"0.1\" }
+" > Bender.yml
+
+# The conflicting requirements on `c` cannot be resolved.
+if $BENDER update --repro "$DIR"/repro.tar.gz &> log; then
+	cat log
+	echo "update should fail" >&2
+	exit 1
+fi
+if [ ! -f "$DIR"/repro.tar.gz ]; then
+	cat log
+	echo "no reproduction archive written" >&2
+	exit 2
+fi
+
+# The archive contains the manifests of all versions in a local registry.
+mkdir "$DIR"/repro
+tar -xzf "$DIR"/repro.tar.gz -C "$DIR"/repro
+cd "$DIR"/repro/bender-repro
+[ -f Bender.yml ]
+grep -q 'registry: registry' Bender.local
+python3 -c "import json; assert sorted(json.load(open('registry/c.json'))['versions']) == ['0.1.0', '0.2.0']"
+grep -q '=0.2.0' registry/b/0.1.0/Bender.yml
+if grep -q 'git' Bender.yml registry/*/*/Bender.yml; then
+	echo "git dependencies not converted" >&2
+	exit 3
+fi
+
+# The failure reproduces offline, without the original repositories.
+mv "$DIR"/c "$DIR"/c.moved
+if $BENDER --local update &> log; then
+	cat log
+	echo "reproduction should fail" >&2
+	exit 4
+fi
+if ! grep -q 'conflicts with other requirements on dependency `c`' log; then
+	cat log
+	echo "reproduction fails differently" >&2
+	exit 5
+fi
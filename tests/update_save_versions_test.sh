@@ -0,0 +1,54 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo
+mkdir bar
+mkdir baz
+mkdir top
+
+# A tagged and an untagged git dependency.
+for pkg in foo bar; do
+	cd "$DIR"/$pkg
+	git init
+	echo "
+package:
+  name: $pkg
+" > Bender.yml
+	git add Bender.yml
+	git -c user.name=test -c user.email=test@example.com commit -m "v0.1.0"
+	git tag v0.1.0
+	touch other.sv
+	git add other.sv
+	git -c user.name=test -c user.email=test@example.com commit -m "untagged"
+done
+BAR_REV="$(git -C "$DIR"/bar rev-parse HEAD)"
+
+echo "
+package:
+  name: baz
+" > "$DIR"/baz/Bender.yml
+
+cd "$DIR"/top
+echo "
+package:
+  name: top
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: \"0.1\" }
+  bar: { git: \"file://$DIR/bar\", rev: \"$BAR_REV\" }
+  baz: { path: \"../baz\" }
+" > Bender.yml
+
+$BENDER update --save-versions versions.yml
+if [ "$(cat versions.yml)" != "foo: 0.1.0" ]; then
+	cat versions.yml
+	echo "only the dependency on a version tag should be recorded" >&2
+	exit 1
+fi
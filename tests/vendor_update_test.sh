@@ -0,0 +1,66 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+GIT="git -c user.name=test -c user.email=test@example.com"
+
+mkdir upstream
+cd "$DIR"/upstream
+git init
+printf "one\ntwo\nthree\nfour\nfive\n" > file.txt
+git add file.txt
+$GIT commit -m "initial"
+REV1=$(git rev-parse HEAD)
+sed -i 's/five/FIVE/' file.txt
+$GIT commit -am "unrelated change"
+REV2=$(git rev-parse HEAD)
+sed -i 's/one/ONE/' file.txt
+$GIT commit -am "conflicting change"
+REV3=$(git rev-parse HEAD)
+
+mkdir -p "$DIR"/top/patches
+cd "$DIR"/top
+echo "
+package:
+  name: top
+
+vendor_package:
+  - name: up
+    target_dir: deps/up
+    upstream: { git: \"file://$DIR/upstream\", rev: \"$REV1\" }
+    patch_dir: patches
+" > Bender.yml
+cat > patches/0001-local.patch <<PATCH
+diff --git a/file.txt b/file.txt
+--- a/file.txt
++++ b/file.txt
+@@ -1,3 +1,3 @@
+-one
++uno
+ two
+ three
+PATCH
+$BENDER vendor init
+grep -q uno deps/up/file.txt
+
+# Patches reapply on top of the new revision.
+$BENDER vendor update up --rev $REV2
+grep -q "rev: \"$REV2\"" Bender.yml
+grep -q uno deps/up/file.txt
+grep -q FIVE deps/up/file.txt
+
+# Patches that no longer apply are reported.
+if $BENDER vendor update up --rev $REV3 &> log; then
+	cat log
+	echo "should fail" >&2
+	exit 1
+fi
+grep -q "0001-local.patch" log
+grep -q "rev: \"$REV3\"" Bender.yml
+grep -q ONE deps/up/file.txt
@@ -0,0 +1,67 @@
+#!/bin/bash
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")"
+BENDER="cargo run --"
+DIR="$(pwd)"/tmp/"$(basename $0 _test.sh)"
+[ ! -d "$DIR" ] || rm -rf "$DIR"
+mkdir -p "$DIR"
+cd "$DIR"
+
+mkdir foo
+cd foo
+git init -q
+echo "
+package:
+  name: foo
+" > Bender.yml
+git add Bender.yml
+git -c user.name=test -c user.email=test@example.com commit -q -m "v1.0.0"
+git tag v1.0.0
+echo "# 2.0.0" >> Bender.yml
+git -c user.name=test -c user.email=test@example.com commit -q -am "v2.0.0"
+git tag v2.0.0
+
+# A monorepo with two members sharing a dependency.
+mkdir -p "$DIR"/mono/ip/a "$DIR"/mono/ip/b
+cd "$DIR"/mono
+for pkg in a b; do
+	touch ip/$pkg/$pkg.sv
+	echo "
+package:
+  name: $pkg
+
+dependencies:
+  foo: { git: \"file://$DIR/foo\", version: \"1.0.0\" }
+
+sources:
+  - $pkg.sv
+" > ip/$pkg/Bender.yml
+done
+echo "
+package:
+  name: mono
+
+workspace:
+  members:
+    - ip/a
+    - ip/b
+" > Bender.yml
+
+# A single update covers all members.
+$BENDER update
+for pkg in a b; do
+	grep -A4 "^  $pkg:" Bender.lock | grep -q "Path: ip/$pkg"
+done
+grep -A2 '^  foo:' Bender.lock | grep -q 'version: 1.0.0'
+$BENDER sources -f > sources.json
+grep -q 'ip/a/a.sv' sources.json
+grep -q 'ip/b/b.sv' sources.json
+
+# Conflicting requirements of the members are reported.
+sed -i 's/version: "1.0.0"/version: "2.0.0"/' ip/b/Bender.yml
+if $BENDER update 2> stderr; then
+	echo "update succeeded despite conflicting members" >&2
+	exit 1
+fi
+grep -q 'conflicts with other requirements on dependency `foo`' stderr